@@ -1,8 +1,18 @@
-use crate::models::AdminEntry;
+use crate::models::{AdminEntry, RoleEntry, UserPermissions};
 use anyhow::Result;
 use reqwest;
 use serde_json;
 
+/// Permisos que comprueban los comandos del backend. Supabase puede definir más; esos solo
+/// los consulta el frontend con `check_permission`
+pub mod permissions {
+    pub const INSTANCES_PUBLISH: &str = "instances.publish";
+    pub const INSTANCES_SCHEDULE_BYPASS: &str = "instances.schedule_bypass";
+}
+
+/// Prefijo común de los errores de permisos para que el frontend pueda detectarlos
+pub const PERMISSION_DENIED: &str = "PERMISSION_DENIED";
+
 fn get_supabase_config() -> (String, String) {
     let url = std::env::var("SUPABASE_URL")
         .unwrap_or_else(|_| env!("SUPABASE_URL").to_string());
@@ -11,17 +21,30 @@ fn get_supabase_config() -> (String, String) {
     (url, key)
 }
 
-#[tauri::command]
-pub async fn check_is_admin(username: String) -> Result<bool, String> {
-    let (supabase_url, supabase_key) = get_supabase_config();
+fn is_supabase_configured(url: &str, key: &str) -> bool {
+    url != "https://your-project.supabase.co" && key != "your-anon-key"
+}
 
-    if supabase_url == "https://your-project.supabase.co" || supabase_key == "your-anon-key" {
-        log::warn!("Supabase not configured - denying admin access for user: {}", username);
-        return Ok(false);
-    }
+/// Permisos por defecto de cada rol, usados si la tabla `roles` no existe o no define el rol
+fn default_role_permissions(role: &str) -> Vec<String> {
+    let perms: &[&str] = match role {
+        "admin" => &["*"],
+        "moderator" => &["whitelist.read", "whitelist.write", "logs.read"],
+        "builder" => &["instances.beta"],
+        _ => &[],
+    };
+    perms.iter().map(|p| p.to_string()).collect()
+}
+
+async fn fetch_staff_entries(username: &str) -> Result<Vec<AdminEntry>, String> {
+    let (supabase_url, supabase_key) = get_supabase_config();
 
     let client = reqwest::Client::new();
-    let url = format!("{}/rest/v1/admins?minecraft_username=eq.{}", supabase_url, username);
+    let url = format!(
+        "{}/rest/v1/admins?minecraft_username=eq.{}",
+        supabase_url,
+        urlencoding::encode(username)
+    );
 
     let response = client
         .get(&url)
@@ -36,7 +59,7 @@ pub async fn check_is_admin(username: String) -> Result<bool, String> {
         })?;
 
     let status = response.status();
-    
+
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_default();
         log::error!("API error response: {}", error_text);
@@ -47,20 +70,172 @@ pub async fn check_is_admin(username: String) -> Result<bool, String> {
         log::error!("Failed to read response: {}", e);
         format!("Failed to read admins response: {}", e)
     })?;
-    
 
-    let entries: Vec<AdminEntry> = serde_json::from_str(&response_text).map_err(|e| {
+    serde_json::from_str(&response_text).map_err(|e| {
         log::error!("Failed to parse JSON: {}", e);
         log::error!("Raw response: {}", response_text);
         format!("Failed to parse admins response: {}", e)
-    })?;
+    })
+}
 
-    let is_admin = !entries.is_empty();
-    
-    if is_admin {
-    } else {
+/// Obtiene la definición de los roles desde la tabla `roles`.
+/// Si la tabla no existe o falla la petición se devuelve vacío y se usan los permisos por defecto
+async fn fetch_role_definitions(roles: &[String]) -> Vec<RoleEntry> {
+    if roles.is_empty() {
+        return Vec::new();
     }
 
-    Ok(is_admin)
+    let (supabase_url, supabase_key) = get_supabase_config();
+    let client = reqwest::Client::new();
+    let names: Vec<String> = roles.iter().map(|r| urlencoding::encode(r).into_owned()).collect();
+    let url = format!("{}/rest/v1/roles?name=in.({})", supabase_url, names.join(","));
+
+    let response = match client
+        .get(&url)
+        .header("apikey", &supabase_key)
+        .header("Authorization", &format!("Bearer {}", supabase_key))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            log::warn!("Failed to query roles table, using default role permissions: {}", e);
+            return Vec::new();
+        }
+    };
+
+    if !response.status().is_success() {
+        log::warn!("Roles API error {}, using default role permissions", response.status());
+        return Vec::new();
+    }
+
+    match response.json::<Vec<RoleEntry>>().await {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to parse roles response, using default role permissions: {}", e);
+            Vec::new()
+        }
+    }
 }
 
+/// Resuelve roles y permisos de un usuario a partir de Supabase
+pub async fn resolve_user_permissions(username: &str) -> Result<UserPermissions, String> {
+    let (supabase_url, supabase_key) = get_supabase_config();
+
+    if !is_supabase_configured(&supabase_url, &supabase_key) {
+        log::warn!("Supabase not configured - denying staff permissions for user: {}", username);
+        return Ok(UserPermissions { username: username.to_string(), ..Default::default() });
+    }
+
+    let entries = fetch_staff_entries(username).await?;
+
+    let mut roles: Vec<String> = Vec::new();
+    let mut granted: Vec<String> = Vec::new();
+    for entry in &entries {
+        // Las filas antiguas de `admins` no tienen rol: siguen siendo admins
+        let role = entry.role.clone().unwrap_or_else(|| "admin".to_string());
+        if !roles.contains(&role) {
+            roles.push(role);
+        }
+        if let Some(extra) = &entry.permissions {
+            granted.extend(extra.iter().cloned());
+        }
+    }
+
+    let definitions = fetch_role_definitions(&roles).await;
+    for role in &roles {
+        match definitions.iter().find(|d| &d.name == role) {
+            Some(definition) => granted.extend(definition.permissions.iter().cloned()),
+            None => granted.extend(default_role_permissions(role)),
+        }
+    }
+
+    granted.sort();
+    granted.dedup();
+
+    Ok(UserPermissions { username: username.to_string(), roles, permissions: granted })
+}
+
+pub fn permission_denied_error(username: &str, permission: &str) -> String {
+    format!("{}: user '{}' lacks permission '{}'", PERMISSION_DENIED, username, permission)
+}
+
+/// Usuario de la sesión activa, que es el único cuyos permisos cuentan
+fn active_username(app_handle: &tauri::AppHandle) -> Result<String, String> {
+    let session_manager = crate::sessions::SessionManager::new(app_handle)
+        .map_err(|e| format!("Failed to initialize session manager: {}", e))?;
+    let session = session_manager.get_active_session()
+        .map_err(|e| format!("Failed to get active session: {}", e))?
+        .ok_or_else(|| format!("{}: no active session", PERMISSION_DENIED))?;
+    Ok(session.username)
+}
+
+/// Guard para comandos: exige que la cuenta con sesión activa tenga el permiso indicado.
+/// Devuelve los permisos resueltos para que el comando pueda comprobar otros sin repetir la consulta
+pub async fn require_permission(app_handle: &tauri::AppHandle, permission: &str) -> Result<UserPermissions, String> {
+    require_user_permission(&active_username(app_handle)?, permission).await
+}
+
+pub async fn require_user_permission(username: &str, permission: &str) -> Result<UserPermissions, String> {
+    let perms = resolve_user_permissions(username).await?;
+    if !perms.has(permission) {
+        log::warn!("Permission '{}' denied for user: {}", permission, username);
+        return Err(permission_denied_error(username, permission));
+    }
+    Ok(perms)
+}
+
+#[tauri::command]
+pub async fn check_is_admin(username: String) -> Result<bool, String> {
+    let perms = resolve_user_permissions(&username).await?;
+    Ok(perms.is_admin())
+}
+
+/// Permisos de la cuenta con sesión activa, los mismos que comprueba `require_permission`
+#[tauri::command]
+pub async fn get_user_permissions(app_handle: tauri::AppHandle) -> Result<UserPermissions, String> {
+    resolve_user_permissions(&active_username(&app_handle)?).await
+}
+
+#[tauri::command]
+pub async fn check_permission(permission: String, app_handle: tauri::AppHandle) -> Result<bool, String> {
+    let perms = resolve_user_permissions(&active_username(&app_handle)?).await?;
+    Ok(perms.has(&permission))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn granted(permissions: &[&str]) -> UserPermissions {
+        UserPermissions {
+            username: "Steve".to_string(),
+            roles: Vec::new(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn wildcards_grant_whole_namespaces() {
+        assert!(granted(&["*"]).has(permissions::INSTANCES_PUBLISH));
+        assert!(granted(&["*"]).has("whitelist.write"));
+        let perms = granted(&["instances.*"]);
+        assert!(perms.has(permissions::INSTANCES_PUBLISH));
+        assert!(perms.has(permissions::INSTANCES_SCHEDULE_BYPASS));
+        assert!(perms.has("instances.beta.early"));
+    }
+
+    #[test]
+    fn permissions_do_not_match_by_prefix() {
+        assert!(!granted(&[]).has(permissions::INSTANCES_PUBLISH));
+        let perms = granted(&["instances.*", "logs.read"]);
+        assert!(!perms.has("instances"));
+        assert!(!perms.has("instancesx.publish"));
+        assert!(!perms.has("whitelist.read"));
+        assert!(!perms.has("logs.read.all"));
+        assert!(!perms.has("logs"));
+        assert!(!granted(&["instances.publish"]).has("instances.*"));
+        assert!(!granted(&["instances.publish"]).has(permissions::INSTANCES_SCHEDULE_BYPASS));
+    }
+}
@@ -0,0 +1,567 @@
+use crate::version_resolver::ResolvedVersion;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Sistema y features contra los que se evalúan las `rules` de un JSON de versión
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+    /// Nombre del sistema tal como lo usa Mojang: "windows", "osx" o "linux"
+    pub os_name: String,
+    pub os_arch: String,
+    pub os_version: String,
+    pub features: HashMap<String, bool>,
+}
+
+impl RuleContext {
+    pub fn current() -> Self {
+        let os_name = if cfg!(target_os = "windows") {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "osx"
+        } else {
+            "linux"
+        };
+        // Las reglas con arch "x86" se refieren a una JVM de 32 bits
+        let os_arch = match std::env::consts::ARCH {
+            "x86" => "x86",
+            "aarch64" => "arm64",
+            _ => "x86_64",
+        };
+        Self {
+            os_name: os_name.to_string(),
+            os_arch: os_arch.to_string(),
+            os_version: sysinfo::System::os_version().unwrap_or_default(),
+            features: HashMap::new(),
+        }
+    }
+
+    pub fn feature(mut self, name: &str, enabled: bool) -> Self {
+        self.features.insert(name.to_string(), enabled);
+        self
+    }
+}
+
+fn rule_matches(rule: &Value, ctx: &RuleContext) -> bool {
+    if let Some(os) = rule.get("os") {
+        if let Some(name) = os.get("name").and_then(|v| v.as_str()) {
+            if name != ctx.os_name {
+                return false;
+            }
+        }
+        if let Some(arch) = os.get("arch").and_then(|v| v.as_str()) {
+            if arch != ctx.os_arch {
+                return false;
+            }
+        }
+        if let Some(version) = os.get("version").and_then(|v| v.as_str()) {
+            match regex::Regex::new(version) {
+                Ok(re) if re.is_match(&ctx.os_version) => {}
+                Ok(_) => return false,
+                Err(e) => {
+                    log::warn!("Invalid os.version rule {}: {}", version, e);
+                    return false;
+                }
+            }
+        }
+    }
+    if let Some(features) = rule.get("features").and_then(|v| v.as_object()) {
+        for (name, expected) in features {
+            let enabled = ctx.features.get(name).copied().unwrap_or(false);
+            if Some(enabled) != expected.as_bool() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Semántica del launcher oficial: sin reglas se permite; con reglas manda la última que encaje
+pub fn rules_allow(rules: Option<&Value>, ctx: &RuleContext) -> bool {
+    let rules = match rules.and_then(|r| r.as_array()) {
+        Some(r) if !r.is_empty() => r,
+        _ => return true,
+    };
+    let mut allowed = false;
+    for rule in rules {
+        if rule_matches(rule, ctx) {
+            allowed = rule.get("action").and_then(|v| v.as_str()) == Some("allow");
+        }
+    }
+    allowed
+}
+
+/// Plantillas de `arguments.game`/`arguments.jvm` que pasan las reglas, sin sustituir
+fn collect_arguments(list: Option<&Value>, ctx: &RuleContext, out: &mut Vec<String>) {
+    let Some(list) = list.and_then(|l| l.as_array()) else {
+        return;
+    };
+    for arg in list {
+        if let Some(s) = arg.as_str() {
+            out.push(s.to_string());
+            continue;
+        }
+        if !rules_allow(arg.get("rules"), ctx) {
+            continue;
+        }
+        match arg.get("value") {
+            Some(Value::String(s)) => out.push(s.clone()),
+            Some(Value::Array(values)) => {
+                out.extend(values.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Si algún argumento de la versión depende de `feature` (p. ej. `is_quick_play_multiplayer`)
+pub fn declares_feature(version: &ResolvedVersion, feature: &str) -> bool {
+    let Some(arguments) = &version.arguments else { return false };
+    ["game", "jvm"]
+        .iter()
+        .filter_map(|kind| arguments.get(*kind).and_then(|l| l.as_array()))
+        .flatten()
+        .filter_map(|arg| arg.get("rules").and_then(|r| r.as_array()))
+        .flatten()
+        .any(|rule| rule.get("features").and_then(|f| f.get(feature)).is_some())
+}
+
+/// Argumentos JVM que el launcher oficial añade a las versiones sin `arguments.jvm` (anteriores a 1.13)
+const LEGACY_JVM_ARGUMENTS: &[&str] = &[
+    "-Djava.library.path=${natives_directory}",
+    "-Dminecraft.launcher.brand=${launcher_name}",
+    "-Dminecraft.launcher.version=${launcher_version}",
+    "-cp",
+    "${classpath}",
+];
+
+/// Plantillas de argumentos de una versión ya evaluadas contra las reglas
+#[derive(Debug, Clone, Default)]
+pub struct ArgumentTemplates {
+    pub jvm: Vec<String>,
+    pub game: Vec<String>,
+    /// La versión usa `minecraftArguments` (sin reglas de resolución ni `-cp` propio)
+    pub legacy: bool,
+}
+
+impl ArgumentTemplates {
+    pub fn from_version(version: &ResolvedVersion, ctx: &RuleContext) -> Self {
+        let mut templates = ArgumentTemplates::default();
+        if let Some(arguments) = &version.arguments {
+            collect_arguments(arguments.get("jvm"), ctx, &mut templates.jvm);
+            collect_arguments(arguments.get("game"), ctx, &mut templates.game);
+            return templates;
+        }
+
+        templates.legacy = true;
+        templates.jvm = LEGACY_JVM_ARGUMENTS.iter().map(|s| s.to_string()).collect();
+        if let Some(legacy) = &version.minecraft_arguments {
+            templates.game = legacy.split_whitespace().map(|s| s.to_string()).collect();
+        }
+        templates
+    }
+}
+
+/// Sustituye cada `${clave}` conocida. Las desconocidas se dejan tal cual
+pub fn substitute(template: &str, vars: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let key = &after[..end];
+                match vars.get(key) {
+                    Some(value) => result.push_str(value),
+                    None => {
+                        log::debug!("Unknown launch placeholder ${{{}}}", key);
+                        result.push_str(&rest[start..start + 2 + end + 1]);
+                    }
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Quita `-cp ${classpath}` (el classpath se pasa aparte) y deja en `-p` solo los módulos que existen
+pub fn finalize_jvm_arguments(args: Vec<String>, classpath_separator: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::with_capacity(args.len());
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-cp" || arg == "-classpath" {
+            iter.next();
+            continue;
+        }
+        if arg == "-p" || arg == "--module-path" {
+            let Some(modules) = iter.next() else { continue };
+            let existing: Vec<String> = modules
+                .split(classpath_separator)
+                .map(|p| {
+                    let p = p.trim();
+                    if cfg!(target_os = "windows") {
+                        p.strip_prefix("\\\\?\\").unwrap_or(p).replace('/', "\\")
+                    } else {
+                        p.to_string()
+                    }
+                })
+                .filter(|p| Path::new(p).exists())
+                .collect();
+            if !existing.is_empty() {
+                result.push(arg);
+                result.push(existing.join(classpath_separator));
+            }
+            continue;
+        }
+        if !arg.trim().is_empty() {
+            result.push(arg);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SEPARATOR: &str = if cfg!(target_os = "windows") { ";" } else { ":" };
+
+    fn resolve(name: &str, ids: &[&str]) -> (PathBuf, ResolvedVersion) {
+        crate::test_fixtures::fixture_version(&format!("arguments-{}", name), ids)
+    }
+
+    fn ctx(os_name: &str, os_arch: &str, os_version: &str) -> RuleContext {
+        RuleContext {
+            os_name: os_name.to_string(),
+            os_arch: os_arch.to_string(),
+            os_version: os_version.to_string(),
+            features: HashMap::new(),
+        }
+    }
+
+    fn linux() -> RuleContext {
+        ctx("linux", "x86_64", "6.1.0")
+            .feature("has_custom_resolution", true)
+            .feature("is_demo_user", false)
+    }
+
+    fn vars(version: &ResolvedVersion, library_directory: &Path) -> HashMap<String, String> {
+        let library_directory = library_directory.to_string_lossy().to_string();
+        [
+            ("auth_player_name", "Steve"),
+            ("auth_uuid", "069a79f444e94726a5befca90e38aaf5"),
+            ("auth_access_token", "token"),
+            ("auth_xuid", "0"),
+            ("clientid", ""),
+            ("user_type", "msa"),
+            ("version_name", version.id.as_str()),
+            ("version_type", version.version_type.as_str()),
+            ("game_directory", "/game"),
+            ("assets_root", "/assets"),
+            ("assets_index_name", "5"),
+            ("resolution_width", "854"),
+            ("resolution_height", "480"),
+            ("natives_directory", "/natives"),
+            ("library_directory", library_directory.as_str()),
+            ("classpath", "/cp"),
+            ("classpath_separator", SEPARATOR),
+            ("launcher_name", "KindlyKlanKlient"),
+            ("launcher_version", "1.0.0"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    /// argv JVM y de juego tal como los monta el plan de lanzamiento
+    fn argv(version: &ResolvedVersion, ctx: &RuleContext, library_directory: &Path) -> (Vec<String>, Vec<String>) {
+        let templates = ArgumentTemplates::from_version(version, ctx);
+        let vars = vars(version, library_directory);
+        let jvm = templates.jvm.iter().map(|a| substitute(a, &vars)).collect();
+        let game = templates.game.iter().map(|a| substitute(a, &vars)).collect();
+        (finalize_jvm_arguments(jvm, SEPARATOR), game)
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    const VANILLA_JVM: &[&str] = &[
+        "-Djava.library.path=/natives",
+        "-Djna.tmpdir=/natives",
+        "-Dorg.lwjgl.system.SharedLibraryExtractPath=/natives",
+        "-Dio.netty.native.workdir=/natives",
+        "-Dminecraft.launcher.brand=KindlyKlanKlient",
+        "-Dminecraft.launcher.version=1.0.0",
+    ];
+
+    fn vanilla_game(version_name: &str) -> Vec<String> {
+        strings(&[
+            "--username", "Steve",
+            "--version", version_name,
+            "--gameDir", "/game",
+            "--assetsDir", "/assets",
+            "--assetIndex", "5",
+            "--uuid", "069a79f444e94726a5befca90e38aaf5",
+            "--accessToken", "token",
+            "--clientId", "",
+            "--xuid", "0",
+            "--userType", "msa",
+            "--versionType", "release",
+            "--width", "854",
+            "--height", "480",
+        ])
+    }
+
+    #[test]
+    fn rules_allow_follows_the_last_matching_rule() {
+        let linux = ctx("linux", "x86_64", "6.1.0");
+        let osx = ctx("osx", "arm64", "14.4");
+        let all_but_osx = serde_json::json!([{ "action": "allow" }, { "action": "disallow", "os": { "name": "osx" } }]);
+        let only_osx = serde_json::json!([{ "action": "allow", "os": { "name": "osx" } }]);
+
+        assert!(rules_allow(None, &linux));
+        assert!(rules_allow(Some(&serde_json::json!([])), &linux));
+        assert!(rules_allow(Some(&all_but_osx), &linux));
+        assert!(!rules_allow(Some(&all_but_osx), &osx));
+        assert!(!rules_allow(Some(&only_osx), &linux));
+        assert!(rules_allow(Some(&only_osx), &osx));
+    }
+
+    #[test]
+    fn rules_allow_checks_arch_version_and_features() {
+        let x86 = serde_json::json!([{ "action": "allow", "os": { "arch": "x86" } }]);
+        let windows_10 = serde_json::json!([{ "action": "allow", "os": { "name": "windows", "version": "^10\\." } }]);
+        let invalid_version = serde_json::json!([{ "action": "allow", "os": { "version": "(" } }]);
+        let demo = serde_json::json!([{ "action": "allow", "features": { "is_demo_user": true } }]);
+
+        assert!(rules_allow(Some(&x86), &ctx("windows", "x86", "10.0")));
+        assert!(!rules_allow(Some(&x86), &ctx("windows", "x86_64", "10.0")));
+        assert!(rules_allow(Some(&windows_10), &ctx("windows", "x86_64", "10.0")));
+        assert!(!rules_allow(Some(&windows_10), &ctx("windows", "x86_64", "6.1")));
+        assert!(!rules_allow(Some(&invalid_version), &ctx("linux", "x86_64", "6.1.0")));
+        assert!(!rules_allow(Some(&demo), &ctx("linux", "x86_64", "6.1.0")));
+        assert!(!rules_allow(Some(&demo), &ctx("linux", "x86_64", "6.1.0").feature("is_demo_user", false)));
+        assert!(rules_allow(Some(&demo), &ctx("linux", "x86_64", "6.1.0").feature("is_demo_user", true)));
+    }
+
+    #[test]
+    fn collect_arguments_evaluates_rules_per_os() {
+        let (dir, version) = resolve("collect", &["1.20.1"]);
+        let jvm = version.arguments.as_ref().and_then(|a| a.get("jvm"));
+        let tail = [
+            "-Djava.library.path=${natives_directory}",
+            "-Djna.tmpdir=${natives_directory}",
+            "-Dorg.lwjgl.system.SharedLibraryExtractPath=${natives_directory}",
+            "-Dio.netty.native.workdir=${natives_directory}",
+            "-Dminecraft.launcher.brand=${launcher_name}",
+            "-Dminecraft.launcher.version=${launcher_version}",
+            "-cp",
+            "${classpath}",
+        ];
+
+        let mut windows = Vec::new();
+        collect_arguments(jvm, &ctx("windows", "x86", "10.0"), &mut windows);
+        let mut expected = strings(&[
+            "-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump",
+            "-Dos.name=Windows 10",
+            "-Dos.version=10.0",
+            "-Xss1M",
+        ]);
+        expected.extend(strings(&tail));
+        assert_eq!(windows, expected);
+
+        let mut osx = Vec::new();
+        collect_arguments(jvm, &ctx("osx", "arm64", "14.4"), &mut osx);
+        let mut expected = strings(&["-XstartOnFirstThread"]);
+        expected.extend(strings(&tail));
+        assert_eq!(osx, expected);
+
+        let mut none = Vec::new();
+        collect_arguments(None, &linux(), &mut none);
+        assert!(none.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn substitute_keeps_unknown_and_unterminated_placeholders() {
+        let vars: HashMap<String, String> = [("a", "1"), ("b", "2")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(substitute("${a}-${b}", &vars), "1-2");
+        assert_eq!(substitute("x${a}y", &vars), "x1y");
+        assert_eq!(substitute("${missing}", &vars), "${missing}");
+        assert_eq!(substitute("${a}${unterminated", &vars), "1${unterminated");
+        assert_eq!(substitute("$a {b}", &vars), "$a {b}");
+    }
+
+    #[test]
+    fn finalize_drops_classpath_blank_and_missing_modules() {
+        let dir = crate::test_fixtures::temp_dir("arguments-finalize");
+        let present = dir.join("present.jar");
+        std::fs::write(&present, b"").unwrap();
+        let present = present.to_string_lossy().to_string();
+        let missing = dir.join("missing.jar").to_string_lossy().to_string();
+
+        let args = strings(&["-Xss1M", "-cp", "/cp", " ", "-p", &format!("{}{}{}", missing, SEPARATOR, present), "-classpath", "/cp"]);
+        let expected_present = if cfg!(target_os = "windows") { present.replace('/', "\\") } else { present.clone() };
+        assert_eq!(finalize_jvm_arguments(args, SEPARATOR), strings(&["-Xss1M", "-p", &expected_present]));
+
+        let only_missing = strings(&["--module-path", &missing, "--add-modules", "ALL-MODULE-PATH"]);
+        assert_eq!(finalize_jvm_arguments(only_missing, SEPARATOR), strings(&["--add-modules", "ALL-MODULE-PATH"]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn vanilla_argv() {
+        let (dir, version) = resolve("vanilla", &["1.20.1"]);
+        let (jvm, game) = argv(&version, &linux(), Path::new("/libraries"));
+        assert_eq!(jvm, strings(VANILLA_JVM));
+        assert_eq!(game, vanilla_game("1.20.1"));
+        assert!(declares_feature(&version, "is_quick_play_multiplayer"));
+
+        let quick_play = linux().feature("is_quick_play_multiplayer", true);
+        let templates = ArgumentTemplates::from_version(&version, &quick_play);
+        assert!(!templates.legacy);
+        assert!(templates.game.ends_with(&strings(&["--quickPlayMultiplayer", "${quickPlayMultiplayer}"])));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn legacy_minecraft_arguments_argv() {
+        let (dir, version) = resolve("legacy", &["1.12.2"]);
+        let templates = ArgumentTemplates::from_version(&version, &linux());
+        assert!(templates.legacy);
+        assert!(!declares_feature(&version, "is_quick_play_multiplayer"));
+
+        let (jvm, game) = argv(&version, &linux(), Path::new("/libraries"));
+        assert_eq!(
+            jvm,
+            strings(&[
+                "-Djava.library.path=/natives",
+                "-Dminecraft.launcher.brand=KindlyKlanKlient",
+                "-Dminecraft.launcher.version=1.0.0",
+            ])
+        );
+        // Sin --clientId/--xuid ni resolución: la de las versiones antiguas la añade el plan
+        assert_eq!(
+            game,
+            strings(&[
+                "--username", "Steve",
+                "--version", "1.12.2",
+                "--gameDir", "/game",
+                "--assetsDir", "/assets",
+                "--assetIndex", "5",
+                "--uuid", "069a79f444e94726a5befca90e38aaf5",
+                "--accessToken", "token",
+                "--userType", "msa",
+                "--versionType", "release",
+            ])
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fabric_argv() {
+        let (dir, version) = resolve("fabric", &["fabric-loader-0.15.11-1.20.1", "1.20.1"]);
+        assert_eq!(version.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        let (jvm, game) = argv(&version, &linux(), Path::new("/libraries"));
+        let mut expected = strings(VANILLA_JVM);
+        expected.push("-DFabricMcEmu= net.minecraft.client.main.Main ".to_string());
+        assert_eq!(jvm, expected);
+        assert_eq!(game, vanilla_game("fabric-loader-0.15.11-1.20.1"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn forge_argv_keeps_existing_modules() {
+        let (dir, version) = resolve("forge", &["1.20.1-forge-47.2.0", "1.20.1"]);
+        let libraries = dir.join("libraries");
+        for module in [
+            "cpw/mods/bootstraplauncher/1.1.2/bootstraplauncher-1.1.2.jar",
+            "cpw/mods/securejarhandler/2.1.10/securejarhandler-2.1.10.jar",
+        ] {
+            let path = libraries.join(module);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, b"").unwrap();
+        }
+        let lib = libraries.to_string_lossy().to_string();
+        let module = |p: &str| {
+            let path = format!("{}/{}", lib, p);
+            if cfg!(target_os = "windows") { path.replace('/', "\\") } else { path }
+        };
+
+        let (jvm, game) = argv(&version, &linux(), &libraries);
+        let mut expected = strings(VANILLA_JVM);
+        expected.extend(strings(&[
+            "-Djava.net.preferIPv6Addresses=system",
+            "-DignoreList=bootstraplauncher,securejarhandler,asm-commons,asm-util,asm-analysis,asm-tree,asm,JarJarFileSystems,client-extra,fmlcore,javafmllanguage,lowcodelanguage,mclanguage,forge-,1.20.1-forge-47.2.0.jar",
+            "-DmergeModules=jna-5.10.0.jar,jna-platform-5.10.0.jar",
+            &format!("-DlibraryDirectory={}", lib),
+            "-p",
+            &format!(
+                "{}{}{}",
+                module("cpw/mods/bootstraplauncher/1.1.2/bootstraplauncher-1.1.2.jar"),
+                SEPARATOR,
+                module("cpw/mods/securejarhandler/2.1.10/securejarhandler-2.1.10.jar"),
+            ),
+            "--add-modules", "ALL-MODULE-PATH",
+            "--add-opens", "java.base/java.util.jar=cpw.mods.securejarhandler",
+            "--add-opens", "java.base/java.lang.invoke=cpw.mods.securejarhandler",
+            "--add-exports", "java.base/sun.security.util=cpw.mods.securejarhandler",
+            "--add-exports", "jdk.naming.dns/com.sun.jndi.dns=java.naming",
+        ]));
+        assert_eq!(jvm, expected);
+
+        let mut expected = vanilla_game("1.20.1-forge-47.2.0");
+        expected.extend(strings(&[
+            "--launchTarget", "forgeclient",
+            "--fml.forgeVersion", "47.2.0",
+            "--fml.mcVersion", "1.20.1",
+            "--fml.forgeGroup", "net.minecraftforge",
+            "--fml.mcpVersion", "20230612.114412",
+        ]));
+        assert_eq!(game, expected);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn neoforge_argv_drops_an_empty_module_path() {
+        let (dir, version) = resolve("neoforge", &["neoforge-20.4.237", "1.20.4"]);
+        assert_eq!(version.inherits, strings(&["neoforge-20.4.237", "1.20.4"]));
+        let (jvm, game) = argv(&version, &linux(), Path::new("/libraries"));
+
+        let mut expected = strings(VANILLA_JVM);
+        expected.extend(strings(&[
+            "-Djava.net.preferIPv6Addresses=system",
+            "-DignoreList=securejarhandler-,asm-,bootstraplauncher-,JarJarFileSystems-,transformerdiscoverer-,fancymodloader-,language-java-,language-lowcode-,language-minecraft-,neoforge-20.4.237.jar",
+            "-DlibraryDirectory=/libraries",
+            "--add-modules", "ALL-MODULE-PATH",
+            "--add-opens", "java.base/java.util.jar=cpw.mods.securejarhandler",
+            "--add-opens", "java.base/java.lang.invoke=cpw.mods.securejarhandler",
+            "--add-exports", "java.base/sun.security.util=cpw.mods.securejarhandler",
+            "--add-exports", "jdk.naming.dns/com.sun.jndi.dns=java.naming",
+        ]));
+        assert_eq!(jvm, expected);
+
+        let mut expected = vanilla_game("neoforge-20.4.237");
+        expected.extend(strings(&[
+            "--fml.neoForgeVersion", "20.4.237",
+            "--fml.fmlVersion", "2.0.17",
+            "--fml.mcVersion", "1.20.4",
+            "--fml.neoFormVersion", "20231207.154220",
+            "--launchTarget", "forgeclient",
+        ]));
+        assert_eq!(game, expected);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,360 @@
+use crate::models::{
+    DistributionInfo, DistributionManifest, FileEntry, InstanceFiles, InstanceInfo, InstanceManifest,
+    InstanceSummary, LaunchSettings, LocalInstanceMetadata, ManifestAuthoringOptions, ManifestAuthoringResult,
+    ModLoader,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Patrones incluidos por defecto: solo contenido que el jugador necesita para jugar el pack
+const DEFAULT_INCLUDE: &[&str] = &[
+    "mods/**",
+    "config/**",
+    "defaultconfigs/**",
+    "kubejs/**",
+    "resourcepacks/**",
+    "shaderpacks/**",
+    "options.txt",
+    "servers.dat",
+];
+
+/// Patrones excluidos siempre, además de los indicados por el autor
+const DEFAULT_EXCLUDE: &[&str] = &[
+    "instance_local.json",
+    ".manifest_history.json",
+    "**/*.log",
+    "**/.DS_Store",
+    "**/Thumbs.db",
+    "logs/**",
+    "crash-reports/**",
+    "saves/**",
+    "screenshots/**",
+    "versions/**",
+    "libraries/**",
+    "assets/**",
+    "natives/**",
+    ".natives/**",
+    ".version_id",
+];
+
+const DISTRIBUTION_MANIFEST_FILE: &str = "manifest.json";
+
+struct AuthoredFile {
+    rel_path: String,
+    source: PathBuf,
+}
+
+fn to_patterns(patterns: &[String]) -> Vec<glob::Pattern> {
+    patterns
+        .iter()
+        .filter_map(|p| match glob::Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                log::warn!("Ignoring invalid glob pattern '{}': {}", p, e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn collect_instance_files(instance_dir: &Path, include: &[String], exclude: &[String]) -> Result<Vec<AuthoredFile>, String> {
+    let include: Vec<String> = if include.is_empty() {
+        DEFAULT_INCLUDE.iter().map(|p| p.to_string()).collect()
+    } else {
+        include.to_vec()
+    };
+    let mut exclude_all: Vec<String> = DEFAULT_EXCLUDE.iter().map(|p| p.to_string()).collect();
+    exclude_all.extend(exclude.iter().cloned());
+
+    let include_patterns = to_patterns(&include);
+    let exclude_patterns = to_patterns(&exclude_all);
+
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(instance_dir) {
+        let entry = entry.map_err(|e| format!("Failed to walk instance directory: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel_path = entry
+            .path()
+            .strip_prefix(instance_dir)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        if !include_patterns.iter().any(|p| p.matches(&rel_path)) {
+            continue;
+        }
+        if exclude_patterns.iter().any(|p| p.matches(&rel_path)) {
+            continue;
+        }
+
+        files.push(AuthoredFile { rel_path, source: entry.path().to_path_buf() });
+    }
+
+    files.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    Ok(files)
+}
+
+fn hash_file(path: &Path) -> Result<(String, String, u64), String> {
+    use sha2::{Digest, Sha256};
+
+    let content = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    let sha256 = format!("{:x}", hasher.finalize());
+    let md5 = format!("{:x}", md5::compute(&content));
+
+    Ok((sha256, md5, content.len() as u64))
+}
+
+fn load_local_metadata(instance_dir: &Path) -> Result<LocalInstanceMetadata, String> {
+    let metadata_path = instance_dir.join("instance_local.json");
+    let content = std::fs::read_to_string(&metadata_path)
+        .map_err(|e| format!("Failed to read local instance metadata: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse local instance metadata: {}", e))
+}
+
+/// Las instancias locales antiguas solo guardan `fabric_version`
+fn mod_loader_from_metadata(metadata: &LocalInstanceMetadata) -> Option<ModLoader> {
+    if let Some(loader) = &metadata.mod_loader {
+        return Some(loader.clone());
+    }
+    if !metadata.fabric_version.is_empty() {
+        return Some(ModLoader { r#type: "fabric".to_string(), version: metadata.fabric_version.clone() });
+    }
+    None
+}
+
+/// Ruta de publicación para un archivo (`instances/<id>/<rel>`), también usada como `url` en el manifest
+pub fn published_file_url(instance_id: &str, rel_path: &str) -> String {
+    format!("instances/{}/{}", instance_id, rel_path.trim_start_matches('/'))
+}
+
+fn build_file_entry(instance_id: &str, rel_path: &str, sha256: String, md5: String, size: u64) -> FileEntry {
+    let name = rel_path.rsplit('/').next().unwrap_or(rel_path).to_string();
+    FileEntry {
+        name,
+        path: rel_path.to_string(),
+        url: published_file_url(instance_id, rel_path),
+        sha256,
+        md5: Some(md5),
+        size: Some(size),
+        required: Some(true),
+        target: Some(rel_path.to_string()),
+    }
+}
+
+/// Sólo los archivos directamente dentro de mods/, resourcepacks/ o shaderpacks/ se tratan como tales:
+/// el sync los coloca por nombre. Todo lo demás (incluidas subcarpetas) va como config con su ruta completa
+fn classify(rel_path: &str) -> &'static str {
+    let mut parts = rel_path.splitn(2, '/');
+    let first = parts.next().unwrap_or("");
+    let rest = parts.next().unwrap_or("");
+    if rest.is_empty() || rest.contains('/') {
+        return "configs";
+    }
+    match first {
+        "mods" => "mods",
+        "resourcepacks" => "resourcepacks",
+        "shaderpacks" => "shaderpacks",
+        _ => "configs",
+    }
+}
+
+fn upsert_distribution_manifest(output_dir: &Path, summary: InstanceSummary) -> Result<PathBuf, String> {
+    let manifest_path = output_dir.join(DISTRIBUTION_MANIFEST_FILE);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut manifest = if manifest_path.exists() {
+        let content = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read distribution manifest: {}", e))?;
+        serde_json::from_str::<DistributionManifest>(&content)
+            .map_err(|e| format!("Failed to parse distribution manifest: {}", e))?
+    } else {
+        DistributionManifest {
+            distribution: DistributionInfo {
+                name: "Kindly Klan".to_string(),
+                version: "1.0.0".to_string(),
+                description: String::new(),
+                base_url: String::new(),
+                last_updated: now.clone(),
+            },
+            instances: Vec::new(),
+        }
+    };
+
+    match manifest.instances.iter_mut().find(|i| i.id == summary.id) {
+        Some(existing) => {
+            // Conservar los campos que no se pueden deducir de la instancia local
+            let background_video = existing.background_video.take();
+            let available_from = existing.available_from.take();
+            let available_until = existing.available_until.take();
+            let icon = summary.icon.clone().or_else(|| existing.icon.take());
+            let background = summary.background.clone().or_else(|| existing.background.take());
+            *existing = InstanceSummary { icon, background, background_video, available_from, available_until, ..summary };
+        }
+        None => manifest.instances.push(summary),
+    }
+    manifest.distribution.last_updated = now;
+
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize distribution manifest: {}", e))?;
+    std::fs::write(&manifest_path, json)
+        .map_err(|e| format!("Failed to write distribution manifest: {}", e))?;
+
+    Ok(manifest_path)
+}
+
+/// Genera un directorio listo para subir al servidor de distribución a partir de una instancia local:
+/// `instances/<id>/instance.json`, `instances/<id>/checksums.json`, los archivos copiados y `manifest.json`
+#[tauri::command]
+pub async fn generate_instance_manifest(
+    local_instance_id: String,
+    output_dir: String,
+    options: ManifestAuthoringOptions,
+    app_handle: AppHandle,
+) -> Result<ManifestAuthoringResult, String> {
+    crate::admins::require_permission(&app_handle, crate::admins::permissions::INSTANCES_PUBLISH).await?;
+
+    crate::utils::validate_id("local instance", &local_instance_id)?;
+    let instance_dir = crate::local_instances::get_local_instances_dir()?.join(&local_instance_id);
+    if !instance_dir.exists() {
+        return Err(format!("Local instance not found: {}", local_instance_id));
+    }
+
+    let metadata = load_local_metadata(&instance_dir)?;
+    let instance_id = options.instance_id.clone().unwrap_or_else(|| metadata.id.clone());
+    // Acaba en `instances/<id>` del directorio de salida y en las URLs de los archivos
+    crate::utils::validate_id("instance", &instance_id)?;
+    let name = options.name.clone().unwrap_or_else(|| metadata.name.clone());
+    let description = options.description.clone().unwrap_or_default();
+    let mod_loader = mod_loader_from_metadata(&metadata);
+
+    log::info!("Generating instance manifest for '{}' from local instance {}", instance_id, local_instance_id);
+
+    let output_root = PathBuf::from(&output_dir);
+    let publish_dir = output_root.join("instances").join(&instance_id);
+    tokio::fs::create_dir_all(&publish_dir)
+        .await
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let files = collect_instance_files(&instance_dir, &options.include, &options.exclude)?;
+    let total = files.len();
+
+    let mut instance_files = InstanceFiles {
+        mods: Vec::new(),
+        configs: Vec::new(),
+        resourcepacks: Some(Vec::new()),
+        shaderpacks: Some(Vec::new()),
+    };
+    let mut checksums: HashMap<String, String> = HashMap::new();
+    let mut total_size: u64 = 0;
+
+    for (index, file) in files.iter().enumerate() {
+        let _ = app_handle.emit("manifest-authoring-progress", serde_json::json!({
+            "current": index,
+            "total": total,
+            "percentage": if total > 0 { (index as f32 / total as f32) * 100.0 } else { 100.0 },
+            "current_file": file.rel_path,
+        }));
+
+        let (sha256, md5, size) = hash_file(&file.source)?;
+        total_size += size;
+
+        let dest = publish_dir.join(&file.rel_path);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+        tokio::fs::copy(&file.source, &dest)
+            .await
+            .map_err(|e| format!("Failed to copy {}: {}", file.rel_path, e))?;
+
+        checksums.insert(file.rel_path.clone(), sha256.clone());
+        let entry = build_file_entry(&instance_id, &file.rel_path, sha256, md5, size);
+        match classify(&file.rel_path) {
+            "mods" => instance_files.mods.push(entry),
+            "resourcepacks" => instance_files.resourcepacks.get_or_insert_with(Vec::new).push(entry),
+            "shaderpacks" => instance_files.shaderpacks.get_or_insert_with(Vec::new).push(entry),
+            _ => instance_files.configs.push(entry),
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let manifest = InstanceManifest {
+        instance: InstanceInfo {
+            id: instance_id.clone(),
+            name: name.clone(),
+            description: description.clone(),
+            version: options.version.clone(),
+            minecraft_version: metadata.minecraft_version.clone(),
+            mod_loader: mod_loader.clone(),
+            icon: options.icon.clone(),
+            background: options.background.clone(),
+            server: options.server.clone(),
+            servers: options.servers.clone(),
+        },
+        files: instance_files,
+        launch_settings: LaunchSettings {
+            min_ram: options.min_ram.unwrap_or(2048),
+            recommended_ram: options.recommended_ram.unwrap_or(4096),
+            jvm_args: options.jvm_args.clone(),
+        },
+        ignored_files: options.ignored_files.clone(),
+    };
+
+    let manifest_path = publish_dir.join("instance.json");
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize instance manifest: {}", e))?;
+    tokio::fs::write(&manifest_path, manifest_json)
+        .await
+        .map_err(|e| format!("Failed to write instance manifest: {}", e))?;
+
+    let checksums_json = serde_json::to_string_pretty(&checksums)
+        .map_err(|e| format!("Failed to serialize checksums: {}", e))?;
+    tokio::fs::write(publish_dir.join("checksums.json"), checksums_json)
+        .await
+        .map_err(|e| format!("Failed to write checksums: {}", e))?;
+
+    let summary = InstanceSummary {
+        id: instance_id.clone(),
+        name,
+        description,
+        version: options.version.clone(),
+        minecraft_version: metadata.minecraft_version.clone(),
+        icon: options.icon.clone(),
+        background: options.background.clone(),
+        background_video: None,
+        last_updated: Some(now),
+        instance_url: format!("instances/{}/instance.json", instance_id),
+        mod_loader,
+        server: options.server.clone(),
+        available_from: None,
+        available_until: None,
+    };
+    let distribution_path = upsert_distribution_manifest(&output_root, summary)?;
+
+    let _ = app_handle.emit("manifest-authoring-progress", serde_json::json!({
+        "current": total,
+        "total": total,
+        "percentage": 100.0,
+        "current_file": "",
+    }));
+
+    log::info!("Instance manifest generated: {} files, {} bytes", total, total_size);
+
+    Ok(ManifestAuthoringResult {
+        instance_id,
+        output_dir: output_root.to_string_lossy().to_string(),
+        instance_manifest_path: manifest_path.to_string_lossy().to_string(),
+        distribution_manifest_path: distribution_path.to_string_lossy().to_string(),
+        file_count: total,
+        total_size,
+    })
+}
@@ -0,0 +1,293 @@
+use crate::models::{BackupConfig, BackupInfo};
+use crate::utils::validate_id;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Motivo de una copia. Solo las automáticas entran en la política de retención
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupReason {
+    Manual,
+    Launch,
+    Update,
+    Delete,
+}
+
+impl BackupReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupReason::Manual => "manual",
+            BackupReason::Launch => "launch",
+            BackupReason::Update => "update",
+            BackupReason::Delete => "delete",
+        }
+    }
+
+    fn enabled(self, config: &BackupConfig) -> bool {
+        match self {
+            BackupReason::Manual => true,
+            BackupReason::Launch => config.before_launch,
+            BackupReason::Update => config.before_update,
+            BackupReason::Delete => config.before_delete,
+        }
+    }
+}
+
+/// Fuera de la instancia: las copias tienen que sobrevivir a su borrado
+pub fn backups_dir(instance_id: &str) -> PathBuf {
+    crate::paths::data_root().join("backups").join(instance_id)
+}
+
+fn emit_progress(app: &AppHandle, operation: &str, instance_id: &str, backup_id: &str, done: usize, total: usize, current_file: &str) {
+    let percentage = if total == 0 { 100 } else { done * 100 / total };
+    let _ = app.emit("world-backup-progress", serde_json::json!({
+        "instance_id": instance_id,
+        "backup_id": backup_id,
+        "operation": operation,
+        "current": done,
+        "total": total,
+        "percentage": percentage,
+        "current_file": current_file
+    }));
+}
+
+/// Carpetas de `saves/` con level.dat, o solo `world` si se indica
+fn worlds_to_back_up(saves_dir: &Path, world: Option<&str>) -> Result<Vec<String>, String> {
+    if let Some(world) = world {
+        crate::worlds::validate_world_name(world)?;
+        if !saves_dir.join(world).join(crate::worlds::LEVEL_DAT).exists() {
+            return Err(format!("World {} not found", world));
+        }
+        return Ok(vec![world.to_string()]);
+    }
+    let Ok(entries) = std::fs::read_dir(saves_dir) else { return Ok(Vec::new()) };
+    let mut worlds: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.path().join(crate::worlds::LEVEL_DAT).exists())
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+    worlds.sort();
+    Ok(worlds)
+}
+
+fn create_backup_blocking(app: &AppHandle, instance_id: &str, world: Option<&str>, reason: BackupReason) -> Result<Option<BackupInfo>, String> {
+    let saves_dir = crate::local_instances::get_instance_directory_smart(instance_id).join("saves");
+    let worlds = worlds_to_back_up(&saves_dir, world)?;
+    if worlds.is_empty() {
+        return Ok(None);
+    }
+    if let Some(open) = worlds.iter().find(|w| crate::worlds::is_world_in_use(&saves_dir.join(w))) {
+        return Err(format!("World {} is open in the game (session.lock is held), close it before backing up", open));
+    }
+
+    let dir = backups_dir(instance_id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    let now = chrono::Local::now();
+    let scope: String = world
+        .unwrap_or("saves")
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let id = format!("{}-{}-{}", now.format("%Y%m%d-%H%M%S%3f"), reason.as_str(), scope);
+
+    // Se escribe con otro nombre y se renombra al terminar: un zip a medias nunca aparece en la lista
+    let part = dir.join(format!("{}.zip.part", id));
+    let written = crate::worlds::write_worlds_zip(&part, &saves_dir, &worlds, |done, total, file| {
+        if done == total || done % 50 == 0 {
+            emit_progress(app, "backup", instance_id, &id, done, total, file);
+        }
+    });
+    let size_bytes = match written {
+        Ok(size) => size,
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            return Err(e);
+        }
+    };
+    std::fs::rename(&part, dir.join(format!("{}.zip", id))).map_err(|e| format!("Failed to finish backup: {}", e))?;
+
+    let info = BackupInfo {
+        id: id.clone(),
+        instance_id: instance_id.to_string(),
+        world: world.map(|w| w.to_string()),
+        worlds,
+        reason: reason.as_str().to_string(),
+        created_at: now.to_rfc3339(),
+        size_bytes,
+    };
+    let json = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(format!("{}.json", id)), json).map_err(|e| format!("Failed to write backup info: {}", e))?;
+    log::info!("Backed up {} world(s) of {} to {} ({} bytes)", info.worlds.len(), instance_id, id, size_bytes);
+    Ok(Some(info))
+}
+
+fn load_backups(instance_id: &str) -> Vec<BackupInfo> {
+    let dir = backups_dir(instance_id);
+    let Ok(entries) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| std::fs::read_to_string(&p).ok())
+        .filter_map(|text| serde_json::from_str::<BackupInfo>(&text).ok())
+        .filter(|info| dir.join(format!("{}.zip", info.id)).exists())
+        .collect();
+    // Más recientes primero
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    backups
+}
+
+fn remove_backup_files(instance_id: &str, backup_id: &str) -> Result<(), String> {
+    let dir = backups_dir(instance_id);
+    std::fs::remove_file(dir.join(format!("{}.zip", backup_id))).map_err(|e| format!("Failed to delete backup {}: {}", backup_id, e))?;
+    let _ = std::fs::remove_file(dir.join(format!("{}.json", backup_id)));
+    Ok(())
+}
+
+/// Borra las copias automáticas que sobran según `keep_last` y `max_age_days`
+fn apply_retention(instance_id: &str, config: &BackupConfig) {
+    let now = chrono::Utc::now();
+    let automatic = load_backups(instance_id).into_iter().filter(|b| b.reason != BackupReason::Manual.as_str());
+    for (index, backup) in automatic.enumerate() {
+        let over_count = config.keep_last > 0 && index >= config.keep_last as usize;
+        let too_old = config.max_age_days.is_some_and(|days| {
+            chrono::DateTime::parse_from_rfc3339(&backup.created_at)
+                .is_ok_and(|created| now.signed_duration_since(created) > chrono::Duration::days(days as i64))
+        });
+        if over_count || too_old {
+            match remove_backup_files(instance_id, &backup.id) {
+                Ok(()) => log::info!("Removed old backup {} of {}", backup.id, instance_id),
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+    }
+}
+
+/// Copia de un mundo o de todos. `None` si la instancia no tiene mundos
+pub async fn create_backup(app: &AppHandle, instance_id: &str, world: Option<String>, reason: BackupReason) -> Result<Option<BackupInfo>, String> {
+    validate_id("instance", instance_id)?;
+    let config = crate::commands::load_backup_config().await?;
+    let app_handle = app.clone();
+    let id = instance_id.to_string();
+    let info = tauri::async_runtime::spawn_blocking(move || create_backup_blocking(&app_handle, &id, world.as_deref(), reason))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))??;
+    if reason != BackupReason::Manual {
+        apply_retention(instance_id, &config);
+    }
+    Ok(info)
+}
+
+/// Copia automática de `saves/` si está activada para este motivo
+pub async fn auto_backup(app: &AppHandle, instance_id: &str, reason: BackupReason) -> Result<(), String> {
+    let config = crate::commands::load_backup_config().await?;
+    if !reason.enabled(&config) {
+        return Ok(());
+    }
+    create_backup(app, instance_id, None, reason).await.map(|_| ())
+}
+
+/// Primer componente de la ruta de cada entrada: los mundos del zip
+fn archive_worlds(zip: &mut zip::ZipArchive<std::fs::File>) -> Vec<String> {
+    let mut worlds: Vec<String> = (0..zip.len())
+        .filter_map(|i| zip.by_index(i).ok()?.enclosed_name()?.components().next()?.as_os_str().to_str().map(|s| s.to_string()))
+        .collect();
+    worlds.sort();
+    worlds.dedup();
+    worlds
+}
+
+fn restore_backup_blocking(app: &AppHandle, backup_instance_id: &str, backup_id: &str, target_instance_id: &str) -> Result<Vec<String>, String> {
+    let zip_path = backups_dir(backup_instance_id).join(format!("{}.zip", backup_id));
+    let file = std::fs::File::open(&zip_path).map_err(|e| format!("Backup {} not found: {}", backup_id, e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read backup {}: {}", backup_id, e))?;
+
+    let instance_dir = crate::local_instances::get_instance_directory_smart(target_instance_id);
+    if !instance_dir.exists() {
+        return Err(format!("Instance {} not found", target_instance_id));
+    }
+    let saves_dir = instance_dir.join("saves");
+    let worlds = archive_worlds(&mut zip);
+    if let Some(open) = worlds.iter().find(|w| crate::worlds::is_world_in_use(&saves_dir.join(w))) {
+        return Err(format!("World {} is open in the game (session.lock is held), close it before restoring", open));
+    }
+
+    // Se extrae aparte y luego se cambia cada mundo de sitio, para no dejar un mundo a medias
+    let staging = saves_dir.join(format!(".restore-{}", backup_id));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).map_err(|e| format!("Failed to create restore directory: {}", e))?;
+    let total = zip.len();
+    for i in 0..total {
+        let mut entry = zip.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
+        let Some(rel) = entry.enclosed_name().map(|p| p.to_path_buf()) else { continue };
+        let outpath = staging.join(&rel);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&outpath).map_err(|e| format!("Create dir failed: {}", e))?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Create parent failed: {}", e))?;
+            }
+            let mut outfile = std::fs::File::create(&outpath).map_err(|e| format!("Create file failed: {}", e))?;
+            std::io::copy(&mut entry, &mut outfile).map_err(|e| format!("Write file failed: {}", e))?;
+        }
+        if i + 1 == total || (i + 1) % 50 == 0 {
+            emit_progress(app, "restore", target_instance_id, backup_id, i + 1, total, &rel.to_string_lossy());
+        }
+    }
+
+    for world in &worlds {
+        let target = saves_dir.join(world);
+        let previous = saves_dir.join(format!(".restore-{}-previous", world));
+        if target.exists() {
+            let _ = std::fs::remove_dir_all(&previous);
+            std::fs::rename(&target, &previous).map_err(|e| format!("Failed to replace world {}: {}", world, e))?;
+        }
+        if let Err(e) = std::fs::rename(staging.join(world), &target) {
+            if previous.exists() {
+                let _ = std::fs::rename(&previous, &target);
+            }
+            return Err(format!("Failed to restore world {}: {}", world, e));
+        }
+        let _ = std::fs::remove_dir_all(&previous);
+    }
+    let _ = std::fs::remove_dir_all(&staging);
+    log::info!("Restored backup {} of {} into {}: {}", backup_id, backup_instance_id, target_instance_id, worlds.join(", "));
+    Ok(worlds)
+}
+
+#[tauri::command]
+pub async fn create_world_backup(app_handle: AppHandle, instance_id: String, world: Option<String>) -> Result<BackupInfo, String> {
+    create_backup(&app_handle, &instance_id, world, BackupReason::Manual)
+        .await?
+        .ok_or_else(|| format!("Instance {} has no worlds to back up", instance_id))
+}
+
+#[tauri::command]
+pub async fn list_world_backups(instance_id: String) -> Result<Vec<BackupInfo>, String> {
+    validate_id("instance", &instance_id)?;
+    Ok(load_backups(&instance_id))
+}
+
+/// Restaura los mundos de una copia, sustituyendo los que tengan el mismo nombre. Con
+/// `target_instance_id` se restaura en otra instancia (p. ej. si la original se borró)
+#[tauri::command]
+pub async fn restore_world_backup(
+    app_handle: AppHandle,
+    instance_id: String,
+    backup_id: String,
+    target_instance_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    validate_id("instance", &instance_id)?;
+    validate_id("backup", &backup_id)?;
+    let target = target_instance_id.unwrap_or_else(|| instance_id.clone());
+    validate_id("instance", &target)?;
+    tauri::async_runtime::spawn_blocking(move || restore_backup_blocking(&app_handle, &instance_id, &backup_id, &target))
+        .await
+        .map_err(|e| format!("Restore task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn delete_world_backup(instance_id: String, backup_id: String) -> Result<(), String> {
+    validate_id("instance", &instance_id)?;
+    validate_id("backup", &backup_id)?;
+    remove_backup_files(&instance_id, &backup_id)
+}
@@ -8,37 +8,12 @@ use reqwest;
 use tauri::{AppHandle, State};
 use tauri::Emitter;
 use crate::UpdateState;
-use crate::models::{LauncherConfig, RamConfig, AdvancedConfig};
+use crate::models::{LauncherConfig, RamConfig, AdvancedConfig, JavaConfig, BackupConfig};
 use crate::{DistributionManifest, InstanceManifest};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use crate::models::{ForgeVersion, NeoForgeVersion};
 
-/// Verifica si un archivo debe ignorarse basándose en los patrones de ignorar :)
-/// Los patrones sin '/' solo coinciden con archivos en la raíz.
-/// Los patrones con '/' pueden coincidir con rutas completas.
-fn should_ignore_config_file(file_path: &str, ignored_patterns: &[String]) -> bool {
-    let is_root_file = !file_path.contains('/');
-    
-    if is_root_file {
-        crate::utils::matches_glob_patterns(file_path, ignored_patterns)
-    } else {
-        let matches_full_path = crate::utils::matches_glob_patterns(file_path, ignored_patterns);
-        if matches_full_path {
-            true
-        } else {
-            let has_simple_pattern = ignored_patterns.iter().any(|p| !p.contains('/'));
-            if has_simple_pattern {
-                // NO ignorar
-                false
-            } else {
-                // No hay patrones simples, solo comparar con la ruta completa
-                false
-            }
-        }
-    }
-}
-
 #[tauri::command]
 pub async fn greet(name: String) -> String {
     format!("Hello, {}! Welcome to Kindly Klan Klient!", name)
@@ -46,9 +21,7 @@ pub async fn greet(name: String) -> String {
 
 #[tauri::command]
 pub async fn create_instance_directory(instance_id: String, java_version: String) -> Result<String, String> {
-    let kindly_dir = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
+    let kindly_dir = crate::paths::data_root();
 
     let instance_dir = kindly_dir.join(&instance_id);
     let runtime_dir = kindly_dir.join("runtime");
@@ -109,9 +82,7 @@ pub async fn launch_game(version: String, session: AuthSession) -> Result<String
 
 #[tauri::command]
 pub async fn check_java_version(version: String) -> Result<String, String> {
-    let kindly_dir = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
+    let kindly_dir = crate::paths::data_root();
     let java_dir = kindly_dir.join("runtime").join(format!("java-{}", version));
     let java_path = if cfg!(target_os = "windows") { java_dir.join("bin").join("java.exe") } else { java_dir.join("bin").join("java") };
     if java_path.exists() { Ok("installed".to_string()) } else { Ok("not_installed".to_string()) }
@@ -135,14 +106,12 @@ pub async fn download_java(version: String, app_handle: AppHandle, state: State<
     // Notificar que comenzó la descarga
     let _ = app_handle.emit("java-download-started", serde_json::json!({ "version": version }));
     
-    let kindly_dir = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
+    let kindly_dir = crate::paths::data_root();
     let runtime_dir = kindly_dir.join("runtime");
     let java_dir = runtime_dir.join(format!("java-{}", version));
     fs::create_dir_all(&runtime_dir).await.map_err(|e| format!("Failed to create runtime directory: {}", e))?;
-    let (os, arch, extension) = if cfg!(target_os = "windows") { ("windows", "x64", "zip") } else if cfg!(target_os = "macos") { ("mac", "x64", "tar.gz") } else { ("linux", "x64", "tar.gz") };
-    let jre_url = format!("https://api.adoptium.net/v3/binary/latest/{}/ga/{}/{}/jdk/hotspot/normal/eclipse", version, os, arch);
+    let (_, _, extension) = crate::java_runtime::adoptium_platform()?;
+    let jre_url = crate::java_runtime::adoptium_url(&version)?;
     
     // Emitir progreso inicial
     let _ = app_handle.emit("java-download-progress", serde_json::json!({
@@ -199,30 +168,28 @@ pub async fn download_java(version: String, app_handle: AppHandle, state: State<
         "status": "Extrayendo Java..."
     }));
     
-    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    if java_dir.exists() { let _ = std::fs::remove_dir_all(&java_dir); }
-    if temp_file.extension().map_or(false, |e| e == "zip") {
-        let reader = std::fs::File::open(&temp_file).map_err(|e| format!("Open zip failed: {}", e))?;
-        let mut archive = zip::ZipArchive::new(reader).map_err(|e| format!("Read zip failed: {}", e))?;
-        let total_files = archive.len();
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
-            let outpath = runtime_dir.join(file.mangled_name());
-            if file.name().ends_with('/') { std::fs::create_dir_all(&outpath).map_err(|e| format!("Create dir failed: {}", e))?; } else {
-                if let Some(p) = outpath.parent() { std::fs::create_dir_all(p).map_err(|e| format!("Create parent failed: {}", e))?; }
-                let mut outfile = std::fs::File::create(&outpath).map_err(|e| format!("Create file failed: {}", e))?;
-                std::io::copy(&mut file, &mut outfile).map_err(|e| format!("Write file failed: {}", e))?;
-            }
-            // Actualizar progreso de extracción
-            let extraction_progress = 85 + ((i * 10) / total_files);
-            let _ = app_handle.emit("java-download-progress", serde_json::json!({
+    // Extraer (zip o tar.gz) y comprobar que bin/java arranca con la versión pedida
+    let expected_major = version.parse::<u32>().ok();
+    let archive = temp_file.clone();
+    let progress_handle = app_handle.clone();
+    let install_result = tauri::async_runtime::spawn_blocking(move || {
+        crate::java_runtime::install_java_archive(&archive, &java_dir, expected_major, &mut |current, total| {
+            // Actualizar progreso de extracción (el tar.gz no sabe cuántas entradas tiene)
+            let Some(total) = total.filter(|t| *t > 0) else { return };
+            let extraction_progress = 85 + ((current * 10) / total);
+            let _ = progress_handle.emit("java-download-progress", serde_json::json!({
                 "percentage": extraction_progress,
                 "status": "Extrayendo Java..."
             }));
+        })
+    }).await.map_err(|e| format!("Java extraction task failed: {}", e))?;
+    
+    let _ = std::fs::remove_file(&temp_file);
+    if let Err(e) = install_result {
+        if let Ok(mut downloading) = state.lock() {
+            *downloading = false;
         }
-    } else {
-        #[cfg(not(target_os = "windows"))]
-        { return Err("Unsupported archive format on this OS without external tools".to_string()); }
+        return Err(e);
     }
     
     // Emitir progreso final
@@ -231,15 +198,6 @@ pub async fn download_java(version: String, app_handle: AppHandle, state: State<
         "status": "Finalizando instalación..."
     }));
     
-    let all_entries = std::fs::read_dir(&runtime_dir).map_err(|e| format!("Failed to read runtime directory: {}", e))?.collect::<Result<Vec<_>, _>>().map_err(|e| format!("Failed to read directory entries: {}", e))?;
-    let extracted_dirs: Vec<_> = all_entries.into_iter().filter(|entry| { let path = entry.path(); path.is_dir() && path != java_dir }).map(|entry| entry.path()).collect();
-    if let Some(extracted_dir) = extracted_dirs.first() {
-        if java_dir.exists() { let _ = std::fs::remove_dir_all(&java_dir); }
-        std::fs::rename(extracted_dir, &java_dir).map_err(|e| format!("Failed to move Java directory: {}", e))?;
-        for dir in extracted_dirs.iter().skip(1) { let _ = std::fs::remove_dir_all(dir); }
-    } else { return Err("No Java directory found after extraction".to_string()); }
-    let _ = std::fs::remove_file(&temp_file);
-    
     // Emitir progreso completado
     let _ = app_handle.emit("java-download-progress", serde_json::json!({
         "percentage": 100,
@@ -257,7 +215,7 @@ pub async fn download_java(version: String, app_handle: AppHandle, state: State<
 
 #[tauri::command]
 pub async fn get_java_path(version: String) -> Result<String, String> {
-    let kindly_dir = std::env::var("USERPROFILE").map(|p| std::path::Path::new(&p).join(".kindlyklanklient")).unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
+    let kindly_dir = crate::paths::data_root();
     let java_dir = kindly_dir.join("runtime").join(format!("java-{}", version));
     let java_path = if cfg!(target_os = "windows") { java_dir.join("bin").join("java.exe") } else { java_dir.join("bin").join("java") };
     if java_path.exists() { Ok(java_path.to_string_lossy().to_string()) } else { Err(format!("Java executable not found at: {}", java_path.display())) }
@@ -334,9 +292,7 @@ use std::fs::File;
 // ============================================================================
 
 fn get_skins_directory() -> std::path::PathBuf {
-    std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient").join("skins"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient").join("skins"))
+    crate::paths::data_root().join("skins")
 }
 
 #[tauri::command]
@@ -481,17 +437,11 @@ pub async fn install_update(app_handle: AppHandle) -> Result<String, String> {
 }
 
 fn launcher_config_path() -> std::path::PathBuf {
-    let base = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
-    base.join("launcher.json")
+    crate::paths::data_root().join("launcher.json")
 }
 
 fn update_state_path() -> std::path::PathBuf {
-    let base = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
-    base.join("update_state.json")
+    crate::paths::data_root().join("update_state.json")
 }
 
 async fn load_launcher_config() -> LauncherConfig {
@@ -671,408 +621,35 @@ pub async fn download_instance_assets(
     app_handle: AppHandle,
     state: State<'_, Arc<Mutex<bool>>>
 ) -> Result<String, String> {
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
     // Establecer estado de descarga
     if let Ok(mut downloading) = state.lock() {
         *downloading = true;
     }
-    let base = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
-    let instance_dir = base.join(&instance_id);
-    let _ = tokio::fs::create_dir_all(instance_dir.join("libraries")).await;
-    let _ = tokio::fs::create_dir_all(instance_dir.join("mods")).await;
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 0,
-        "total": 1,
-        "percentage": 0,
-        "current_file": "",
-        "status": "Starting"
-    }));
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 0,
-        "total": 100,
-        "percentage": 1,
-        "current_file": "",
-        "status": "Version"
-    }));
-    crate::instances::ensure_minecraft_client_present(&instance_dir, &minecraft_version).await?;
-    
-    let mut instance_manifest_for_assets: Option<crate::models::InstanceManifest> = None;
-    let mut base_url_for_assets: Option<String> = None;
-    let mut installed_mod_loader_version_id: Option<String> = None;
-    if let (Some(base_ml), Some(inst_url_ml)) = (base_url.clone(), instance_url.clone()) {
-        base_url_for_assets = Some(base_ml.clone());
-        let full_url = if inst_url_ml.starts_with("http") { inst_url_ml } else { format!("{}/{}", base_ml.trim_end_matches('/'), inst_url_ml.trim_start_matches('/')) };
-        let client = reqwest::Client::new();
-        let response = client.get(&full_url).send().await.map_err(|e| format!("Failed to fetch instance details: {}", e))?;
-        if !response.status().is_success() { return Err(format!("HTTP error: {}", response.status())); }
-        let manifest: crate::models::InstanceManifest = response.json().await.map_err(|e| format!("Failed to parse instance JSON: {}", e))?;
-        instance_manifest_for_assets = Some(manifest.clone());
-        if let Some(mod_loader) = manifest.instance.mod_loader.as_ref() {
-            let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-                "current": 3,
-                "total": 100,
-                "percentage": 3,
-                "current_file": "",
-                "status": "ModLoader"
-            }));
-            installed_mod_loader_version_id = crate::instances::install_mod_loader(&minecraft_version, mod_loader, &instance_dir).await?;
-        }
-    }
+    // Una pre-descarga en segundo plano de la misma instancia se detiene: esta sincronización la completa
+    crate::predownload::cancel_predownload(&instance_id).await;
 
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 10,
-        "total": 100,
-        "percentage": 10,
-        "current_file": "",
-        "status": "Mojang"
-    }));
-    let _ = crate::instances::ensure_assets_present_with_progress(&app_handle, &instance_dir, &minecraft_version, None).await?;
-    
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 90,
-        "total": 100,
-        "percentage": 90,
-        "current_file": "",
-        "status": "Libraries"
-    }));
-    crate::instances::ensure_version_libraries(&instance_dir, &minecraft_version).await?;
-    
-    // Download mod loader libraries if applicable (using the version_id returned by install_mod_loader)
-    if let Some(version_id) = &installed_mod_loader_version_id {
-        crate::instances::ensure_mod_loader_libraries(&instance_dir, version_id).await?;
+    // Una actualización puede cambiar mods que tocan los mundos: copia antes de sincronizar
+    if let Err(e) = crate::backups::auto_backup(&app_handle, &instance_id, crate::backups::BackupReason::Update).await {
+        log::warn!("Backup before updating {} failed: {}", instance_id, e);
     }
-    
-    if let (Some(instance), Some(base)) = (instance_manifest_for_assets, base_url_for_assets) {
-        let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-            "current": 95,
-            "total": 100,
-            "percentage": 95,
-            "current_file": "",
-            "status": "Instance"
-        }));
-        // Cargar historial de manifest anterior
-        let previous_history = crate::instances::load_manifest_history(&instance_dir)?;
-        
-        // Obtener patrones de archivos ignorados
-        let ignored_patterns = instance.ignored_files.as_ref();
-        let empty_vec = Vec::<String>::new();
-        let ignored_mods = ignored_patterns.map(|p| &p.mods).unwrap_or(&empty_vec);
-        let ignored_configs = ignored_patterns.map(|p| &p.configs).unwrap_or(&empty_vec);
-        let ignored_resourcepacks = ignored_patterns.map(|p| &p.resourcepacks).unwrap_or(&empty_vec);
-        let ignored_shaderpacks = ignored_patterns.map(|p| &p.shaderpacks).unwrap_or(&empty_vec);
-        
-        use std::collections::HashSet;
-        let mut expected_mods: HashSet<String> = HashSet::new();
-        
-        // Preparar directorio de mods
-        let mods_dir = instance_dir.join("mods");
-        if let Some(parent) = mods_dir.parent() { 
-            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?; 
-        }
-        tokio::fs::create_dir_all(&mods_dir).await.map_err(|e| e.to_string())?;
-        
-        // Preparar lista de archivos a descargar en paralelo
-        let mut mods_to_download: Vec<(String, std::path::PathBuf)> = Vec::new();
-        for mod_file in &instance.files.mods {
-            expected_mods.insert(mod_file.name.clone());
-            let should_ignore = crate::utils::matches_glob_patterns(&mod_file.name, ignored_mods);
-            let file_url = if mod_file.url.starts_with("http") { 
-                mod_file.url.clone() 
-            } else { 
-                format!("{}/{}", base.trim_end_matches('/'), mod_file.url.trim_start_matches('/')) 
-            };
-            let target_path = mods_dir.join(&mod_file.name);
-            
-            if should_ignore {
-                // Archivo ignorado: solo descargar si NO existe (primera vez)
-                if !target_path.exists() {
-                    mods_to_download.push((file_url, target_path));
-                }
-            } else {
-                // Archivo no ignorado: verificar si necesita descarga
-            let mut needs_download = true;
-            if target_path.exists() {
-                if !mod_file.sha256.is_empty() {
-                        if crate::instances::verify_file_checksum(&target_path, &mod_file.sha256).is_ok() { 
-                            needs_download = false; 
-                        }
-                } else if let Some(md5) = mod_file.md5.as_ref() {
-                    if !md5.is_empty() {
-                            if crate::instances::verify_file_md5(&target_path, md5).is_ok() { 
-                                needs_download = false; 
-                            }
-                    }
-                }
-            }
-                if needs_download {
-                    mods_to_download.push((file_url, target_path));
-                }
-            }
-        }
-        
-        // Descargar mods en paralelo
-        if !mods_to_download.is_empty() {
-            use futures_util::stream::{self, StreamExt};
-            let parallel = num_cpus::get().saturating_mul(8).max(50).min(mods_to_download.len());
-            
-            // Cliente HTTP optimizado con pool de conexiones grande
-            let client = std::sync::Arc::new(reqwest::Client::builder()
-                .user_agent("KindlyKlanKlient/1.0")
-                .connect_timeout(std::time::Duration::from_secs(5))
-                .timeout(std::time::Duration::from_secs(120))
-                .pool_max_idle_per_host(50)
-                .pool_idle_timeout(std::time::Duration::from_secs(60))
-                .tcp_nodelay(true)
-                .build()
-                .map_err(|e| format!("Failed to build HTTP client: {}", e))?);
-
-            let results: Vec<Result<(), String>> = stream::iter(mods_to_download.into_iter())
-                .map(|(url, path)| {
-                    let client = client.clone();
-                    async move {
-                        crate::instances::download_file_with_retry_and_client(&client, &url, &path).await
-                    }
-                })
-                .buffer_unordered(parallel)
-                .collect()
-                .await;
-            
-            for result in results {
-                if let Err(e) = result {
-                    log::warn!("Error downloading mod: {}", e);
-                }
-            }
-        }
-        
-        if let Some(history) = &previous_history {
-            let mods_dir = instance_dir.join("mods");
-            if mods_dir.exists() {
-                for entry in std::fs::read_dir(&mods_dir).map_err(|e| e.to_string())? {
-                    let entry = entry.map_err(|e| e.to_string())?;
-                    if entry.file_type().map_err(|e| e.to_string())?.is_file() {
-                        let name = entry.file_name().to_string_lossy().to_string();
-                        // Solo borrar si estaba en el historial pero ya no está en el manifest actual
-                        if history.files.mods.contains(&name) && !expected_mods.contains(&name) {
-                            let should_ignore = crate::utils::matches_glob_patterns(&name, ignored_mods);
-                            if !should_ignore {
-                                let _ = std::fs::remove_file(entry.path());
-                            }
-                        }
-                    }
-                }
-            }
-        }
 
-        let mut expected_configs: HashSet<String> = HashSet::new();
-        let mut expected_root_files: HashSet<String> = HashSet::new();
-        
-        // Preparar lista de configs a descargar en paralelo
-        let mut configs_to_download: Vec<(String, std::path::PathBuf)> = Vec::new();
-        for config_file in &instance.files.configs {
-            let file_url = if config_file.url.starts_with("http") { 
-                config_file.url.clone() 
-            } else { 
-                format!("{}/{}", base.trim_end_matches('/'), config_file.url.trim_start_matches('/')) 
-            };
-            let mut rel = config_file.target.clone().unwrap_or(config_file.path.clone());
-            if rel == "config/options.txt" { rel = "options.txt".to_string(); }
-            if rel.starts_with("config/config/") { rel = rel.replacen("config/config/", "config/", 1); }
-            else if rel.starts_with("config/") { rel = rel.replacen("config/", "config/", 1); }
-            expected_configs.insert(rel.clone());
-            
-            if !rel.contains('/') {
-                expected_root_files.insert(rel.clone());
-            }
-            
-            let should_ignore = should_ignore_config_file(&rel, ignored_configs);
-            let target_path = instance_dir.join(&rel);
-            
-            // Crear directorio padre si es necesario
-            if let Some(parent) = target_path.parent() { 
-                tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?; 
-            }
-            
-            if should_ignore {
-                // Archivo ignorado: solo descargar si NO existe (primera vez)
-                if !target_path.exists() {
-                    configs_to_download.push((file_url, target_path));
-                }
-            } else {
-                // Archivo no ignorado: verificar si necesita descarga
-            let mut needs_download = true;
-            if target_path.exists() {
-                if !config_file.sha256.is_empty() {
-                        if crate::instances::verify_file_checksum(&target_path, &config_file.sha256).is_ok() { 
-                            needs_download = false; 
-                        }
-                } else if let Some(md5) = config_file.md5.as_ref() {
-                    if !md5.is_empty() {
-                            if crate::instances::verify_file_md5(&target_path, md5).is_ok() { 
-                                needs_download = false; 
-                            }
-                    }
-                }
-            }
-                if needs_download {
-                    configs_to_download.push((file_url, target_path));
-                }
-            }
-        }
-        
-        // Descargar configs en paralelo
-        if !configs_to_download.is_empty() {
-            use futures_util::stream::{self, StreamExt};
-            let parallel = num_cpus::get().saturating_mul(4).max(20).min(configs_to_download.len());
-            
-            // Cliente HTTP optimizado con pool de conexiones grande
-            let client = std::sync::Arc::new(reqwest::Client::builder()
-                .user_agent("KindlyKlanKlient/1.0")
-                .connect_timeout(std::time::Duration::from_secs(5))
-                .timeout(std::time::Duration::from_secs(120))
-                .pool_max_idle_per_host(50)
-                .pool_idle_timeout(std::time::Duration::from_secs(60))
-                .tcp_nodelay(true)
-                .build()
-                .map_err(|e| format!("Failed to build HTTP client: {}", e))?);
-
-            let results: Vec<Result<(), String>> = stream::iter(configs_to_download.into_iter())
-                .map(|(url, path)| {
-                    let client = client.clone();
-                    async move {
-                        crate::instances::download_file_with_retry_and_client(&client, &url, &path).await
-                    }
-                })
-                .buffer_unordered(parallel)
-                .collect()
-                .await;
-            
-            for result in results {
-                if let Err(e) = result {
-                    log::warn!("Error downloading config: {}", e);
-                }
-            }
-        }
-        
-        if let Some(history) = &previous_history {
-            let config_dir = instance_dir.join("config");
-            if config_dir.exists() {
-                for entry in walkdir::WalkDir::new(&config_dir) {
-                    let entry = entry.map_err(|e| e.to_string())?;
-                    if entry.file_type().is_file() {
-                        let rel_path = entry.path().strip_prefix(&instance_dir).map_err(|e| e.to_string())?.to_string_lossy().replace('\\', "/");
-                        // Solo borrar si estaba en el historial pero ya no está en el manifest actual
-                        if history.files.configs.contains(&rel_path) && !expected_configs.contains(&rel_path) {
-                            let should_ignore = should_ignore_config_file(&rel_path, ignored_configs);
-                            if !should_ignore {
-                                let _ = std::fs::remove_file(entry.path());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Limpiar archivos en la raíz: solo borrar si estaban en el historial pero ya no están en el manifest actual
-        if let Some(history) = &previous_history {
-            if let Ok(entries) = std::fs::read_dir(&instance_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_file() {
-                        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                            // Ignorar archivos del sistema (.manifest_history.json, etc.)
-                            if file_name.starts_with('.') {
-                                continue;
-                            }
-                            // Solo procesar archivos que estaban en el historial de root_files
-                            if history.files.root_files.contains(&file_name.to_string()) && !expected_root_files.contains(file_name) {
-                                let should_ignore = should_ignore_config_file(file_name, ignored_configs);
-                                if !should_ignore {
-                                    let _ = std::fs::remove_file(&path);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Limpiar resourcepacks: solo borrar si estaban en el historial pero ya no están en el manifest actual
-        if let Some(history) = &previous_history {
-            let mut expected_resourcepacks: HashSet<String> = HashSet::new();
-            if let Some(resourcepacks) = &instance.files.resourcepacks {
-                for rp_file in resourcepacks {
-                    expected_resourcepacks.insert(rp_file.name.clone());
-                }
-            }
-            
-            let resourcepacks_dir = instance_dir.join("resourcepacks");
-            if resourcepacks_dir.exists() {
-                if let Ok(entries) = std::fs::read_dir(&resourcepacks_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.is_file() {
-                            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                                if history.files.resourcepacks.contains(&file_name.to_string()) && !expected_resourcepacks.contains(file_name) {
-                                    let should_ignore = crate::utils::matches_glob_patterns(file_name, ignored_resourcepacks);
-                                    if !should_ignore {
-                                        let _ = std::fs::remove_file(&path);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Limpiar shaderpacks: solo borrar si estaban en el historial pero ya no están en el manifest actual
-        if let Some(history) = &previous_history {
-            let mut expected_shaderpacks: HashSet<String> = HashSet::new();
-            if let Some(shaderpacks) = &instance.files.shaderpacks {
-                for sp_file in shaderpacks {
-                    expected_shaderpacks.insert(sp_file.name.clone());
-                }
-            }
-            
-            let shaderpacks_dir = instance_dir.join("shaderpacks");
-            if shaderpacks_dir.exists() {
-                if let Ok(entries) = std::fs::read_dir(&shaderpacks_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.is_file() {
-                            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                                if history.files.shaderpacks.contains(&file_name.to_string()) && !expected_shaderpacks.contains(file_name) {
-                                    let should_ignore = crate::utils::matches_glob_patterns(file_name, ignored_shaderpacks);
-                                    if !should_ignore {
-                                        let _ = std::fs::remove_file(&path);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Guardar el nuevo historial después de procesar todos los archivos
-        crate::instances::save_manifest_history(&instance_dir, &instance).await?;
-    }
-    
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 100,
-        "total": 100,
-        "percentage": 100,
-        "current_file": "",
-        "status": "Completado"
-    }));
-    let _ = app_handle.emit("asset-download-completed", serde_json::json!({ "phase": "complete" }));
-    
+    let request = crate::sync_engine::InstanceSyncRequest {
+        instance_id,
+        minecraft_version,
+        base_url,
+        instance_url,
+    };
+    let result = crate::sync_engine::sync_instance(&app_handle, &request, &crate::sync_engine::SyncOptions::default()).await;
+
     // Limpiar estado de descarga
     if let Ok(mut downloading) = state.lock() {
         *downloading = false;
     }
-    
+    result?;
+
+    let _ = app_handle.emit("asset-download-completed", serde_json::json!({ "phase": "complete" }));
     Ok("ok".to_string())
 }
 
@@ -1082,6 +659,7 @@ pub async fn load_distribution_manifest(url: String) -> Result<DistributionManif
     let response = client.get(&url).send().await.map_err(|e| format!("Failed to fetch manifest: {}", e))?;
     if !response.status().is_success() { return Err(format!("HTTP error: {}", response.status())); }
     let manifest: DistributionManifest = response.json().await.map_err(|e| format!("Failed to parse manifest JSON: {}", e))?;
+    crate::scheduling::record_distribution_schedule(&manifest);
     Ok(manifest)
 }
 
@@ -1196,7 +774,7 @@ pub async fn get_minecraft_profile_safe(access_token: String) -> Result<ProfileR
 }
 
 pub fn get_required_java_version(minecraft_version: &str) -> String {
-    crate::launcher::get_required_java_version_for_minecraft(minecraft_version).to_string()
+    crate::java_runtime::java_requirement_for_version(minecraft_version).major_version.to_string()
 }
 
 #[tauri::command]
@@ -1240,6 +818,30 @@ pub async fn load_ram_config() -> Result<(f64, f64), String> {
     Ok((config.ram_config.min_ram, config.ram_config.max_ram))
 }
 
+#[tauri::command]
+pub async fn save_java_config(use_mojang_runtime: bool) -> Result<(), String> {
+    let mut config = load_launcher_config().await;
+    config.java_config = JavaConfig { use_mojang_runtime };
+    save_launcher_config_internal(&config).await
+}
+
+#[tauri::command]
+pub async fn load_java_config() -> Result<JavaConfig, String> {
+    Ok(load_launcher_config().await.java_config)
+}
+
+#[tauri::command]
+pub async fn save_backup_config(backup_config: BackupConfig) -> Result<(), String> {
+    let mut config = load_launcher_config().await;
+    config.backup_config = backup_config;
+    save_launcher_config_internal(&config).await
+}
+
+#[tauri::command]
+pub async fn load_backup_config() -> Result<BackupConfig, String> {
+    Ok(load_launcher_config().await.backup_config)
+}
+
 #[tauri::command]
 pub fn get_system_ram() -> Result<u32, String> {
     use sysinfo::System;
@@ -1518,12 +1120,7 @@ fn parse_neoforge_versions_from_xml(xml: &str, mc_version: &str) -> Result<Vec<N
 
 /// Obtiene la ruta del archivo de logs del frontend
 fn get_frontend_log_path() -> Result<std::path::PathBuf, String> {
-    let base = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map(|p| std::path::PathBuf::from(p))
-        .unwrap_or_else(|_| std::path::PathBuf::from("."));
-    
-    let log_dir = base.join(".kindlyklanklient").join("logs");
+    let log_dir = crate::paths::logs_dir();
     std::fs::create_dir_all(&log_dir)
         .map_err(|e| format!("Failed to create log directory: {}", e))?;
     
@@ -2127,6 +1724,10 @@ pub struct MinecraftWorld {
     pub name: String,
     pub path: String,
     pub icon_path: Option<String>,
+    /// Datos de level.dat; `None` si no se pudo leer
+    #[serde(flatten)]
+    pub metadata: Option<crate::models::WorldMetadata>,
+    pub size_bytes: u64,
 }
 
 #[tauri::command]
@@ -2138,7 +1739,7 @@ pub async fn list_minecraft_worlds(instance_id: String) -> Result<Vec<MinecraftW
         return Ok(Vec::new());
     }
     
-    let mut worlds = Vec::new();
+    let mut world_dirs = Vec::new();
     let mut entries = tokio::fs::read_dir(&saves_dir)
         .await
         .map_err(|e| format!("Failed to read saves directory: {}", e))?;
@@ -2147,33 +1748,50 @@ pub async fn list_minecraft_worlds(instance_id: String) -> Result<Vec<MinecraftW
         .map_err(|e| format!("Failed to read directory entry: {}", e))? {
         let path = entry.path();
         
-        if path.is_dir() {
-            let world_name = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
-            
-            // Buscar icono del mundo (icon.png en la raíz del mundo)
-            let icon_path = path.join("icon.png");
-            let icon = if icon_path.exists() {
-                Some(icon_path.to_string_lossy().to_string())
-            } else {
-                None
-            };
-            
-            // Verificar que es un mundo válido (debe tener level.dat)
-            let level_dat = path.join("level.dat");
-            if level_dat.exists() {
-                worlds.push(MinecraftWorld {
-                    name: world_name,
-                    path: path.to_string_lossy().to_string(),
-                    icon_path: icon,
-                });
-            }
+        // Verificar que es un mundo válido (debe tener level.dat)
+        if path.is_dir() && path.join(crate::worlds::LEVEL_DAT).exists() {
+            world_dirs.push(path);
         }
     }
     
-    Ok(worlds)
+    // Leer level.dat y medir cada mundo recorre muchos archivos: fuera del runtime async
+    tauri::async_runtime::spawn_blocking(move || {
+        world_dirs
+            .into_iter()
+            .map(|path| {
+                let world_name = path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                
+                // Buscar icono del mundo (icon.png en la raíz del mundo)
+                let icon_path = path.join("icon.png");
+                let icon = if icon_path.exists() {
+                    Some(icon_path.to_string_lossy().to_string())
+                } else {
+                    None
+                };
+                
+                let metadata = match crate::worlds::read_world_metadata(&path) {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        log::warn!("Failed to read level.dat of world {}: {}", world_name, e);
+                        None
+                    }
+                };
+                
+                MinecraftWorld {
+                    name: world_name,
+                    path: path.to_string_lossy().to_string(),
+                    icon_path: icon,
+                    metadata,
+                    size_bytes: crate::worlds::directory_size(&path),
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to list worlds: {}", e))
 }
 
 // ========== Copy folders between instances ==========
@@ -2233,6 +1851,9 @@ pub async fn copy_instance_folders(
             "percentage": 0
         }));
 
+        // Mods y packs se enlazan (reflink/hardlink); saves, configs... solo reflink o copia
+        let allow_hardlink = crate::dedup::SHAREABLE_FOLDERS.contains(&base_folder);
+
         // Copiar carpeta recursivamente
         if source_folder.is_dir() {
             // Crear directorio de destino
@@ -2241,18 +1862,14 @@ pub async fn copy_instance_folders(
                 .map_err(|e| format!("Failed to create target folder: {}", e))?;
 
             // Copiar archivos
-            copy_directory_recursive(&source_folder, &target_folder)
+            let (source, target) = (source_folder.clone(), target_folder.clone());
+            tauri::async_runtime::spawn_blocking(move || crate::dedup::copy_tree(&source, &target, allow_hardlink))
                 .await
+                .map_err(|e| format!("Copy task failed: {}", e))?
                 .map_err(|e| format!("Failed to copy folder {}: {}", folder, e))?;
         } else {
             // Es un archivo, copiarlo directamente
-            if let Some(parent) = target_folder.parent() {
-                tokio::fs::create_dir_all(parent)
-                    .await
-                    .map_err(|e| format!("Failed to create parent directory: {}", e))?;
-            }
-            tokio::fs::copy(&source_folder, &target_folder)
-                .await
+            crate::dedup::link_or_copy(&source_folder, &target_folder, allow_hardlink)
                 .map_err(|e| format!("Failed to copy file {}: {}", folder, e))?;
         }
 
@@ -2273,42 +1890,6 @@ pub async fn copy_instance_folders(
     Ok(format!("Successfully copied {} folder(s)", copied_count))
 }
 
-async fn copy_directory_recursive(
-    source: &std::path::Path,
-    target: &std::path::Path,
-) -> Result<(), String> {
-    copy_directory_recursive_impl(source, target).await
-}
-
-async fn copy_directory_recursive_impl(
-    source: &std::path::Path,
-    target: &std::path::Path,
-) -> Result<(), String> {
-    let mut entries = tokio::fs::read_dir(source)
-        .await
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
-
-    while let Some(entry) = entries.next_entry().await
-        .map_err(|e| format!("Failed to read directory entry: {}", e))? {
-        let path = entry.path();
-        let target_path = target.join(path.file_name().ok_or("Invalid file name")?);
-
-        if path.is_dir() {
-            tokio::fs::create_dir_all(&target_path)
-                .await
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
-            // Usar Box::pin para la recursión async
-            Box::pin(copy_directory_recursive_impl(&path, &target_path)).await?;
-        } else {
-            tokio::fs::copy(&path, &target_path)
-                .await
-                .map_err(|e| format!("Failed to copy file: {}", e))?;
-        }
-    }
-
-    Ok(())
-}
-
 // ========== Discord RPC Commands ==========
 
 #[tauri::command]
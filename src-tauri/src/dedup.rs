@@ -0,0 +1,376 @@
+use crate::models::DedupReport;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Índice sha256 de los archivos de contenido de todas las instancias
+const INDEX_FILE: &str = ".dedup_index.json";
+
+/// Carpetas de instancia cuyos archivos no se modifican en el sitio y pueden compartirse
+pub const SHAREABLE_FOLDERS: &[&str] = &["mods", "resourcepacks", "shaderpacks"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    Reflink,
+    Hardlink,
+    Copy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    sha256: String,
+    size: u64,
+    /// mtime en segundos; si cambia el archivo se vuelve a hashear
+    modified: u64,
+    #[serde(default)]
+    placement: Option<Placement>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DedupIndex {
+    files: HashMap<String, IndexedFile>,
+    #[serde(skip)]
+    refreshed: bool,
+}
+
+static INDEX: Lazy<Arc<Mutex<Option<DedupIndex>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+fn index_path() -> PathBuf {
+    crate::store::store_root().join(INDEX_FILE)
+}
+
+fn load_index() -> DedupIndex {
+    std::fs::read_to_string(index_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &DedupIndex) {
+    match serde_json::to_string(index) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(index_path(), json) {
+                log::warn!("Failed to write dedup index: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize dedup index: {}", e),
+    }
+}
+
+/// Ejecuta `f` con el índice cargado. Los cambios quedan en memoria hasta `persist_index`
+fn with_index<T>(f: impl FnOnce(&mut DedupIndex) -> T) -> T {
+    let mut guard = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    let index = guard.get_or_insert_with(load_index);
+    f(index)
+}
+
+/// Guarda en disco el índice en memoria, si se ha cargado
+fn save_loaded_index() {
+    let guard = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(index) = guard.as_ref() {
+        save_index(index);
+    }
+}
+
+/// El índice hashea y recorre carpetas: desde código async se usa a través de esto
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Dedup task failed: {}", e))
+}
+
+/// Carga el índice y lo pone al día con lo que hay en disco. Una sincronización lo llama una vez
+/// al empezar y `persist_index` una vez al terminar
+pub async fn begin_sync() -> Result<(), String> {
+    run_blocking(|| with_index(refresh_index)).await
+}
+
+pub async fn persist_index() -> Result<(), String> {
+    run_blocking(save_loaded_index).await
+}
+
+/// Olvida el índice en memoria (por ejemplo tras mover la carpeta de datos)
+pub fn reset_index() {
+    if let Ok(mut guard) = INDEX.lock() {
+        *guard = None;
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Some((metadata.len(), modified))
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Añade (o actualiza) un archivo en el índice, hasheándolo solo si cambió
+fn index_file(index: &mut DedupIndex, path: &Path, placement: Option<Placement>) -> Option<String> {
+    let (size, modified) = file_stamp(path)?;
+    let key = path_key(path);
+    if let Some(entry) = index.files.get_mut(&key) {
+        if entry.size == size && entry.modified == modified {
+            if placement.is_some() {
+                entry.placement = placement;
+            }
+            return Some(entry.sha256.clone());
+        }
+    }
+    let sha256 = sha256_file(path).ok()?;
+    index.files.insert(key, IndexedFile { sha256: sha256.clone(), size, modified, placement });
+    Some(sha256)
+}
+
+/// Recorre las carpetas compartibles de todas las instancias. Se hace una vez por sesión
+fn refresh_index(index: &mut DedupIndex) {
+    index.files.retain(|path, entry| {
+        matches!(file_stamp(Path::new(path)), Some((size, modified)) if size == entry.size && modified == entry.modified)
+    });
+    for instance_dir in crate::store::instance_dirs() {
+        for folder in SHAREABLE_FOLDERS {
+            let Ok(entries) = std::fs::read_dir(instance_dir.join(folder)) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() && !path.to_string_lossy().ends_with(".tmp") {
+                    index_file(index, &path, None);
+                }
+            }
+        }
+    }
+    index.refreshed = true;
+}
+
+/// Busca un archivo existente con el mismo contenido que no sea el propio destino
+fn find_source(index: &mut DedupIndex, sha256: &str, target: &Path) -> Option<PathBuf> {
+    if !index.refreshed {
+        refresh_index(index);
+    }
+    let target_key = path_key(target);
+    let candidates: Vec<(String, u64, u64)> = index
+        .files
+        .iter()
+        .filter(|(path, entry)| entry.sha256.eq_ignore_ascii_case(sha256) && **path != target_key)
+        .map(|(path, entry)| (path.clone(), entry.size, entry.modified))
+        .collect();
+    for (path, size, modified) in candidates {
+        // El índice puede estar desfasado: solo se usa si el archivo no ha cambiado desde que se hasheó
+        match file_stamp(Path::new(&path)) {
+            Some(stamp) if stamp == (size, modified) => return Some(PathBuf::from(path)),
+            _ => {
+                index.files.remove(&path);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, target: &Path) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    // FICLONE = _IOW(0x94, 9, int)
+    const FICLONE: libc::c_ulong = 0x40049409;
+    let src = std::fs::File::open(source)?;
+    let dst = std::fs::File::create(target)?;
+    let result = unsafe { libc::ioctl(dst.as_raw_fd(), FICLONE as _, src.as_raw_fd()) };
+    if result == 0 {
+        Ok(())
+    } else {
+        let error = std::io::Error::last_os_error();
+        drop(dst);
+        let _ = std::fs::remove_file(target);
+        Err(error)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn reflink(source: &Path, target: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let src = CString::new(source.as_os_str().as_bytes())?;
+    let dst = CString::new(target.as_os_str().as_bytes())?;
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// En Windows solo ReFS admite clonado de bloques; se pasa directamente al hardlink
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn reflink(_source: &Path, _target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "reflink not supported"))
+}
+
+/// Coloca `target` con el contenido de `source`: reflink, hardlink (si se permite) o copia.
+/// Se escribe en un temporal y se renombra para no dejar nunca un archivo a medias
+pub fn link_or_copy(source: &Path, target: &Path, allow_hardlink: bool) -> Result<Placement, String> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = target.with_file_name(format!("{}.kk-link.tmp", file_name));
+    let _ = std::fs::remove_file(&tmp);
+
+    let placement = if reflink(source, &tmp).is_ok() {
+        Placement::Reflink
+    } else if allow_hardlink && std::fs::hard_link(source, &tmp).is_ok() {
+        Placement::Hardlink
+    } else {
+        std::fs::copy(source, &tmp).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+        Placement::Copy
+    };
+
+    if let Err(e) = std::fs::rename(&tmp, target) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("Failed to place {}: {}", target.display(), e));
+    }
+    Ok(placement)
+}
+
+/// Intenta colocar un archivo conocido por su sha256 a partir de una copia que ya esté en disco.
+/// Devuelve `None` si no hay ninguna y hay que descargarlo
+pub fn place_existing(sha256: &str, target: &Path) -> Option<Placement> {
+    if sha256.is_empty() {
+        return None;
+    }
+    with_index(|index| {
+        let source = find_source(index, sha256, target)?;
+        match link_or_copy(&source, target, true) {
+            Ok(placement) => {
+                index_file(index, target, Some(placement));
+                log::debug!("Placed {} from {} ({:?})", target.display(), source.display(), placement);
+                Some(placement)
+            }
+            Err(e) => {
+                log::warn!("Dedup placement failed, falling back to download: {}", e);
+                None
+            }
+        }
+    })
+}
+
+/// Versión de `place_existing` para código async
+pub async fn place_existing_in_background(sha256: String, target: PathBuf) -> Option<Placement> {
+    run_blocking(move || place_existing(&sha256, &target)).await.ok().flatten()
+}
+
+/// Registra archivos recién descargados para que otras instancias puedan reutilizarlos
+pub async fn register_files(paths: Vec<PathBuf>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    run_blocking(move || {
+        with_index(|index| {
+            for path in &paths {
+                index_file(index, path, Some(Placement::Copy));
+            }
+        })
+    })
+    .await
+}
+
+/// Copia un árbol de directorios enlazando los archivos cuando se puede.
+/// `allow_hardlink` solo debe activarse para carpetas cuyo contenido no se edita en el sitio
+pub fn copy_tree(source: &Path, target: &Path, allow_hardlink: bool) -> Result<usize, String> {
+    let mut placed = Vec::new();
+    for entry in walkdir::WalkDir::new(source).into_iter().flatten() {
+        let rel = entry.path().strip_prefix(source).map_err(|e| e.to_string())?;
+        let dest = target.join(rel);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest).map_err(|e| format!("Failed to create directory: {}", e))?;
+        } else if entry.file_type().is_file() {
+            let placement = link_or_copy(entry.path(), &dest, allow_hardlink)?;
+            placed.push((dest, placement));
+        }
+    }
+    let count = placed.len();
+    if allow_hardlink {
+        with_index(|index| {
+            for (dest, placement) in &placed {
+                index_file(index, dest, Some(*placement));
+            }
+        });
+        save_loaded_index();
+    }
+    Ok(count)
+}
+
+fn build_report(index: &DedupIndex) -> DedupReport {
+    let mut by_hash: HashMap<&str, Vec<&IndexedFile>> = HashMap::new();
+    for entry in index.files.values() {
+        by_hash.entry(entry.sha256.as_str()).or_default().push(entry);
+    }
+
+    let mut report = DedupReport {
+        indexed_files: index.files.len(),
+        unique_files: by_hash.len(),
+        logical_bytes: 0,
+        saved_bytes: 0,
+        reflinked_files: 0,
+        hardlinked_files: 0,
+        duplicate_copy_bytes: 0,
+    };
+    for entries in by_hash.values() {
+        let mut copies = 0usize;
+        for entry in entries {
+            report.logical_bytes += entry.size;
+            match entry.placement {
+                Some(Placement::Reflink) => {
+                    report.reflinked_files += 1;
+                    report.saved_bytes += entry.size;
+                }
+                Some(Placement::Hardlink) => {
+                    report.hardlinked_files += 1;
+                    report.saved_bytes += entry.size;
+                }
+                _ => copies += 1,
+            }
+        }
+        // Una de las copias completas es el original; el resto se podría haber compartido
+        if copies > 1 {
+            report.duplicate_copy_bytes += entries[0].size * (copies as u64 - 1);
+        }
+    }
+    report
+}
+
+/// Resumen del espacio ahorrado al compartir archivos entre instancias
+#[tauri::command]
+pub async fn get_dedup_savings_report() -> Result<DedupReport, String> {
+    run_blocking(|| {
+        let report = with_index(|index| {
+            refresh_index(index);
+            build_report(index)
+        });
+        save_loaded_index();
+        report
+    })
+    .await
+}
@@ -0,0 +1,306 @@
+use crate::models::GameLogLine;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, LineWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Líneas que se guardan en memoria por instancia (las del último lanzamiento)
+const BUFFER_LINES: usize = 5000;
+
+/// Archivos de salida que se conservan por instancia
+const KEEP_LOG_FILES: usize = 20;
+
+const DEFAULT_TAIL_LINES: usize = 200;
+
+/// El evento `game-log` agrupa las líneas: se envía cada `EMIT_INTERVAL` o al juntar `EMIT_BATCH_LINES`
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+const EMIT_BATCH_LINES: usize = 500;
+
+static BUFFERS: Lazy<Mutex<HashMap<String, VecDeque<GameLogLine>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Layouts de consola de log4j que usan el juego y los mod loaders:
+/// vanilla `[12:34:56] [main/INFO]: msg`, Forge/NeoForge `[12:34:56] [main/INFO] [logger/MARKER]: msg`
+/// y Fabric `[12:34:56] [main/INFO] (logger) msg`
+static LAYOUT: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(
+        r"^\[(?P<time>[^\]]+)\] \[(?P<thread>.+?)/(?P<level>TRACE|DEBUG|INFO|WARN|ERROR|FATAL)\](?: \[(?P<logger>[^\]/]+)(?:/[^\]]*)?\]:| \((?P<short_logger>[^)]+)\)|:)? ?(?P<message>.*)$",
+    )
+    .expect("valid log4j layout regex")
+});
+
+/// Carpeta de la instancia con la salida de cada lanzamiento
+fn log_dir(instance_dir: &Path) -> PathBuf {
+    instance_dir.join("logs").join("game-output")
+}
+
+fn level_rank(level: &str) -> u8 {
+    match level {
+        "TRACE" => 0,
+        "DEBUG" => 1,
+        "INFO" => 2,
+        "WARN" => 3,
+        "ERROR" => 4,
+        "FATAL" => 5,
+        _ => 2,
+    }
+}
+
+/// Las líneas sin layout (trazas de excepciones, prints directos) heredan el nivel de la anterior si
+/// son continuación; si no, stdout es INFO y stderr WARN
+fn parse_line(instance_id: &str, seq: u64, stream: &str, raw: &str, previous_level: &mut Option<String>) -> GameLogLine {
+    if let Some(caps) = LAYOUT.captures(raw) {
+        let level = caps["level"].to_string();
+        *previous_level = Some(level.clone());
+        return GameLogLine {
+            instance_id: instance_id.to_string(),
+            seq,
+            stream: stream.to_string(),
+            time: Some(caps["time"].to_string()),
+            level,
+            thread: Some(caps["thread"].to_string()),
+            logger: caps.name("logger").or_else(|| caps.name("short_logger")).map(|m| m.as_str().to_string()),
+            message: caps["message"].to_string(),
+        };
+    }
+    let is_continuation = raw.starts_with(char::is_whitespace) || raw.starts_with("Caused by:");
+    let level = match previous_level {
+        Some(level) if is_continuation => level.clone(),
+        _ if stream == "stderr" => "WARN".to_string(),
+        _ => "INFO".to_string(),
+    };
+    GameLogLine {
+        instance_id: instance_id.to_string(),
+        seq,
+        stream: stream.to_string(),
+        time: None,
+        level,
+        thread: None,
+        logger: None,
+        message: raw.to_string(),
+    }
+}
+
+/// Deja sitio para el archivo nuevo borrando los más antiguos (los nombres llevan la fecha)
+fn prune_log_files(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    files.sort();
+    let excess = (files.len() + 1).saturating_sub(KEEP_LOG_FILES);
+    for path in files.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Nombre del archivo de un lanzamiento. Con milisegundos y PID dos lanzamientos seguidos no
+/// comparten archivo, y el orden alfabético sigue siendo el cronológico
+fn log_file_name(now: chrono::DateTime<chrono::Local>, pid: u32) -> String {
+    format!("game-{}-{}.log", now.format("%Y-%m-%d_%H-%M-%S-%3f"), pid)
+}
+
+/// Llama a `on_line` por cada línea de `reader`, sin el salto de línea. Los bytes que no son UTF-8
+/// válido (consolas de Windows, mods con otra codificación) se sustituyen en lugar de cortar la lectura
+fn read_lines(reader: impl Read, mut on_line: impl FnMut(&str)) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                on_line(line.trim_end_matches(['\n', '\r']));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                log::warn!("Stopped reading game output: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Salida de un lanzamiento: archivo propio, buffer en memoria y evento `game-log` con las líneas
+/// nuevas en lotes
+pub struct GameLogSession {
+    app: AppHandle,
+    instance_id: String,
+    file: Mutex<Option<LineWriter<std::fs::File>>>,
+    seq: AtomicU64,
+    /// Líneas pendientes de enviar a la interfaz
+    pending: Mutex<Vec<GameLogLine>>,
+}
+
+impl GameLogSession {
+    pub fn start(app: &AppHandle, instance_id: &str, instance_dir: &Path, pid: u32) -> Arc<Self> {
+        let dir = log_dir(instance_dir);
+        let _ = std::fs::create_dir_all(&dir);
+        prune_log_files(&dir);
+        let path = dir.join(log_file_name(chrono::Local::now(), pid));
+        // Sin archivo la salida sigue llegando al buffer y a la interfaz
+        let file = match std::fs::File::create(&path) {
+            Ok(file) => Some(LineWriter::new(file)),
+            Err(e) => {
+                log::warn!("Failed to create game log {}: {}", path.display(), e);
+                None
+            }
+        };
+        if let Ok(mut buffers) = BUFFERS.lock() {
+            buffers.insert(instance_id.to_string(), VecDeque::with_capacity(BUFFER_LINES));
+        }
+        log::info!("Game output of {} is written to {}", instance_id, path.display());
+        let session = Arc::new(Self {
+            app: app.clone(),
+            instance_id: instance_id.to_string(),
+            file: Mutex::new(file),
+            seq: AtomicU64::new(0),
+            pending: Mutex::new(Vec::new()),
+        });
+        // Envía lo pendiente cada poco mientras alguien siga leyendo la salida
+        let weak: Weak<Self> = Arc::downgrade(&session);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EMIT_INTERVAL);
+            match weak.upgrade() {
+                Some(session) => session.flush(),
+                None => break,
+            }
+        });
+        session
+    }
+
+    fn flush(&self) {
+        let lines = match self.pending.lock() {
+            Ok(mut pending) if !pending.is_empty() => std::mem::take(&mut *pending),
+            _ => return,
+        };
+        let _ = self.app.emit("game-log", lines);
+    }
+
+    fn record(&self, stream: &str, raw: &str, previous_level: &mut Option<String>) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(writer) = file.as_mut() {
+                let _ = writeln!(writer, "{}", raw);
+            }
+        }
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let line = parse_line(&self.instance_id, seq, stream, raw, previous_level);
+        if let Ok(mut buffers) = BUFFERS.lock() {
+            let buffer = buffers.entry(self.instance_id.clone()).or_default();
+            if buffer.len() >= BUFFER_LINES {
+                buffer.pop_front();
+            }
+            buffer.push_back(line.clone());
+        }
+        let batch_full = self.pending.lock().map(|mut pending| {
+            pending.push(line);
+            pending.len() >= EMIT_BATCH_LINES
+        });
+        if batch_full.unwrap_or(false) {
+            self.flush();
+        }
+    }
+
+    /// Lee `reader` línea a línea en un hilo propio hasta que el proceso cierre el pipe
+    pub fn capture(self: &Arc<Self>, reader: impl Read + Send + 'static, stream: &'static str) {
+        let session = Arc::clone(self);
+        std::thread::spawn(move || {
+            let mut previous_level = None;
+            read_lines(reader, |line| session.record(stream, line, &mut previous_level));
+            session.flush();
+        });
+    }
+}
+
+/// Si el launcher se reinició el buffer está vacío: se reconstruye desde el último archivo
+fn lines_from_latest_file(instance_id: &str) -> Vec<GameLogLine> {
+    let dir = log_dir(&crate::local_instances::get_instance_directory_smart(instance_id));
+    let Ok(entries) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let latest = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .max();
+    let Some(content) = latest.and_then(|p| std::fs::read(p).ok()) else { return Vec::new() };
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let skip = lines.len().saturating_sub(BUFFER_LINES);
+    let mut previous_level = None;
+    lines
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(seq, raw)| parse_line(instance_id, seq as u64, "stdout", raw, &mut previous_level))
+        .collect()
+}
+
+fn buffered_lines(instance_id: &str) -> Vec<GameLogLine> {
+    let buffered = BUFFERS
+        .lock()
+        .ok()
+        .and_then(|buffers| buffers.get(instance_id).map(|b| b.iter().cloned().collect::<Vec<_>>()));
+    match buffered {
+        Some(lines) => lines,
+        None => lines_from_latest_file(instance_id),
+    }
+}
+
+/// Últimas `lines` líneas de la salida del juego (del lanzamiento actual o del último)
+#[tauri::command]
+pub async fn get_game_log_tail(instance_id: String, lines: Option<usize>) -> Result<Vec<GameLogLine>, String> {
+    let mut all = buffered_lines(&instance_id);
+    let keep = lines.unwrap_or(DEFAULT_TAIL_LINES);
+    let skip = all.len().saturating_sub(keep);
+    Ok(all.split_off(skip))
+}
+
+/// Busca sin distinguir mayúsculas en mensaje, logger e hilo. `min_level` filtra por severidad (p. ej. "WARN")
+#[tauri::command]
+pub async fn search_game_log(
+    instance_id: String,
+    query: String,
+    min_level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<GameLogLine>, String> {
+    let query = query.to_lowercase();
+    let min_rank = min_level.map(|l| level_rank(&l.to_uppercase())).unwrap_or(0);
+    let contains = |value: Option<&String>| value.is_some_and(|v| v.to_lowercase().contains(&query));
+    let mut matches: Vec<GameLogLine> = buffered_lines(&instance_id)
+        .into_iter()
+        .filter(|line| level_rank(&line.level) >= min_rank)
+        .filter(|line| query.is_empty() || contains(Some(&line.message)) || contains(line.logger.as_ref()) || contains(line.thread.as_ref()))
+        .collect();
+    if let Some(limit) = limit {
+        let skip = matches.len().saturating_sub(limit);
+        matches.drain(..skip);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_utf8_does_not_stop_reading() {
+        let output: &[u8] = b"[12:00:00] [main/INFO]: ok\r\nTexto en cp1252: \xf1\xe1\n\nlast";
+        let mut lines = Vec::new();
+        read_lines(output, |line| lines.push(line.to_string()));
+        assert_eq!(lines, ["[12:00:00] [main/INFO]: ok", "Texto en cp1252: \u{FFFD}\u{FFFD}", "", "last"]);
+    }
+
+    #[test]
+    fn log_file_names_are_unique_and_sortable() {
+        let now = chrono::Local::now();
+        let first = log_file_name(now, 4242);
+        assert_ne!(first, log_file_name(now, 4243));
+        assert!(first < log_file_name(now + chrono::Duration::milliseconds(1), 1));
+        assert!(first.starts_with("game-") && first.ends_with("-4242.log"));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::models::InstanceManifest;
 use std::collections::HashMap;
 use crate::models::{FileEntry, InstanceAsset, ModLoader};
+use crate::sync_engine::SyncOptions;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 #[cfg(target_os = "windows")]
@@ -242,6 +243,12 @@ pub async fn download_file_with_retry_and_client(client: &reqwest::Client, url:
     Err(format!("Failed to download {} after {} attempts", url, MAX_RETRIES))
 }
 
+/// Descarga un archivo suelto respetando el límite de ancho de banda y la cancelación de la sincronización
+async fn download_with_sync_options(url: &str, file_path: &Path, options: &SyncOptions) -> Result<(), String> {
+    let client = crate::sync_engine::build_sync_client()?;
+    crate::sync_engine::download_with_options(&client, url, file_path, options).await
+}
+
 pub fn verify_file_checksum(file_path: &Path, expected_sha256: &str) -> Result<(), String> {
     use sha2::{Digest, Sha256};
 
@@ -292,7 +299,8 @@ pub fn load_manifest_history(instance_dir: &Path) -> Result<Option<crate::models
     Ok(Some(history))
 }
 
-pub async fn save_manifest_history(instance_dir: &Path, instance: &crate::models::InstanceManifest) -> Result<(), String> {
+/// `managed_servers` son las IPs que el launcher tiene puestas en `servers.dat`
+pub async fn save_manifest_history(instance_dir: &Path, instance: &crate::models::InstanceManifest, managed_servers: Vec<String>) -> Result<(), String> {
     let mut history_files = crate::models::ManifestHistoryFiles {
         mods: Vec::new(),
         configs: Vec::new(),
@@ -331,9 +339,23 @@ pub async fn save_manifest_history(instance_dir: &Path, instance: &crate::models
         }
     }
     
+    // Si el manifest de distribución no se ha cargado en esta sesión se conserva la ventana anterior
+    let (available_from, available_until) = match crate::scheduling::recorded_schedule(&instance.instance.id) {
+        Some(schedule) => schedule,
+        None => load_manifest_history(instance_dir)
+            .ok()
+            .flatten()
+            .map(|h| (h.available_from, h.available_until))
+            .unwrap_or_default(),
+    };
+
     let history = crate::models::ManifestHistory {
         last_updated: chrono::Utc::now().to_rfc3339(),
         files: history_files,
+        server: instance.instance.server.clone(),
+        managed_servers,
+        available_from,
+        available_until,
     };
     
     let history_path = instance_dir.join(".manifest_history.json");
@@ -372,7 +394,7 @@ pub async fn count_mojang_assets_pending(instance_dir: &Path, mc_version: &str)
     let vtext = tokio::fs::read_to_string(&json_path).await.map_err(|e| e.to_string())?;
     let vj: VJson = serde_json::from_str(&vtext).map_err(|e| e.to_string())?;
     let Some(ai) = vj.asset_index else { return Ok(0); };
-    let assets_dir = instance_dir.join("assets");
+    let assets_dir = crate::store::assets_dir();
     let indexes_dir = assets_dir.join("indexes");
     tokio::fs::create_dir_all(&indexes_dir).await.map_err(|e| e.to_string())?;
     let index_path = indexes_dir.join(format!("{}.json", ai.id));
@@ -397,18 +419,7 @@ pub async fn count_mojang_assets_pending(instance_dir: &Path, mc_version: &str)
 }
 
 pub async fn create_instance_directory_safe(instance_id: &str, _app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
-    use std::env;
-
-    let mut data_dir = if let Ok(home) = env::var("HOME") {
-        PathBuf::from(home)
-    } else if let Ok(home) = env::var("USERPROFILE") {
-        PathBuf::from(home)
-    } else {
-        return Err("Could not determine user home directory".to_string());
-    };
-
-    data_dir.push(".kindlyklanklient");
-    data_dir.push(instance_id);
+    let data_dir = crate::paths::data_root().join(instance_id);
 
     tokio::fs::create_dir_all(&data_dir).await
         .map_err(|e| format!("Failed to create instance directory: {}", e))?;
@@ -416,13 +427,24 @@ pub async fn create_instance_directory_safe(instance_id: &str, _app_handle: &tau
     Ok(data_dir)
 }
 
-pub async fn ensure_minecraft_client_present(instance_dir: &Path, mc_version: &str) -> Result<(), String> {
+/// El JSON de la versión vive en la instancia (y una copia en el store para los instaladores);
+/// el jar del cliente solo en el store compartido, verificado con el sha1 del JSON
+pub async fn ensure_minecraft_client_present(instance_dir: &Path, mc_version: &str, options: &SyncOptions) -> Result<(), String> {
     let version_dir = instance_dir.join("versions").join(mc_version);
-    let jar_path = version_dir.join(format!("{}.jar", mc_version));
     let json_path = version_dir.join(format!("{}.json", mc_version));
+    let store_version_dir = crate::store::version_dir(mc_version);
+    let store_json_path = store_version_dir.join(format!("{}.json", mc_version));
+    let jar_path = crate::store::client_jar_path(mc_version);
 
     tokio::fs::create_dir_all(&version_dir).await
         .map_err(|e| format!("Failed to create version dir {}: {}", version_dir.display(), e))?;
+    tokio::fs::create_dir_all(&store_version_dir).await
+        .map_err(|e| format!("Failed to create version dir {}: {}", store_version_dir.display(), e))?;
+
+    if !json_path.exists() && store_json_path.exists() {
+        tokio::fs::copy(&store_json_path, &json_path).await
+            .map_err(|e| format!("Failed to copy version json: {}", e))?;
+    }
 
     if !json_path.exists() {
         let manifest_url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
@@ -450,19 +472,25 @@ pub async fn ensure_minecraft_client_present(instance_dir: &Path, mc_version: &s
             .map_err(|e| format!("Failed to write version json: {}", e))?;
     }
 
+    if !store_json_path.exists() {
+        tokio::fs::copy(&json_path, &store_json_path).await
+            .map_err(|e| format!("Failed to copy version json to store: {}", e))?;
+    }
+
     if !jar_path.exists() {
         let vjson_text = tokio::fs::read_to_string(&json_path).await
             .map_err(|e| format!("Failed to read version json: {}", e))?;
         #[derive(serde::Deserialize)]
         struct Dls { client: Option<Info> }
         #[derive(serde::Deserialize)]
-        struct Info { url: String }
+        struct Info { url: String, sha1: Option<String> }
         #[derive(serde::Deserialize)]
         struct Vj { downloads: Option<Dls> }
         let vj: Vj = serde_json::from_str(&vjson_text)
             .map_err(|e| format!("Failed to parse version json: {}", e))?;
-        if let Some(url) = vj.downloads.and_then(|d| d.client).map(|c| c.url) {
-            download_file_with_retry(&url, &jar_path).await?;
+        if let Some(client) = vj.downloads.and_then(|d| d.client) {
+            download_with_sync_options(&client.url, &jar_path, options).await?;
+            crate::store::verify_store_file(&jar_path, client.sha1.as_deref())?;
         } else {
             return Err("Client download URL not found in version json".to_string());
         }
@@ -470,196 +498,135 @@ pub async fn ensure_minecraft_client_present(instance_dir: &Path, mc_version: &s
     Ok(())
 }
 
-pub async fn ensure_version_libraries(instance_dir: &Path, mc_version: &str) -> Result<(), String> {
-    let version_dir = instance_dir.join("versions").join(mc_version);
-    let json_path = version_dir.join(format!("{}.json", mc_version));
-    if !json_path.exists() { return Err(format!("Version json not found: {}", json_path.display())); }
-    let version_data = tokio::fs::read_to_string(&json_path).await.map_err(|e| e.to_string())?;
+/// Librerías de un JSON de versión que faltan en el store (jar y, en versiones antiguas, nativos)
+async fn missing_libraries(json_path: &Path) -> Result<Vec<(String, PathBuf, Option<String>)>, String> {
+    let version_data = tokio::fs::read_to_string(json_path).await.map_err(|e| e.to_string())?;
     #[derive(serde::Deserialize)]
     struct VersionJson { libraries: Vec<crate::versions::Library> }
     let vj: VersionJson = serde_json::from_str(&version_data).map_err(|e| e.to_string())?;
-    let os_name = if cfg!(target_os = "windows") { "windows" } else { "linux" };
-
-    // Preparar lista de libraries para descargar en paralelo
-    let mut libraries_to_download: Vec<(String, std::path::PathBuf)> = Vec::new();
+    let ctx = crate::arguments::RuleContext::current();
 
+    let mut missing = Vec::new();
     for lib in vj.libraries.iter() {
-        if !crate::versions::is_library_allowed(lib, os_name) { continue; }
-        if let Some(downloads) = &lib.downloads {
-            if let Some(artifact) = &downloads.artifact {
-                let lib_path = instance_dir.join("libraries").join(&artifact.path);
-                if let Some(parent) = lib_path.parent() {
-                    tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
-                }
-                if !lib_path.exists() {
-                    libraries_to_download.push((artifact.url.clone(), lib_path));
-                }
+        if !lib.is_allowed(&ctx) { continue; }
+        // Además del jar, las versiones antiguas traen los nativos como classifier
+        let artifacts = lib.downloads.as_ref().and_then(|d| d.artifact.as_ref()).into_iter()
+            .chain(lib.native_artifact(&ctx.os_name));
+        for artifact in artifacts {
+            let lib_path = crate::store::libraries_dir().join(&artifact.path);
+            if let Some(parent) = lib_path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+            }
+            if !lib_path.exists() {
+                missing.push((artifact.url.clone(), lib_path, artifact.sha1.clone()));
             }
         }
     }
+    Ok(missing)
+}
 
-    // Descargar libraries en paralelo
-    if !libraries_to_download.is_empty() {
-        use futures_util::stream::{self, StreamExt};
-        let parallel = num_cpus::get().saturating_mul(6).max(30).min(libraries_to_download.len());
-
-        let client = std::sync::Arc::new(reqwest::Client::builder()
-            .user_agent("KindlyKlanKlient/1.0")
-            .connect_timeout(std::time::Duration::from_secs(5))
-            .timeout(std::time::Duration::from_secs(120))
-            .pool_max_idle_per_host(40)
-            .pool_idle_timeout(std::time::Duration::from_secs(60))
-            .tcp_nodelay(true)
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?);
-
-        let results: Vec<Result<(), String>> = stream::iter(libraries_to_download.into_iter())
-            .map(|(url, path)| {
-                let client = client.clone();
-                async move {
-                    download_file_with_retry_and_client(&client, &url, &path).await
-                }
-            })
-            .buffer_unordered(parallel)
-            .collect()
-            .await;
-
-        // Log errors but don't fail completely
-        for result in results {
-            if let Err(e) = result {
-                log::warn!("Error downloading library: {}", e);
+/// Descarga las librerías en paralelo. Un fallo no aborta el resto: se devuelve cuántas fallaron
+async fn download_libraries(libraries: Vec<(String, PathBuf, Option<String>)>, options: &SyncOptions, label: &str) -> Result<usize, String> {
+    if libraries.is_empty() {
+        return Ok(0);
+    }
+    use futures_util::stream::{self, StreamExt};
+    let parallel = options.parallel(num_cpus::get().saturating_mul(6).max(30), libraries.len());
+    let client = crate::sync_engine::build_sync_client()?;
+
+    let results: Vec<Result<(), String>> = stream::iter(libraries.into_iter())
+        .map(|(url, path, sha1)| {
+            let client = client.clone();
+            async move {
+                crate::store::download_to_store(&client, &url, &path, sha1.as_deref(), options).await
             }
+        })
+        .buffer_unordered(parallel)
+        .collect()
+        .await;
+
+    let mut failed = 0;
+    for result in results {
+        if let Err(e) = result {
+            log::warn!("Error downloading {}: {}", label, e);
+            failed += 1;
         }
     }
+    Ok(failed)
+}
 
-    Ok(())
+/// Devuelve el número de librerías que no se pudieron descargar
+pub async fn ensure_version_libraries(instance_dir: &Path, mc_version: &str, options: &SyncOptions) -> Result<usize, String> {
+    let version_dir = instance_dir.join("versions").join(mc_version);
+    let json_path = version_dir.join(format!("{}.json", mc_version));
+    if !json_path.exists() { return Err(format!("Version json not found: {}", json_path.display())); }
+    let libraries = missing_libraries(&json_path).await?;
+    download_libraries(libraries, options, "library").await
 }
 
 /// Descarga las bibliotecas del JSON del mod loader (NeoForge/Fabric/Forge)
 /// Esto es CRÍTICO porque mod loaders como Fabric/NeoForge agregan sus propias versiones de bibliotecas
 /// Ejemplo: Fabric usa asm-9.9 en lugar del asm-9.6 de vanilla MC
-pub async fn ensure_mod_loader_libraries(instance_dir: &Path, version_id: &str) -> Result<(), String> {
+pub async fn ensure_mod_loader_libraries(instance_dir: &Path, version_id: &str, options: &SyncOptions) -> Result<usize, String> {
     let version_dir = instance_dir.join("versions").join(version_id);
     let json_path = version_dir.join(format!("{}.json", version_id));
     
     if !json_path.exists() {
         // No hay JSON de mod loader, no hacer nada (vanilla)
-        return Ok(());
-    }
-    
-    let version_data = tokio::fs::read_to_string(&json_path).await.map_err(|e| e.to_string())?;
-    
-    #[derive(serde::Deserialize)]
-    struct VersionJson {
-        libraries: Vec<crate::versions::Library>
+        return Ok(0);
     }
     
-    let vj: VersionJson = serde_json::from_str(&version_data).map_err(|e| e.to_string())?;
-    let os_name = if cfg!(target_os = "windows") { "windows" } else { "linux" };
-    
-    // Preparar lista de mod loader libraries para descargar en paralelo
-    let mut mod_loader_libraries_to_download: Vec<(String, std::path::PathBuf)> = Vec::new();
-
-    for lib in vj.libraries.iter() {
-        if !crate::versions::is_library_allowed(lib, os_name) {
-            continue;
-        }
-
-        if let Some(downloads) = &lib.downloads {
-            if let Some(artifact) = &downloads.artifact {
-                let lib_path = instance_dir.join("libraries").join(&artifact.path);
-
-                if let Some(parent) = lib_path.parent() {
-                    tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
-                }
-
-                if !lib_path.exists() {
-                    mod_loader_libraries_to_download.push((artifact.url.clone(), lib_path));
-                }
-            }
-        }
-    }
-
-    // Descargar mod loader libraries en paralelo
-    if !mod_loader_libraries_to_download.is_empty() {
-        use futures_util::stream::{self, StreamExt};
-        let parallel = num_cpus::get().saturating_mul(6).max(30).min(mod_loader_libraries_to_download.len());
-
-        let client = std::sync::Arc::new(reqwest::Client::builder()
-            .user_agent("KindlyKlanKlient/1.0")
-            .connect_timeout(std::time::Duration::from_secs(5))
-            .timeout(std::time::Duration::from_secs(120))
-            .pool_max_idle_per_host(40)
-            .pool_idle_timeout(std::time::Duration::from_secs(60))
-            .tcp_nodelay(true)
-            .build()
-            .map_err(|e| format!("Failed to build HTTP client: {}", e))?);
-
-        let results: Vec<Result<(), String>> = stream::iter(mod_loader_libraries_to_download.into_iter())
-            .map(|(url, path)| {
-                let client = client.clone();
-                async move {
-                    download_file_with_retry_and_client(&client, &url, &path).await
-                }
-            })
-            .buffer_unordered(parallel)
-            .collect()
-            .await;
-
-        // Log errors but don't fail completely
-        for result in results {
-            if let Err(e) = result {
-                log::warn!("Error downloading mod loader library: {}", e);
-            }
-        }
-    }
-    
-    Ok(())
+    let libraries = missing_libraries(&json_path).await?;
+    download_libraries(libraries, options, "mod loader library").await
 }
 
-pub async fn install_mod_loader(minecraft_version: &str, mod_loader: &ModLoader, instance_dir: &Path) -> Result<Option<String>, String> {
+pub async fn install_mod_loader(minecraft_version: &str, mod_loader: &ModLoader, instance_dir: &Path, options: &SyncOptions) -> Result<Option<String>, String> {
     match mod_loader.r#type.as_str() {
-        "fabric" => install_fabric(minecraft_version, &mod_loader.version, instance_dir).await,
-        "forge" => install_forge(minecraft_version, &mod_loader.version, instance_dir).await,
-        "neoforge" => install_neoforge(minecraft_version, &mod_loader.version, instance_dir).await,
+        "fabric" => install_fabric(minecraft_version, &mod_loader.version, instance_dir, options).await,
+        "forge" => install_forge(minecraft_version, &mod_loader.version, instance_dir, options).await,
+        "neoforge" => install_neoforge(minecraft_version, &mod_loader.version, instance_dir, options).await,
         "vanilla" => Ok(None),
         _ => Err(format!("Unsupported mod loader type: {}", mod_loader.r#type))
     }
 }
 
-async fn install_fabric(minecraft_version: &str, fabric_version: &str, instance_dir: &Path) -> Result<Option<String>, String> {
-    let loader_jar = instance_dir
-        .join("libraries")
+/// Los instaladores trabajan sobre la raíz del store: las librerías quedan compartidas y
+/// el JSON del loader se copia después a la instancia
+async fn install_fabric(minecraft_version: &str, fabric_version: &str, instance_dir: &Path, options: &SyncOptions) -> Result<Option<String>, String> {
+    let libraries_dir = crate::store::libraries_dir();
+    let loader_jar = libraries_dir
         .join("net")
         .join("fabricmc")
         .join("fabric-loader")
         .join(fabric_version)
         .join(format!("fabric-loader-{}.jar", fabric_version));
     if loader_jar.exists() {
-        // Si ya está instalado, buscar el version_id existente
-        return Ok(find_version_id_in_versions_dir(instance_dir, "fabric"));
+        // Si ya está instalado en el store, reutilizar su version_id
+        if let Some(id) = crate::store::adopt_loader_version(instance_dir, "fabric", minecraft_version, fabric_version)? {
+            return Ok(Some(id));
+        }
     }
 
-    let libraries_dir = instance_dir.join("libraries");
     tokio::fs::create_dir_all(&libraries_dir).await
         .map_err(|e| format!("Failed to create libraries directory: {}", e))?;
 
     let installer_info = get_fabric_installer_info().await?;
-    let installer_path = download_fabric_installer(&installer_info, &libraries_dir).await?;
+    let installer_path = download_fabric_installer(&installer_info, &libraries_dir, options).await?;
     let profile_json = get_fabric_profile_json(minecraft_version, fabric_version).await?;
-    download_fabric_libraries(&profile_json, &libraries_dir).await?;
-    run_fabric_installer(&installer_path, instance_dir, minecraft_version, fabric_version).await?;
-    ensure_minecraft_client_present(instance_dir, minecraft_version).await?;
+    download_fabric_libraries(&profile_json, &libraries_dir, options).await?;
+    ensure_minecraft_client_present(instance_dir, minecraft_version, options).await?;
+    run_fabric_installer(&installer_path, &crate::store::store_root(), minecraft_version, fabric_version).await?;
     
     // Buscar el version_id creado por el instalador
-    Ok(find_version_id_in_versions_dir(instance_dir, "fabric"))
+    Ok(crate::store::adopt_loader_version(instance_dir, "fabric", minecraft_version, fabric_version)?
+        .or_else(|| find_version_id_in_versions_dir(instance_dir, "fabric")))
 }
 
-async fn install_forge(minecraft_version: &str, forge_version: &str, instance_dir: &Path) -> Result<Option<String>, String> {
+async fn install_forge(minecraft_version: &str, forge_version: &str, instance_dir: &Path, options: &SyncOptions) -> Result<Option<String>, String> {
     log::info!("Installing Forge {} for Minecraft {}", forge_version, minecraft_version);
     
-    let forge_marker = instance_dir
-        .join("libraries")
+    let libraries_dir = crate::store::libraries_dir();
+    let forge_marker = libraries_dir
         .join("net")
         .join("minecraftforge")
         .join("forge")
@@ -667,10 +634,11 @@ async fn install_forge(minecraft_version: &str, forge_version: &str, instance_di
         .join(format!("forge-{}.jar", forge_version));
     
     if forge_marker.exists() {
-        return Ok(find_version_id_in_versions_dir(instance_dir, "forge"));
+        if let Some(id) = crate::store::adopt_loader_version(instance_dir, "forge", minecraft_version, forge_version)? {
+            return Ok(Some(id));
+        }
     }
     
-    let libraries_dir = instance_dir.join("libraries");
     tokio::fs::create_dir_all(&libraries_dir).await
         .map_err(|e| format!("Failed to create libraries directory: {}", e))?;
     
@@ -680,17 +648,24 @@ async fn install_forge(minecraft_version: &str, forge_version: &str, instance_di
     );
     
     let installer_path = libraries_dir.join(format!("forge-installer-{}.jar", forge_version));
-    download_file_with_retry(&installer_url, &installer_path).await?;
+    download_with_sync_options(&installer_url, &installer_path, options).await?;
     
-    run_forge_installer(&installer_path, instance_dir, minecraft_version).await?;
+    // El instalador necesita el cliente vanilla en el store
+    ensure_minecraft_client_present(instance_dir, minecraft_version, options).await?;
+    let libraries_before = crate::store::snapshot_libraries();
+    run_forge_installer(&installer_path, &crate::store::store_root(), minecraft_version).await?;
+    let _ = std::fs::remove_file(&installer_path);
     log::info!("Forge {} installed successfully", forge_version);
-    ensure_minecraft_client_present(instance_dir, minecraft_version).await?;
     
-    Ok(find_version_id_in_versions_dir(instance_dir, "forge"))
+    let version_id = crate::store::adopt_loader_version(instance_dir, "forge", minecraft_version, forge_version)?;
+    if let Some(id) = &version_id {
+        crate::store::record_install_receipt(id, &libraries_before);
+    }
+    Ok(version_id.or_else(|| find_version_id_in_versions_dir(instance_dir, "forge")))
 }
 
-async fn run_forge_installer(installer: &Path, instance_dir: &Path, minecraft_version: &str) -> Result<(), String> {
-    ensure_launcher_profile(instance_dir)?;
+async fn run_forge_installer(installer: &Path, install_dir: &Path, minecraft_version: &str) -> Result<(), String> {
+    ensure_launcher_profile(install_dir)?;
     
     let java_path = crate::launcher::find_or_install_java_for_minecraft(minecraft_version).await?;
     
@@ -714,7 +689,7 @@ async fn run_forge_installer(installer: &Path, instance_dir: &Path, minecraft_ve
             "-jar",
             &temp_installer.to_string_lossy(),
             "--installClient",
-            instance_dir.to_string_lossy().as_ref(),
+            install_dir.to_string_lossy().as_ref(),
         ])
         .output()
         .map_err(|e| format!("Failed to run Forge installer: {}", e))?;
@@ -733,11 +708,11 @@ async fn run_forge_installer(installer: &Path, instance_dir: &Path, minecraft_ve
     Ok(())
 }
 
-async fn install_neoforge(minecraft_version: &str, neoforge_version: &str, instance_dir: &Path) -> Result<Option<String>, String> {
+async fn install_neoforge(minecraft_version: &str, neoforge_version: &str, instance_dir: &Path, options: &SyncOptions) -> Result<Option<String>, String> {
     log::info!("Installing NeoForge {} for Minecraft {}", neoforge_version, minecraft_version);
     
-    let neoforge_marker = instance_dir
-        .join("libraries")
+    let libraries_dir = crate::store::libraries_dir();
+    let neoforge_marker = libraries_dir
         .join("net")
         .join("neoforged")
         .join("neoforge")
@@ -745,10 +720,11 @@ async fn install_neoforge(minecraft_version: &str, neoforge_version: &str, insta
         .join(format!("neoforge-{}.jar", neoforge_version));
     
     if neoforge_marker.exists() {
-        return Ok(find_version_id_in_versions_dir(instance_dir, "neoforge"));
+        if let Some(id) = crate::store::adopt_loader_version(instance_dir, "neoforge", minecraft_version, neoforge_version)? {
+            return Ok(Some(id));
+        }
     }
     
-    let libraries_dir = instance_dir.join("libraries");
     tokio::fs::create_dir_all(&libraries_dir).await
         .map_err(|e| format!("Failed to create libraries directory: {}", e))?;
     
@@ -758,17 +734,23 @@ async fn install_neoforge(minecraft_version: &str, neoforge_version: &str, insta
     );
     
     let installer_path = libraries_dir.join(format!("neoforge-installer-{}.jar", neoforge_version));
-    download_file_with_retry(&installer_url, &installer_path).await?;
+    download_with_sync_options(&installer_url, &installer_path, options).await?;
     
-    run_neoforge_installer(&installer_path, instance_dir, minecraft_version).await?;
+    ensure_minecraft_client_present(instance_dir, minecraft_version, options).await?;
+    let libraries_before = crate::store::snapshot_libraries();
+    run_neoforge_installer(&installer_path, &crate::store::store_root(), minecraft_version).await?;
+    let _ = std::fs::remove_file(&installer_path);
     log::info!("NeoForge {} installed successfully", neoforge_version);
-    ensure_minecraft_client_present(instance_dir, minecraft_version).await?;
     
-    Ok(find_version_id_in_versions_dir(instance_dir, "neoforge"))
+    let version_id = crate::store::adopt_loader_version(instance_dir, "neoforge", minecraft_version, neoforge_version)?;
+    if let Some(id) = &version_id {
+        crate::store::record_install_receipt(id, &libraries_before);
+    }
+    Ok(version_id.or_else(|| find_version_id_in_versions_dir(instance_dir, "neoforge")))
 }
 
-async fn run_neoforge_installer(installer: &Path, instance_dir: &Path, minecraft_version: &str) -> Result<(), String> {
-    ensure_launcher_profile(instance_dir)?;
+async fn run_neoforge_installer(installer: &Path, install_dir: &Path, minecraft_version: &str) -> Result<(), String> {
+    ensure_launcher_profile(install_dir)?;
     
     let java_path = crate::launcher::find_or_install_java_for_minecraft(minecraft_version).await?;
     
@@ -792,7 +774,7 @@ async fn run_neoforge_installer(installer: &Path, instance_dir: &Path, minecraft
             "-jar",
             &temp_installer.to_string_lossy(),
             "--installClient",
-            instance_dir.to_string_lossy().as_ref(),
+            install_dir.to_string_lossy().as_ref(),
         ])
         .output()
         .map_err(|e| format!("Failed to run NeoForge installer: {}", e))?;
@@ -931,13 +913,13 @@ async fn get_fabric_profile_json(minecraft_version: &str, fabric_version: &str)
 
     Ok(profile)
 }
-async fn download_fabric_installer(info: &crate::models::FabricInstallerMeta, libs: &Path) -> Result<PathBuf, String> {
+async fn download_fabric_installer(info: &crate::models::FabricInstallerMeta, libs: &Path, options: &SyncOptions) -> Result<PathBuf, String> {
     let installer_path = libs.join(format!("fabric-installer-{}.jar", info.version));
-    download_file_with_retry(&info.url, &installer_path).await?;
+    download_with_sync_options(&info.url, &installer_path, options).await?;
     Ok(installer_path)
 }
 
-async fn download_fabric_libraries(profile: &crate::models::FabricProfileJson, libs: &Path) -> Result<(), String> {
+async fn download_fabric_libraries(profile: &crate::models::FabricProfileJson, libs: &Path, options: &SyncOptions) -> Result<(), String> {
     for library in profile.libraries.iter() {
         let library_path = resolve_maven_path(&library.name, libs)?;
         if let Some(parent) = library_path.parent() {
@@ -945,12 +927,12 @@ async fn download_fabric_libraries(profile: &crate::models::FabricProfileJson, l
                 .map_err(|e| format!("Failed to create library directory: {}", e))?;
         }
         let library_url = build_library_url(library)?;
-        download_file_with_retry(&library_url, &library_path).await?;
+        download_with_sync_options(&library_url, &library_path, options).await?;
     }
     Ok(())
 }
 
-async fn run_fabric_installer(installer: &Path, instance_dir: &Path, mc: &str, fabric: &str) -> Result<(), String> {
+async fn run_fabric_installer(installer: &Path, install_dir: &Path, mc: &str, fabric: &str) -> Result<(), String> {
     let java_path = crate::launcher::find_or_install_java_for_minecraft(mc).await?;
     let mut cmd = Command::new(&java_path);
     #[cfg(target_os = "windows")]
@@ -965,7 +947,7 @@ async fn run_fabric_installer(installer: &Path, instance_dir: &Path, mc: &str, f
             "client",
             "-noprofile",
             "-dir",
-            &instance_dir.to_string_lossy(),
+            &install_dir.to_string_lossy(),
             "-mcversion",
             mc,
             "-loader",
@@ -1011,7 +993,7 @@ pub async fn ensure_assets_present(app_handle: &tauri::AppHandle, instance_dir:
     let vtext = tokio::fs::read_to_string(&json_path).await.map_err(|e| e.to_string())?;
     let vj: VJson = serde_json::from_str(&vtext).map_err(|e| e.to_string())?;
     let Some(ai) = vj.asset_index else { return Err("assetIndex missing in version json".to_string()); };
-    let assets_dir = instance_dir.join("assets");
+    let assets_dir = crate::store::assets_dir();
     let indexes_dir = assets_dir.join("indexes");
     tokio::fs::create_dir_all(&indexes_dir).await.map_err(|e| e.to_string())?;
     let index_path = indexes_dir.join(format!("{}.json", ai.id));
@@ -1078,7 +1060,8 @@ pub async fn ensure_assets_present_with_progress(
     app_handle: &tauri::AppHandle,
     instance_dir: &Path,
     mc_version: &str,
-    combined: Option<(std::sync::Arc<std::sync::atomic::AtomicU64>, u64)>
+    combined: Option<(std::sync::Arc<std::sync::atomic::AtomicU64>, u64)>,
+    options: &crate::sync_engine::SyncOptions,
 ) -> Result<String, String> {
     let version_dir = instance_dir.join("versions").join(mc_version);
     let json_path = version_dir.join(format!("{}.json", mc_version));
@@ -1090,11 +1073,11 @@ pub async fn ensure_assets_present_with_progress(
     let vtext = tokio::fs::read_to_string(&json_path).await.map_err(|e| e.to_string())?;
     let vj: VJson = serde_json::from_str(&vtext).map_err(|e| e.to_string())?;
     let Some(ai) = vj.asset_index else { return Err("assetIndex missing in version json".to_string()); };
-    let assets_dir = instance_dir.join("assets");
+    let assets_dir = crate::store::assets_dir();
     let indexes_dir = assets_dir.join("indexes");
     tokio::fs::create_dir_all(&indexes_dir).await.map_err(|e| e.to_string())?;
     let index_path = indexes_dir.join(format!("{}.json", ai.id));
-    if !index_path.exists() { download_file_with_retry(&ai.url, &index_path).await?; }
+    if !index_path.exists() { download_with_sync_options(&ai.url, &index_path, options).await?; }
     let index_text = tokio::fs::read_to_string(&index_path).await.map_err(|e| e.to_string())?;
     #[derive(serde::Deserialize)]
     struct AssetObject { hash: String }
@@ -1112,7 +1095,7 @@ pub async fn ensure_assets_present_with_progress(
         if !obj_path.exists() { pending.push((prefix, obj.hash)); }
     }
     if pending.is_empty() { return Ok(ai.id); }
-    let parallel = num_cpus::get().saturating_mul(12).max(100);
+    let parallel = options.parallel(num_cpus::get().saturating_mul(12).max(100), pending.len());
     use futures_util::stream::{self, StreamExt};
 
     // Cliente HTTP optimizado con pool de conexiones grande
@@ -1134,20 +1117,23 @@ pub async fn ensure_assets_present_with_progress(
         async move {
             let url = format!("https://resources.download.minecraft.net/{}/{}", prefix, hash);
             let obj_path = objects_dir.join(&prefix).join(&hash);
-            download_file_with_retry_and_client(&client, &url, &obj_path).await?;
+            crate::sync_engine::download_with_options(&client, &url, &obj_path, options).await?;
             if let Some((counter, total)) = &combined {
                 let cur = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
-                let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-                    "current": cur,
-                    "total": total,
-                    "percentage": ((cur as f32 / *total as f32) * 100.0).min(100.0),
-                    "current_file": "",
-                    "status": "Mojang"
-                }));
+                options.emit_progress(&app_handle, cur, *total, ((cur as f32 / *total as f32) * 100.0).min(100.0), "", "Mojang");
             }
             Ok(())
         }
     })).buffer_unordered(parallel).collect().await;
-    for result in results { if let Err(e) = result { eprintln!("Warning: Mojang asset download error: {}", e); } }
+    let mut failed = 0;
+    for result in results {
+        if let Err(e) = result {
+            log::warn!("Mojang asset download error: {}", e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} Mojang assets failed to download", failed));
+    }
     Ok(ai.id)
 }
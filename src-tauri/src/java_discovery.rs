@@ -0,0 +1,345 @@
+use crate::models::{JavaCompatibility, JavaInstallation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Instalaciones de Java encontradas. La JVM fijada de cada instancia va en su perfil de lanzamiento
+const REGISTRY_FILE: &str = "java_installations.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct JavaRegistry {
+    #[serde(default)]
+    installations: Vec<JavaInstallation>,
+    #[serde(default)]
+    last_scan: Option<String>,
+}
+
+fn registry_path() -> PathBuf {
+    crate::paths::data_root().join(REGISTRY_FILE)
+}
+
+fn load_registry() -> JavaRegistry {
+    std::fs::read_to_string(registry_path())
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(registry: &JavaRegistry) -> Result<(), String> {
+    let path = registry_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn java_exe_name() -> &'static str {
+    if cfg!(target_os = "windows") { "java.exe" } else { "java" }
+}
+
+/// Ejecutables de Java dentro de una carpeta de JDK/JRE (incluye el layout de macOS)
+fn java_in_home(home: &Path) -> Vec<PathBuf> {
+    [home.join("bin"), home.join("Contents").join("Home").join("bin"), home.join("jre.bundle").join("Contents").join("Home").join("bin")]
+        .into_iter()
+        .map(|bin| bin.join(java_exe_name()))
+        .filter(|java| java.is_file())
+        .collect()
+}
+
+/// Ejecutables de Java de cada subcarpeta de `dir` (p. ej. `/usr/lib/jvm/*`)
+fn java_in_children(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).flat_map(|p| java_in_home(&p)).collect()
+}
+
+/// Rutas candidatas con su origen. Pueden repetirse; se deduplican al canonicalizar
+fn candidates() -> Vec<(PathBuf, &'static str)> {
+    let mut found: Vec<(PathBuf, &'static str)> = Vec::new();
+
+    if let Some(path_var) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_var) {
+            let java = dir.join(java_exe_name());
+            if java.is_file() {
+                found.push((java, "path"));
+            }
+        }
+    }
+
+    if let Some(java_home) = std::env::var_os("JAVA_HOME").filter(|v| !v.is_empty()) {
+        found.extend(java_in_home(Path::new(&java_home)).into_iter().map(|j| (j, "java_home")));
+    }
+
+    let system_dirs: Vec<PathBuf> = if cfg!(target_os = "windows") {
+        ["ProgramFiles", "ProgramFiles(x86)"]
+            .iter()
+            .filter_map(|var| std::env::var_os(var))
+            .flat_map(|pf| {
+                let pf = PathBuf::from(pf);
+                ["Java", "Eclipse Adoptium", "Microsoft", "Zulu", "Amazon Corretto", "BellSoft"].map(|vendor| pf.join(vendor))
+            })
+            .collect()
+    } else if cfg!(target_os = "macos") {
+        vec![PathBuf::from("/Library/Java/JavaVirtualMachines")]
+    } else {
+        vec![PathBuf::from("/usr/lib/jvm"), PathBuf::from("/usr/lib64/jvm"), PathBuf::from("/usr/java"), PathBuf::from("/opt/java")]
+    };
+    for dir in system_dirs {
+        found.extend(java_in_children(&dir).into_iter().map(|j| (j, "system")));
+    }
+
+    let sdkman_dir = std::env::var_os("SDKMAN_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".sdkman")));
+    if let Some(sdkman) = sdkman_dir {
+        found.extend(java_in_children(&sdkman.join("candidates").join("java")).into_iter().map(|j| (j, "sdkman")));
+    }
+
+    // Runtimes del launcher: java-N de Adoptium y componentes de Mojang
+    found.extend(java_in_children(&crate::paths::runtime_dir()).into_iter().map(|j| (j, "runtime")));
+
+    found
+}
+
+/// Lee las propiedades de `java -XshowSettings:properties -version` (las escribe en stderr)
+fn parse_properties(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().split_once(" = "))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+fn major_from_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        return parts.next()?.parse().ok();
+    }
+    Some(first)
+}
+
+/// Ejecuta la JVM para saber su versión, fabricante y arquitectura
+pub fn probe_java(java: &Path, source: &str) -> Result<JavaInstallation, String> {
+    let mut cmd = std::process::Command::new(java);
+    cmd.arg("-XshowSettings:properties").arg("-version");
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+    let output = cmd.output().map_err(|e| format!("Failed to run {}: {}", java.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", java.display(), output.status));
+    }
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
+    let props = parse_properties(&text);
+    let version = props
+        .get("java.version")
+        .cloned()
+        .ok_or_else(|| format!("{} did not report java.version", java.display()))?;
+    let major = props
+        .get("java.specification.version")
+        .and_then(|v| major_from_version(v))
+        .or_else(|| major_from_version(&version))
+        .ok_or_else(|| format!("Could not parse Java version '{}'", version))?;
+    Ok(JavaInstallation {
+        path: java.to_string_lossy().to_string(),
+        version,
+        major_version: major,
+        vendor: props.get("java.vendor").cloned(),
+        arch: props.get("os.arch").cloned(),
+        source: source.to_string(),
+    })
+}
+
+fn scan() -> Vec<JavaInstallation> {
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut installations = Vec::new();
+    for (candidate, source) in candidates() {
+        // /usr/bin/java suele ser un enlace a /usr/lib/jvm/...; se cuenta una sola vez
+        let canonical = dunce::canonicalize(&candidate).unwrap_or(candidate);
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical.clone());
+        match probe_java(&canonical, source) {
+            Ok(installation) => installations.push(installation),
+            Err(e) => log::debug!("Skipping Java candidate {}: {}", canonical.display(), e),
+        }
+    }
+    installations
+}
+
+/// Normaliza `os.arch` de Java al nombre de Rust para compararlo con el del sistema
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "amd64" | "x86_64" => "x86_64",
+        "aarch64" | "arm64" => "aarch64",
+        "x86" | "i386" | "i486" | "i586" | "i686" => "x86",
+        other => other,
+    }
+}
+
+fn check_compatibility(path: &str, installation: Option<&JavaInstallation>, required_major: u32) -> JavaCompatibility {
+    let Some(installation) = installation else {
+        return JavaCompatibility {
+            path: path.to_string(),
+            major_version: None,
+            required_major,
+            compatible: false,
+            warnings: vec![format!("{} is not a working Java installation", path)],
+        };
+    };
+    let mut warnings = Vec::new();
+    let major = installation.major_version;
+    let compatible = major >= required_major;
+    if major < required_major {
+        warnings.push(format!("Java {} is too old: this version of Minecraft needs Java {}", major, required_major));
+    } else if major > required_major {
+        warnings.push(format!(
+            "Java {} is newer than the recommended Java {}; older mod loaders may fail to start",
+            major, required_major
+        ));
+    }
+    if let Some(arch) = &installation.arch {
+        if normalize_arch(arch) != std::env::consts::ARCH {
+            warnings.push(format!("This JVM is built for {} but the system is {}", arch, std::env::consts::ARCH));
+        }
+    }
+    JavaCompatibility { path: path.to_string(), major_version: Some(major), required_major, compatible, warnings }
+}
+
+/// Datos de una JVM: los del registro o, si no está registrada, los que dé al ejecutarla
+fn describe_java(path: &str) -> Option<JavaInstallation> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    load_registry()
+        .installations
+        .into_iter()
+        .find(|i| i.path == path)
+        .or_else(|| probe_java(Path::new(path), "manual").ok())
+}
+
+/// JVM fijada para una instancia, si la hay y sigue existiendo
+pub fn pinned_java(instance_id: &str) -> Option<String> {
+    let path = crate::launch_profiles::load_launch_profile(instance_id).java_path?;
+    if Path::new(&path).is_file() {
+        Some(path)
+    } else {
+        log::warn!("Pinned Java for {} no longer exists: {}", instance_id, path);
+        None
+    }
+}
+
+/// Java registrada con exactamente la versión mayor pedida y la arquitectura del sistema
+pub fn find_registered_java(major: u32) -> Option<String> {
+    load_registry()
+        .installations
+        .into_iter()
+        .filter(|i| i.major_version == major && Path::new(&i.path).is_file())
+        .find(|i| i.arch.as_deref().map(|a| normalize_arch(a) == std::env::consts::ARCH).unwrap_or(true))
+        .map(|i| i.path)
+}
+
+/// Java para lanzar una instancia: la fijada por el usuario o la que pida la versión
+pub async fn java_for_instance(instance_id: &str, minecraft_version: &str) -> Result<String, String> {
+    if let Some(path) = pinned_java(instance_id) {
+        let required = crate::java_runtime::java_requirement_for_version(minecraft_version).major_version;
+        let installation = describe_java(&path);
+        for warning in check_compatibility(&path, installation.as_ref(), required).warnings {
+            log::warn!("Pinned Java for {}: {}", instance_id, warning);
+        }
+        return Ok(path);
+    }
+    crate::launcher::find_or_install_java_for_minecraft(minecraft_version).await
+}
+
+/// Busca instalaciones de Java en el sistema y guarda el resultado
+#[tauri::command]
+pub async fn discover_java_installations() -> Result<Vec<JavaInstallation>, String> {
+    let found = tauri::async_runtime::spawn_blocking(scan)
+        .await
+        .map_err(|e| format!("Java discovery task failed: {}", e))?;
+
+    let mut registry = load_registry();
+    // Las añadidas a mano se conservan mientras sigan existiendo
+    let manual: Vec<JavaInstallation> = registry
+        .installations
+        .drain(..)
+        .filter(|i| i.source == "manual" && Path::new(&i.path).is_file() && !found.iter().any(|f| f.path == i.path))
+        .collect();
+    registry.installations = found;
+    registry.installations.extend(manual);
+    registry.installations.sort_by(|a, b| b.major_version.cmp(&a.major_version).then(a.path.cmp(&b.path)));
+    registry.last_scan = Some(chrono::Utc::now().to_rfc3339());
+    save_registry(&registry)?;
+
+    log::info!("Java discovery found {} installations", registry.installations.len());
+    Ok(registry.installations)
+}
+
+#[tauri::command]
+pub async fn get_java_installations() -> Result<Vec<JavaInstallation>, String> {
+    Ok(load_registry().installations)
+}
+
+/// Registra a mano un ejecutable de Java que no se encontró al buscar
+#[tauri::command]
+pub async fn add_java_installation(java_path: String) -> Result<JavaInstallation, String> {
+    let path = PathBuf::from(&java_path);
+    let canonical = dunce::canonicalize(&path).map_err(|e| format!("Invalid Java path {}: {}", java_path, e))?;
+    let installation = tauri::async_runtime::spawn_blocking(move || probe_java(&canonical, "manual"))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let mut registry = load_registry();
+    registry.installations.retain(|i| i.path != installation.path);
+    registry.installations.push(installation.clone());
+    save_registry(&registry)?;
+    Ok(installation)
+}
+
+/// Fija una JVM para una instancia (o la quita con `None`). Devuelve la compatibilidad con la
+/// versión de Minecraft para que la interfaz pueda avisar
+#[tauri::command]
+pub async fn set_instance_java(
+    instance_id: String,
+    java_path: Option<String>,
+    minecraft_version: String,
+) -> Result<Option<JavaCompatibility>, String> {
+    let mut profile = crate::launch_profiles::load_launch_profile(&instance_id);
+    let Some(java_path) = java_path.filter(|p| !p.trim().is_empty()) else {
+        profile.java_path = None;
+        crate::launch_profiles::write_launch_profile(&instance_id, &profile)?;
+        return Ok(None);
+    };
+
+    let registered = load_registry().installations.into_iter().find(|i| i.path == java_path);
+    let installation = match registered {
+        Some(installation) => installation,
+        None => add_java_installation(java_path.clone()).await?,
+    };
+    let required = crate::java_runtime::java_requirement_for_version(&minecraft_version).major_version;
+    let compatibility = check_compatibility(&installation.path, Some(&installation), required);
+
+    profile.java_path = Some(installation.path.clone());
+    crate::launch_profiles::write_launch_profile(&instance_id, &profile)?;
+    log::info!("Pinned Java {} for instance {}", installation.path, instance_id);
+    Ok(Some(compatibility))
+}
+
+/// JVM fijada de una instancia con sus avisos de compatibilidad, o `None` si usa la automática
+#[tauri::command]
+pub async fn get_instance_java(instance_id: String, minecraft_version: String) -> Result<Option<JavaCompatibility>, String> {
+    let Some(path) = crate::launch_profiles::load_launch_profile(&instance_id).java_path else { return Ok(None) };
+    let required = crate::java_runtime::java_requirement_for_version(&minecraft_version).major_version;
+    let installation = tauri::async_runtime::spawn_blocking({
+        let path = path.clone();
+        move || describe_java(&path)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(Some(check_compatibility(&path, installation.as_ref(), required)))
+}
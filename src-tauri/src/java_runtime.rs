@@ -0,0 +1,512 @@
+use crate::models::JavaRequirement;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Manifest de runtimes de Java que usa el launcher oficial
+const JAVA_RUNTIME_MANIFEST_URL: &str =
+    "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// Se escribe al terminar una instalación completa y verificada, con el nombre de la versión
+const INSTALLED_MARKER: &str = ".version";
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeDownload {
+    sha1: String,
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeVersion {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeEntry {
+    manifest: RuntimeDownload,
+    version: RuntimeVersion,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeFileDownloads {
+    raw: RuntimeDownload,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeFile {
+    #[serde(rename = "type")]
+    file_type: String,
+    #[serde(default)]
+    executable: bool,
+    downloads: Option<RuntimeFileDownloads>,
+    target: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RuntimeManifest {
+    files: HashMap<String, RuntimeFile>,
+}
+
+/// Componente de Mojang equivalente a cada versión mayor, para cuando no hay JSON de versión
+fn component_for_major(major: u32) -> &'static str {
+    match major {
+        0..=8 => "jre-legacy",
+        9..=16 => "java-runtime-alpha",
+        17 => "java-runtime-gamma",
+        18..=21 => "java-runtime-delta",
+        _ => "java-runtime-epsilon",
+    }
+}
+
+fn read_java_requirement(json_path: &Path) -> Option<JavaRequirement> {
+    let content = std::fs::read_to_string(json_path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let java = json.get("javaVersion")?;
+    let major = java.get("majorVersion").and_then(|v| v.as_u64())? as u32;
+    let component = java
+        .get("component")
+        .and_then(|v| v.as_str())
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| component_for_major(major).to_string());
+    Some(JavaRequirement { component, major_version: major, from_version_json: true })
+}
+
+/// Java que pide una versión de Minecraft según el `javaVersion` de su JSON. Si el JSON aún no
+/// está descargado (o es tan antiguo que no lo trae) se estima a partir del número de versión
+pub fn java_requirement_for_version(mc_version: &str) -> JavaRequirement {
+    let json_path = crate::store::version_dir(mc_version).join(format!("{}.json", mc_version));
+    if let Some(requirement) = read_java_requirement(&json_path) {
+        return requirement;
+    }
+    let major = crate::launcher::get_required_java_version_for_minecraft(mc_version) as u32;
+    JavaRequirement {
+        component: component_for_major(major).to_string(),
+        major_version: major,
+        from_version_json: false,
+    }
+}
+
+/// Nombre de la plataforma en el manifest de Mojang
+fn platform_key() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        ("macos", "x86_64") => Some("mac-os"),
+        ("macos", "aarch64") => Some("mac-os-arm64"),
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        _ => None,
+    }
+}
+
+/// Carpeta del runtime de Mojang de un componente (`runtime/<component>`)
+pub fn component_dir(component: &str) -> PathBuf {
+    crate::paths::runtime_dir().join(component)
+}
+
+fn component_java_path(component: &str) -> PathBuf {
+    java_path_in(&component_dir(component))
+}
+
+fn java_path_in(dir: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        dir.join("bin").join("java.exe")
+    } else if cfg!(target_os = "macos") {
+        dir.join("jre.bundle").join("Contents").join("Home").join("bin").join("java")
+    } else {
+        dir.join("bin").join("java")
+    }
+}
+
+/// Ejecutable de Java del runtime de Mojang si está instalado por completo
+pub fn installed_runtime_java(component: &str) -> Option<PathBuf> {
+    let java = component_java_path(component);
+    (component_dir(component).join(INSTALLED_MARKER).exists() && java.exists()).then_some(java)
+}
+
+async fn fetch_runtime_entry(component: &str) -> Result<RuntimeEntry, String> {
+    let platform = platform_key().ok_or_else(|| {
+        format!("No Mojang Java runtime for {}-{}", std::env::consts::OS, std::env::consts::ARCH)
+    })?;
+    let all: HashMap<String, HashMap<String, Vec<RuntimeEntry>>> = crate::http_client::HTTP_CLIENT
+        .get(JAVA_RUNTIME_MANIFEST_URL)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch Java runtime manifest: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Java runtime manifest: {}", e))?;
+    all.get(platform)
+        .and_then(|components| components.get(component))
+        .and_then(|entries| entries.first())
+        .cloned()
+        .ok_or_else(|| format!("Java runtime '{}' is not available for {}", component, platform))
+}
+
+async fn fetch_runtime_manifest(entry: &RuntimeEntry) -> Result<RuntimeManifest, String> {
+    use sha1::{Digest, Sha1};
+
+    let bytes = crate::http_client::HTTP_CLIENT
+        .get(&entry.manifest.url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch runtime file list: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read runtime file list: {}", e))?;
+    let actual = format!("{:x}", Sha1::digest(&bytes));
+    if !actual.eq_ignore_ascii_case(&entry.manifest.sha1) {
+        return Err(format!("SHA1 mismatch for runtime file list: expected {}, got {}", entry.manifest.sha1, actual));
+    }
+    serde_json::from_slice(&bytes).map_err(|e| format!("Failed to parse runtime file list: {}", e))
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to set executable bit on {}: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_link(target: &str, link: &Path) -> Result<(), String> {
+    let _ = std::fs::remove_file(link);
+    std::os::unix::fs::symlink(target, link)
+        .map_err(|e| format!("Failed to create link {}: {}", link.display(), e))
+}
+
+#[cfg(not(unix))]
+fn create_link(_target: &str, _link: &Path) -> Result<(), String> {
+    // Los runtimes de Windows no traen enlaces
+    Ok(())
+}
+
+/// Sustituye `dir` por `staging`. Si algo falla la versión anterior vuelve a su sitio
+fn swap_in_staging(staging: &Path, dir: &Path) -> Result<(), String> {
+    let previous = dir.with_extension("old");
+    let _ = std::fs::remove_dir_all(&previous);
+    let had_previous = dir.exists();
+    if had_previous {
+        std::fs::rename(dir, &previous).map_err(|e| format!("Failed to move {} aside: {}", dir.display(), e))?;
+    }
+    if let Err(e) = std::fs::rename(staging, dir) {
+        if had_previous {
+            let _ = std::fs::rename(&previous, dir);
+        }
+        return Err(format!("Failed to move the new runtime into {}: {}", dir.display(), e));
+    }
+    if had_previous {
+        if let Err(e) = std::fs::remove_dir_all(&previous) {
+            log::warn!("Failed to remove previous runtime {}: {}", previous.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Instala (o repara) un runtime del manifest de Mojang en `runtime/<component>` verificando el
+/// sha1 de cada archivo. Se monta en una carpeta aparte y solo sustituye a la instalada si todo
+/// salió bien; los archivos que ya estaban correctos se reutilizan. Devuelve la ruta del ejecutable de Java
+pub async fn install_mojang_runtime(component: &str) -> Result<PathBuf, String> {
+    let entry = fetch_runtime_entry(component).await?;
+    let manifest = fetch_runtime_manifest(&entry).await?;
+    let dir = component_dir(component);
+    let staging = dir.with_extension("staging");
+    log::info!("Installing Java runtime {} ({}) into {}", component, entry.version.name, dir.display());
+
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+    let result = stage_mojang_runtime(component, &manifest, &dir, &staging).await;
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    if !java_path_in(&staging).exists() {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(format!("Java runtime {} downloaded but {} is missing", component, java_path_in(&staging).display()));
+    }
+    let marker = staging.join(INSTALLED_MARKER);
+    std::fs::write(&marker, &entry.version.name).map_err(|e| format!("Failed to write {}: {}", marker.display(), e))?;
+    if let Err(e) = swap_in_staging(&staging, &dir) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    log::info!("Java runtime {} ({}) installed", component, entry.version.name);
+    Ok(component_java_path(component))
+}
+
+/// Monta el runtime completo en `staging`, copiando de `dir` lo que ya coincide con el manifest
+async fn stage_mojang_runtime(component: &str, manifest: &RuntimeManifest, dir: &Path, staging: &Path) -> Result<(), String> {
+    let mut to_download: Vec<(String, PathBuf, String)> = Vec::new();
+    let mut executables: Vec<PathBuf> = Vec::new();
+    let mut links: Vec<(String, PathBuf)> = Vec::new();
+    for (rel, file) in &manifest.files {
+        let path = staging.join(rel);
+        match file.file_type.as_str() {
+            "directory" => {
+                std::fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+            }
+            "file" => {
+                let Some(downloads) = &file.downloads else { continue };
+                let installed = dir.join(rel);
+                let up_to_date = installed.exists()
+                    && crate::store::sha1_file(&installed).map(|s| s.eq_ignore_ascii_case(&downloads.raw.sha1)).unwrap_or(false);
+                let reused = up_to_date && crate::dedup::link_or_copy(&installed, &path, true).is_ok();
+                if !reused {
+                    to_download.push((downloads.raw.url.clone(), path.clone(), downloads.raw.sha1.clone()));
+                }
+                if file.executable {
+                    executables.push(path);
+                }
+            }
+            "link" => {
+                if let Some(target) = &file.target {
+                    links.push((target.clone(), path));
+                }
+            }
+            other => log::warn!("Unknown runtime entry type '{}' for {}", other, rel),
+        }
+    }
+
+    if !to_download.is_empty() {
+        use futures_util::stream::{self, StreamExt};
+        log::info!("Downloading {} files of Java runtime {}", to_download.len(), component);
+        let parallel = num_cpus::get().saturating_mul(4).clamp(8, 32);
+        // El cliente global corta a los 30 s y el archivo `modules` de un runtime pesa decenas de MB
+        let client = reqwest::Client::builder()
+            .user_agent("KindlyKlanKlient/1.0")
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .timeout(std::time::Duration::from_secs(600))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        let results: Vec<Result<(), String>> = stream::iter(to_download.into_iter())
+            .map(|(url, path, sha1)| {
+                let client = client.clone();
+                async move {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                    }
+                    crate::store::download_to_store(&client, &url, &path, Some(&sha1), &crate::sync_engine::SyncOptions::default()).await
+                }
+            })
+            .buffer_unordered(parallel)
+            .collect()
+            .await;
+        // A diferencia de las librerías, un runtime incompleto no sirve: cualquier fallo aborta
+        if let Some(error) = results.into_iter().find_map(|r| r.err()) {
+            return Err(format!("Failed to install Java runtime {}: {}", component, error));
+        }
+    }
+
+    for path in &executables {
+        set_executable(path)?;
+    }
+    for (target, link) in &links {
+        if let Some(parent) = link.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        create_link(target, link)?;
+    }
+    Ok(())
+}
+
+/// Sistema, arquitectura y formato de archivo de Adoptium para esta máquina
+pub fn adoptium_platform() -> Result<(&'static str, &'static str, &'static str), String> {
+    let (os, extension) = match std::env::consts::OS {
+        "windows" => ("windows", "zip"),
+        "macos" => ("mac", "tar.gz"),
+        "linux" => ("linux", "tar.gz"),
+        other => return Err(format!("Java downloads are not supported on {}", other)),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "aarch64",
+        "arm" => "arm",
+        other => return Err(format!("Java downloads are not supported on {} architecture", other)),
+    };
+    Ok((os, arch, extension))
+}
+
+pub fn adoptium_url(java_version: &str) -> Result<String, String> {
+    let (os, arch, _) = adoptium_platform()?;
+    Ok(format!(
+        "https://api.adoptium.net/v3/binary/latest/{}/ga/{}/{}/jdk/hotspot/normal/eclipse",
+        java_version, os, arch
+    ))
+}
+
+fn extract_zip(archive: &Path, target: &Path, on_entry: &mut dyn FnMut(usize, Option<usize>)) -> Result<(), String> {
+    let reader = std::fs::File::open(archive).map_err(|e| format!("Open zip failed: {}", e))?;
+    let mut zip = zip::ZipArchive::new(reader).map_err(|e| format!("Read zip failed: {}", e))?;
+    let total = zip.len();
+    for i in 0..total {
+        let mut file = zip.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
+        let Some(rel) = file.enclosed_name().map(|p| p.to_path_buf()) else { continue };
+        let outpath = target.join(rel);
+        if file.is_dir() {
+            std::fs::create_dir_all(&outpath).map_err(|e| format!("Create dir failed: {}", e))?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                std::fs::create_dir_all(p).map_err(|e| format!("Create parent failed: {}", e))?;
+            }
+            let mut outfile = std::fs::File::create(&outpath).map_err(|e| format!("Create file failed: {}", e))?;
+            std::io::copy(&mut file, &mut outfile).map_err(|e| format!("Write file failed: {}", e))?;
+            #[cfg(unix)]
+            if let Some(mode) = file.unix_mode() {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = std::fs::set_permissions(&outpath, std::fs::Permissions::from_mode(mode));
+            }
+        }
+        on_entry(i + 1, Some(total));
+    }
+    Ok(())
+}
+
+fn extract_tar_gz(archive: &Path, target: &Path, on_entry: &mut dyn FnMut(usize, Option<usize>)) -> Result<(), String> {
+    let reader = std::fs::File::open(archive).map_err(|e| format!("Open tar.gz failed: {}", e))?;
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    // Conservar los permisos: sin el bit de ejecución bin/java no arranca
+    tar.set_preserve_permissions(true);
+    let entries = tar.entries().map_err(|e| format!("Read tar.gz failed: {}", e))?;
+    for (i, entry) in entries.enumerate() {
+        let mut entry = entry.map_err(|e| format!("Tar entry failed: {}", e))?;
+        // unpack_in rechaza rutas que salgan de la carpeta destino
+        entry.unpack_in(target).map_err(|e| format!("Extract file failed: {}", e))?;
+        on_entry(i + 1, None);
+    }
+    Ok(())
+}
+
+/// Carpeta raíz del JDK dentro de lo extraído. En macOS el JDK va dentro de `Contents/Home`
+fn find_java_home(extracted: &Path) -> Option<PathBuf> {
+    let exe = if cfg!(target_os = "windows") { "java.exe" } else { "java" };
+    let mut candidates = vec![extracted.to_path_buf()];
+    if let Ok(entries) = std::fs::read_dir(extracted) {
+        candidates.extend(entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()));
+    }
+    candidates.into_iter().find_map(|dir| {
+        [dir.clone(), dir.join("Contents").join("Home")]
+            .into_iter()
+            .find(|home| home.join("bin").join(exe).is_file())
+    })
+}
+
+/// Versión mayor de Java de la salida de `java -version` ("1.8.0_392" → 8, "21.0.2" → 21)
+fn parse_java_major(output: &str) -> Option<u32> {
+    let version = output.split('"').nth(1)?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        return parts.next()?.parse().ok();
+    }
+    Some(first)
+}
+
+/// Ejecuta `java -version` y comprueba que arranca y que es la versión esperada
+pub fn validate_java(java: &Path, expected_major: Option<u32>) -> Result<u32, String> {
+    let mut cmd = std::process::Command::new(java);
+    cmd.arg("-version");
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+    let output = cmd.output().map_err(|e| format!("Failed to run {}: {}", java.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("{} -version exited with {}", java.display(), output.status));
+    }
+    // java -version escribe en stderr
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
+    let major = parse_java_major(&text).ok_or_else(|| format!("Could not read Java version from: {}", text.trim()))?;
+    if let Some(expected) = expected_major {
+        if major != expected {
+            return Err(format!("Expected Java {} but {} reports Java {}", expected, java.display(), major));
+        }
+    }
+    Ok(major)
+}
+
+/// Extrae un JDK descargado (zip o tar.gz) y lo instala en `java_dir` si `bin/java` funciona.
+/// Se extrae y valida en una carpeta temporal; el JDK anterior solo se sustituye si el nuevo es válido
+pub fn install_java_archive(
+    archive: &Path,
+    java_dir: &Path,
+    expected_major: Option<u32>,
+    on_entry: &mut dyn FnMut(usize, Option<usize>),
+) -> Result<PathBuf, String> {
+    let file_name = java_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let staging = java_dir.with_file_name(format!(".{}.extracting", file_name));
+    if staging.exists() {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    std::fs::create_dir_all(&staging).map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+
+    let result = (|| -> Result<PathBuf, String> {
+        if archive.to_string_lossy().ends_with(".zip") {
+            extract_zip(archive, &staging, on_entry)?;
+        } else {
+            extract_tar_gz(archive, &staging, on_entry)?;
+        }
+        let home = find_java_home(&staging).ok_or("No Java directory found after extraction")?;
+        let exe = if cfg!(target_os = "windows") { "java.exe" } else { "java" };
+        // Se valida antes de tocar el JDK instalado: una descarga mala no debe romper el que funciona
+        validate_java(&home.join("bin").join(exe), expected_major)?;
+        swap_in_staging(&home, java_dir)?;
+        Ok(java_dir.join("bin").join(exe))
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+#[tauri::command]
+pub async fn get_java_requirement(minecraft_version: String) -> Result<JavaRequirement, String> {
+    Ok(java_requirement_for_version(&minecraft_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// tar.gz con un JDK falso cuyo `bin/java` es un script con este cuerpo
+    #[cfg(unix)]
+    fn fake_jdk_archive(dir: &Path, java_script: &str) -> PathBuf {
+        let archive = dir.join("jdk.tar.gz");
+        let file = std::fs::File::create(&archive).unwrap();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(file, flate2::Compression::fast()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(java_script.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, "jdk-21.0.2+13/bin/java", java_script.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        archive
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_broken_download_keeps_the_installed_jdk() {
+        let dir = crate::test_fixtures::temp_dir("java-archive-broken");
+        let java_dir = dir.join("java21");
+        std::fs::create_dir_all(java_dir.join("bin")).unwrap();
+        std::fs::write(java_dir.join("bin").join("java"), "installed").unwrap();
+
+        let archive = fake_jdk_archive(&dir, "#!/bin/sh\nexit 1\n");
+        assert!(install_java_archive(&archive, &java_dir, Some(21), &mut |_, _| {}).is_err());
+        assert_eq!(std::fs::read_to_string(java_dir.join("bin").join("java")).unwrap(), "installed");
+
+        let archive = fake_jdk_archive(&dir, "#!/bin/sh\necho 'openjdk version \"21.0.2\" 2024-01-16' >&2\n");
+        let java = install_java_archive(&archive, &java_dir, Some(21), &mut |_, _| {}).unwrap();
+        assert_eq!(java, java_dir.join("bin").join("java"));
+        assert_ne!(std::fs::read_to_string(&java).unwrap(), "installed");
+        assert!(!java_dir.with_extension("old").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
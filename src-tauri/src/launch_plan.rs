@@ -0,0 +1,851 @@
+use crate::launch_profiles::ResolvedLaunchSettings;
+use crate::models::{ClasspathEntry, QuickPlayTarget, ResolvedLaunchCommand};
+use crate::sync_engine::SyncOptions;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Cuenta con la que se lanza el juego
+#[derive(Debug, Clone)]
+pub struct LaunchAccount {
+    pub username: String,
+    pub uuid: String,
+    pub access_token: String,
+}
+
+/// Lo que la interfaz puede cambiar en cada lanzamiento
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOverrides {
+    /// Java elegida por la interfaz; la del perfil de la instancia tiene prioridad
+    pub java_path: Option<String>,
+    pub min_ram_gb: Option<f64>,
+    pub max_ram_gb: Option<f64>,
+    /// Sustituye al servidor declarado en el manifest de la instancia
+    pub quick_play: Option<QuickPlayTarget>,
+}
+
+pub const DEFAULT_SERVER_PORT: u16 = 25565;
+
+/// Separa "host:puerto" (o "[ipv6]:puerto"). El puerto es `None` si la dirección no lo lleva
+pub fn split_server_address(address: &str) -> (String, Option<u16>) {
+    let address = address.trim();
+    if let Some(rest) = address.strip_prefix('[') {
+        if let Some((host, tail)) = rest.split_once(']') {
+            return (host.to_string(), tail.strip_prefix(':').and_then(|p| p.parse().ok()));
+        }
+    }
+    match address.rsplit_once(':') {
+        // Más de un ':' sin corchetes es una IPv6 sin puerto
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), Some(port)),
+            Err(_) => (address.to_string(), None),
+        },
+        _ => (address.to_string(), None),
+    }
+}
+
+/// Como `split_server_address`, con el puerto de Minecraft si no se indica
+pub fn parse_server_address(address: &str) -> (String, u16) {
+    let (host, port) = split_server_address(address);
+    (host, port.unwrap_or(DEFAULT_SERVER_PORT))
+}
+
+/// Todo lo necesario para arrancar Minecraft. Construirlo no lanza nada, así que se puede revisar
+/// (o probar) antes de crear el proceso
+#[derive(Debug, Clone)]
+pub struct LaunchPlan {
+    pub instance_id: String,
+    pub java_path: String,
+    pub jvm_args: Vec<String>,
+    pub classpath: String,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+    pub working_dir: PathBuf,
+    pub env: Vec<(String, String)>,
+    /// Id de la versión usada (la del mod loader si hay)
+    pub version_id: String,
+    /// Nativos que se extraen al lanzar; su carpeta se borra cuando el juego se cierra
+    pub natives: Option<crate::natives::NativesPlan>,
+}
+
+/// Instancias sincronizadas antes de registrar su versión: busca el JSON del mod loader en
+/// `versions/` o usa la versión vanilla
+fn detect_version_id(instance_dir: &Path, minecraft_version: &str) -> String {
+    let versions_dir = instance_dir.join("versions");
+    let mut fallback: Option<String> = None;
+
+    if let Ok(entries) = std::fs::read_dir(&versions_dir) {
+        for entry in entries.flatten() {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            let json_path = entry.path().join(format!("{}.json", dir_name));
+            let Some(json) = std::fs::read_to_string(&json_path)
+                .ok()
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+            else {
+                continue;
+            };
+            // Un JSON de mod loader hereda de la versión vanilla que se lanza
+            if json.get("inheritsFrom").and_then(|v| v.as_str()) != Some(minecraft_version) {
+                continue;
+            }
+            let is_clear_mod_loader = ["fabric-loader-", "forge-", "neoforge-"]
+                .iter()
+                .any(|prefix| dir_name.starts_with(prefix));
+            if is_clear_mod_loader {
+                return dir_name;
+            }
+            fallback.get_or_insert(dir_name);
+        }
+    }
+
+    fallback.unwrap_or_else(|| minecraft_version.to_string())
+}
+
+pub struct LaunchPlanBuilder {
+    instance_id: String,
+    instance_dir: PathBuf,
+    minecraft_version: String,
+    version_id: Option<String>,
+    mod_loader: Option<(String, String)>,
+    account: Option<LaunchAccount>,
+    settings: Option<ResolvedLaunchSettings>,
+    java_path: Option<String>,
+    asset_index: Option<String>,
+    game_assets: Option<PathBuf>,
+    quick_play: Option<QuickPlayTarget>,
+    env: Vec<(String, String)>,
+}
+
+impl LaunchPlan {
+    pub fn builder(instance_id: &str, instance_dir: &Path, minecraft_version: &str) -> LaunchPlanBuilder {
+        LaunchPlanBuilder {
+            instance_id: instance_id.to_string(),
+            instance_dir: instance_dir.to_path_buf(),
+            minecraft_version: minecraft_version.to_string(),
+            version_id: None,
+            mod_loader: None,
+            account: None,
+            settings: None,
+            java_path: None,
+            asset_index: None,
+            game_assets: None,
+            quick_play: None,
+            env: Vec::new(),
+        }
+    }
+
+    /// Comando listo para lanzar, con la salida capturada
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.java_path);
+        #[cfg(target_os = "windows")]
+        {
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+        command
+            .args(&self.jvm_args)
+            .arg("-cp")
+            .arg(&self.classpath)
+            .arg(&self.main_class)
+            .args(&self.game_args)
+            .envs(self.env.iter().cloned())
+            .current_dir(&self.working_dir)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        command
+    }
+}
+
+impl LaunchPlanBuilder {
+    /// Id exacto de la versión a lanzar (p. ej. la del mod loader). Sin él se usa el registrado en la instancia
+    pub fn version_id(mut self, version_id: Option<&str>) -> Self {
+        self.version_id = version_id.map(|v| v.to_string());
+        self
+    }
+
+    pub fn mod_loader(mut self, loader_type: &str, loader_version: &str) -> Self {
+        self.mod_loader = Some((loader_type.to_string(), loader_version.to_string()));
+        self
+    }
+
+    pub fn account(mut self, account: LaunchAccount) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    pub fn settings(mut self, settings: ResolvedLaunchSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    pub fn java_path(mut self, java_path: &str) -> Self {
+        self.java_path = Some(java_path.to_string());
+        self
+    }
+
+    pub fn asset_index(mut self, asset_index: &str) -> Self {
+        self.asset_index = Some(asset_index.to_string());
+        self
+    }
+
+    /// Carpeta con los assets por nombre de los índices antiguos (ver `legacy_assets`)
+    pub fn game_assets(mut self, dir: Option<PathBuf>) -> Self {
+        self.game_assets = dir;
+        self
+    }
+
+    pub fn quick_play(mut self, target: Option<QuickPlayTarget>) -> Self {
+        self.quick_play = target;
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Id exacto a lanzar: el indicado, el registrado en la instancia o, para instancias antiguas,
+    /// el que se detecte
+    fn resolve_version_id(&self) -> String {
+        self.version_id.clone().unwrap_or_else(|| {
+            crate::version_resolver::recorded_version_id(&self.instance_dir)
+                .unwrap_or_else(|| detect_version_id(&self.instance_dir, &self.minecraft_version))
+        })
+    }
+
+    /// Valores de los `${...}` de los JSON de versión
+    fn launch_variables(
+        &self,
+        account: &LaunchAccount,
+        settings: &ResolvedLaunchSettings,
+        version: &crate::version_resolver::ResolvedVersion,
+        classpath: &str,
+        natives_dir: Option<&Path>,
+    ) -> HashMap<String, String> {
+        let game_dir = self.instance_dir.to_string_lossy().to_string();
+        let assets_dir = crate::store::assets_dir().to_string_lossy().to_string();
+        // Sin nativos que extraer (1.19+) LWJGL los saca del classpath; la ruta solo tiene que existir en los argumentos
+        let natives_dir = natives_dir
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| self.instance_dir.join("versions").join(&version.id).join("natives"));
+        let uuid = account.uuid.replace('-', "");
+
+        let vars = [
+            ("auth_player_name", account.username.clone()),
+            ("auth_uuid", uuid.clone()),
+            ("auth_access_token", account.access_token.clone()),
+            ("auth_session", format!("token:{}:{}", account.access_token, uuid)),
+            ("auth_xuid", "0".to_string()),
+            ("clientid", String::new()),
+            ("user_type", "msa".to_string()),
+            ("user_properties", "{}".to_string()),
+            ("version_name", version.id.clone()),
+            ("version_type", version.version_type.clone()),
+            ("game_directory", game_dir),
+            ("game_assets", self.game_assets.as_ref().map_or_else(|| assets_dir.clone(), |p| p.to_string_lossy().to_string())),
+            ("assets_root", assets_dir),
+            ("assets_index_name", self.asset_index.clone().unwrap_or_default()),
+            ("resolution_width", settings.window_width.to_string()),
+            ("resolution_height", settings.window_height.to_string()),
+            ("natives_directory", natives_dir.to_string_lossy().to_string()),
+            ("library_directory", crate::store::libraries_dir().to_string_lossy().to_string()),
+            ("classpath", classpath.to_string()),
+            ("classpath_separator", if cfg!(target_os = "windows") { ";" } else { ":" }.to_string()),
+            ("launcher_name", "KindlyKlanKlient".to_string()),
+            ("launcher_version", env!("CARGO_PKG_VERSION").to_string()),
+        ];
+        vars.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+
+    /// Resuelve versión, classpath y argumentos. No escribe en disco: los nativos se extraen al lanzar
+    pub fn build(self) -> Result<LaunchPlan, String> {
+        let account = self.account.clone().ok_or("Launch plan needs an account")?;
+        let settings = self.settings.clone().ok_or("Launch plan needs launch settings")?;
+        let java_path = self.java_path.clone().ok_or("Launch plan needs a Java executable")?;
+        if self.asset_index.is_none() {
+            return Err("Launch plan needs an asset index".to_string());
+        }
+
+        let version_id = self.resolve_version_id();
+        let version = crate::version_resolver::resolve_version(&self.instance_dir, &version_id)?;
+        let classpath = crate::launcher::build_classpath_for_version(&self.instance_dir, &version)?;
+        let natives = crate::natives::plan_natives(&self.instance_dir, &version);
+
+        // Quick Play solo si la versión lo declara (1.20+); si no, --server/--port para multijugador
+        let quick_play_feature = self.quick_play.as_ref().and_then(|target| match target {
+            QuickPlayTarget::Multiplayer { .. } => Some("is_quick_play_multiplayer"),
+            QuickPlayTarget::Singleplayer { .. } => Some("is_quick_play_singleplayer"),
+            QuickPlayTarget::Realms { .. } => Some("is_quick_play_realms"),
+            QuickPlayTarget::None => None,
+        });
+        let quick_play_supported = quick_play_feature.is_some_and(|f| crate::arguments::declares_feature(&version, f));
+
+        let mut ctx = crate::arguments::RuleContext::current()
+            .feature("has_custom_resolution", true)
+            .feature("is_demo_user", false);
+        if let (Some(feature), true) = (quick_play_feature, quick_play_supported) {
+            ctx = ctx.feature(feature, true);
+        }
+        let templates = crate::arguments::ArgumentTemplates::from_version(&version, &ctx);
+        let mut vars = self.launch_variables(&account, &settings, &version, &classpath, natives.as_ref().map(|n| n.target.as_path()));
+        let quick_play_var = match &self.quick_play {
+            Some(QuickPlayTarget::Multiplayer { address }) => Some(("quickPlayMultiplayer", address)),
+            Some(QuickPlayTarget::Singleplayer { world }) => Some(("quickPlaySingleplayer", world)),
+            Some(QuickPlayTarget::Realms { realm_id }) => Some(("quickPlayRealms", realm_id)),
+            Some(QuickPlayTarget::None) | None => None,
+        };
+        if let Some((key, value)) = quick_play_var {
+            vars.insert(key.to_string(), value.clone());
+        }
+
+        let mut jvm_args = crate::launcher::build_minecraft_jvm_args(
+            &account.access_token,
+            settings.min_ram_gb,
+            settings.max_ram_gb,
+            &settings.garbage_collector,
+            &settings.jvm_args,
+        )?;
+        let version_jvm_args: Vec<String> = templates.jvm.iter().map(|a| crate::arguments::substitute(a, &vars)).collect();
+        jvm_args.extend(crate::arguments::finalize_jvm_arguments(version_jvm_args, &vars["classpath_separator"]));
+        crate::launcher::ensure_required_add_opens(self.mod_loader.as_ref().map(|(t, _)| t.as_str()), &mut jvm_args);
+
+        let mut game_args: Vec<String> = templates.game.iter().map(|a| crate::arguments::substitute(a, &vars)).collect();
+        // Las versiones con minecraftArguments no traen la resolución en sus reglas
+        if templates.legacy {
+            game_args.extend([
+                "--width".to_string(), settings.window_width.to_string(),
+                "--height".to_string(), settings.window_height.to_string(),
+            ]);
+        }
+        if settings.fullscreen {
+            game_args.push("--fullscreen".to_string());
+        }
+        match (&self.quick_play, quick_play_supported) {
+            (Some(QuickPlayTarget::Multiplayer { address }), false) => {
+                let (host, port) = parse_server_address(address);
+                game_args.extend(["--server".to_string(), host, "--port".to_string(), port.to_string()]);
+            }
+            (Some(QuickPlayTarget::None), _) => {}
+            (Some(target), false) => {
+                log::warn!("{} does not support Quick Play, ignoring {:?}", version.id, target);
+            }
+            _ => {}
+        }
+
+        Ok(LaunchPlan {
+            instance_id: self.instance_id,
+            java_path,
+            jvm_args,
+            classpath,
+            main_class: version.main_class,
+            game_args,
+            working_dir: self.instance_dir,
+            env: self.env,
+            version_id,
+            natives,
+        })
+    }
+}
+
+/// Instancia a preparar, sea remota o local
+pub struct LaunchTarget<'a> {
+    pub instance_id: &'a str,
+    pub instance_dir: &'a Path,
+    pub minecraft_version: &'a str,
+    /// Id exacto de la versión; sin él se usa el registrado o el que se detecte
+    pub version_id: Option<&'a str>,
+    /// Tipo y versión del mod loader, si hay
+    pub mod_loader: Option<(&'a str, &'a str)>,
+}
+
+fn emit_launch_progress(app_handle: &AppHandle, percentage: u32, status: &str) {
+    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
+        "current": percentage,
+        "total": 100,
+        "percentage": percentage,
+        "current_file": "",
+        "status": status
+    }));
+}
+
+/// Si ya hay alguna librería de LWJGL en el almacén compartido
+fn libraries_have_lwjgl() -> Result<bool, String> {
+    for entry in walkdir::WalkDir::new(crate::store::libraries_dir()) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entry.file_type().is_file() && entry.path().to_string_lossy().contains("lwjgl") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Id registrado en la instancia o, para instancias antiguas, el detectado, que queda registrado
+/// para los siguientes lanzamientos
+fn ensure_recorded_version_id(instance_id: &str, instance_dir: &Path, minecraft_version: &str) -> String {
+    if let Some(vid) = crate::version_resolver::recorded_version_id(instance_dir) {
+        return vid;
+    }
+    let detected = detect_version_id(instance_dir, minecraft_version);
+    log::warn!("Instance {} has no recorded version, using {}", instance_id, detected);
+    if let Err(e) = crate::version_resolver::record_version_id(instance_dir, &detected) {
+        log::warn!("{}", e);
+    }
+    detected
+}
+
+/// Prepara el lanzamiento de una instancia (descarga lo que falte) sin arrancar el juego.
+/// Sin Java en `overrides` se usa la del perfil o la que pida la versión
+pub async fn prepare_launch(
+    app_handle: &AppHandle,
+    target: LaunchTarget<'_>,
+    account: LaunchAccount,
+    overrides: LaunchOverrides,
+) -> Result<LaunchPlan, String> {
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
+    let LaunchTarget { instance_id, instance_dir, minecraft_version, .. } = target;
+
+    let version_id = match target.version_id {
+        Some(vid) => vid.to_string(),
+        None => ensure_recorded_version_id(instance_id, instance_dir, minecraft_version),
+    };
+
+    emit_launch_progress(app_handle, 0, "Verificando archivos...");
+    crate::instances::ensure_minecraft_client_present(instance_dir, minecraft_version, &SyncOptions::default()).await?;
+
+    emit_launch_progress(app_handle, 33, "Verificando librerías...");
+    crate::instances::ensure_version_libraries(instance_dir, minecraft_version, &SyncOptions::default()).await?;
+    // Librerías propias de Fabric/Forge/NeoForge
+    if version_id != minecraft_version {
+        crate::instances::ensure_mod_loader_libraries(instance_dir, &version_id, &SyncOptions::default()).await?;
+    }
+
+    let _ = std::fs::create_dir_all(crate::store::libraries_dir());
+    let _ = std::fs::create_dir_all(instance_dir.join("mods"));
+    if !libraries_have_lwjgl()? {
+        crate::instances::ensure_minecraft_client_present(instance_dir, minecraft_version, &SyncOptions::default()).await?;
+    }
+
+    emit_launch_progress(app_handle, 66, "Verificando assets...");
+    let asset_index_id = crate::instances::ensure_assets_present(app_handle, instance_dir, minecraft_version).await?;
+    let game_assets = crate::legacy_assets::prepare_legacy_assets(instance_dir, &asset_index_id).await?;
+    emit_launch_progress(app_handle, 100, "Completado");
+    let _ = app_handle.emit("asset-download-completed", serde_json::json!({ "phase": "complete" }));
+
+    // El perfil de la instancia (si tiene) manda sobre la configuración global
+    let settings = crate::launch_profiles::resolve_launch_settings(instance_id, overrides.min_ram_gb, overrides.max_ram_gb).await;
+
+    // El servidor del evento viene del manifest sincronizado salvo que se pida otro destino (o
+    // ninguno, con `QuickPlayTarget::None`)
+    let quick_play = overrides.quick_play.or_else(|| {
+        crate::instances::load_manifest_history(instance_dir)
+            .ok()
+            .flatten()
+            .and_then(|history| history.server)
+            .map(|address| QuickPlayTarget::Multiplayer { address })
+    });
+
+    // La JVM fijada en el perfil tiene prioridad sobre la elegida por la interfaz
+    let java_path = match (&settings.java_path, overrides.java_path) {
+        (None, Some(path)) => path,
+        _ => crate::java_discovery::java_for_instance(instance_id, minecraft_version).await?,
+    };
+
+    let mut builder = LaunchPlan::builder(instance_id, instance_dir, minecraft_version)
+        .version_id(Some(&version_id))
+        .account(account)
+        .settings(settings)
+        .java_path(&java_path)
+        .asset_index(&asset_index_id)
+        .game_assets(game_assets)
+        .quick_play(quick_play);
+    if let Some((loader_type, loader_version)) = target.mod_loader {
+        builder = builder.mod_loader(loader_type, loader_version);
+    }
+    builder.build()
+}
+
+/// Prepara el lanzamiento de una instancia remota sin arrancar el juego
+pub async fn prepare_remote_launch(
+    app_handle: &AppHandle,
+    instance_id: &str,
+    minecraft_version: &str,
+    account: LaunchAccount,
+    overrides: LaunchOverrides,
+) -> Result<LaunchPlan, String> {
+    let instance_dir = crate::launcher::get_instance_directory(instance_id);
+    let target = LaunchTarget { instance_id, instance_dir: &instance_dir, minecraft_version, version_id: None, mod_loader: None };
+    prepare_launch(app_handle, target, account, overrides).await
+}
+
+/// Lanza un plan: registra el proceso, captura su salida (ver `game_logs`) y avisa con `minecraft_exited`
+/// cuando termina. Devuelve el PID
+pub fn spawn_launch_plan(app_handle: &AppHandle, plan: &LaunchPlan) -> Result<u32, String> {
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
+    let processes_state = app_handle
+        .try_state::<Arc<Mutex<HashMap<String, u32>>>>()
+        .map(|state| state.inner().clone())
+        .ok_or("Failed to get processes state")?;
+
+    log::info!("Launching instance {} ({}) with main class: {}", plan.instance_id, plan.version_id, plan.main_class);
+    if let Some(natives) = &plan.natives {
+        natives.extract()?;
+    }
+
+    let mut child = plan.command().spawn()
+        .map_err(|e| format!("Failed to start Minecraft: {}", e))?;
+    let pid = child.id();
+
+    if let Ok(mut processes) = processes_state.lock() {
+        processes.insert(plan.instance_id.clone(), pid);
+    }
+
+    // La salida del juego va a su propio archivo en la instancia, no al log del launcher
+    let game_log = crate::game_logs::GameLogSession::start(app_handle, &plan.instance_id, &plan.working_dir, pid);
+    if let Some(stdout) = child.stdout.take() {
+        game_log.capture(stdout, "stdout");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        game_log.capture(stderr, "stderr");
+    }
+
+    let app = app_handle.clone();
+    let instance_id = plan.instance_id.clone();
+    let natives_dir = plan.natives.as_ref().map(|n| n.target.clone());
+    std::thread::spawn(move || {
+        let result = child.wait();
+        if let Ok(mut processes) = processes_state.lock() {
+            processes.remove(&instance_id);
+        }
+        if let Some(dir) = natives_dir {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+        match result {
+            Ok(status) => {
+                log::info!("Minecraft exited for instance {} with status: {:?}", instance_id, status.code());
+                let _ = app.emit("minecraft_exited", serde_json::json!({
+                    "instance_id": instance_id,
+                    "status": "exited",
+                    "code": status.code()
+                }));
+            }
+            Err(e) => {
+                log::error!("Error waiting for Minecraft process {}: {}", instance_id, e);
+                let _ = app.emit("minecraft_exited", serde_json::json!({
+                    "instance_id": instance_id,
+                    "status": "error",
+                    "error": e.to_string()
+                }));
+            }
+        }
+    });
+
+    Ok(pid)
+}
+
+/// Sustituye al token de acceso en un plan de prueba para poder ocultarlo después
+const TOKEN_SENTINEL: &str = "__KINDLYKLANKLIENT_ACCESS_TOKEN__";
+
+const TOKEN_ENV_VAR: &str = "MC_ACCESS_TOKEN";
+
+fn redact(arg: &str) -> String {
+    arg.replace(TOKEN_SENTINEL, "<redacted>")
+}
+
+/// Entrecomillado para sh; el token se deja como variable de entorno fuera de las comillas
+fn posix_quote(arg: &str) -> String {
+    arg.split(TOKEN_SENTINEL)
+        .map(|part| format!("'{}'", part.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join(&format!("\"${}\"", TOKEN_ENV_VAR))
+}
+
+/// Entrecomillado para cmd.exe, donde una comilla dentro de otras se escribe doble
+fn batch_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%").replace('"', "\"\"");
+    format!("\"{}\"", escaped.replace(TOKEN_SENTINEL, &format!("%{}%", TOKEN_ENV_VAR)))
+}
+
+fn plan_argv(plan: &LaunchPlan) -> Vec<String> {
+    let mut argv = vec![plan.java_path.clone()];
+    argv.extend(plan.jvm_args.iter().cloned());
+    argv.push("-cp".to_string());
+    argv.push(plan.classpath.clone());
+    argv.push(plan.main_class.clone());
+    argv.extend(plan.game_args.iter().cloned());
+    argv
+}
+
+/// Escribe junto a la instancia un script que repite el lanzamiento. El token no se guarda: se lee
+/// de la variable de entorno MC_ACCESS_TOKEN
+fn write_launch_script(plan: &LaunchPlan) -> Result<PathBuf, String> {
+    let argv = plan_argv(plan);
+    let working_dir = plan.working_dir.to_string_lossy().to_string();
+    let (path, content) = if cfg!(target_os = "windows") {
+        let mut lines = vec![
+            "@echo off".to_string(),
+            format!("rem Launch of {} generated by KindlyKlanKlient. Set {} before running", plan.instance_id, TOKEN_ENV_VAR),
+        ];
+        lines.extend(plan.env.iter().map(|(k, v)| format!("set \"{}={}\"", k, v.replace('%', "%%"))));
+        lines.push(format!("cd /d {}", batch_quote(&working_dir)));
+        lines.push(argv.iter().map(|a| batch_quote(a)).collect::<Vec<_>>().join(" ^\r\n  "));
+        (plan.working_dir.join("launch_command.bat"), lines.join("\r\n") + "\r\n")
+    } else {
+        let mut lines = vec![
+            "#!/bin/sh".to_string(),
+            format!("# Launch of {} generated by KindlyKlanKlient. Set {} before running", plan.instance_id, TOKEN_ENV_VAR),
+        ];
+        lines.extend(plan.env.iter().map(|(k, v)| format!("export {}={}", k, posix_quote(v))));
+        lines.push(format!("cd {} || exit 1", posix_quote(&working_dir)));
+        lines.push(format!("exec {}", argv.iter().map(|a| posix_quote(a)).collect::<Vec<_>>().join(" \\\n  ")));
+        (plan.working_dir.join("launch_command.sh"), lines.join("\n") + "\n")
+    };
+
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755));
+    }
+    Ok(path)
+}
+
+/// Versión de Minecraft de una instancia remota según el id que se registró al instalarla: la
+/// base de la cadena de `inheritsFrom` ("fabric-loader-0.15.11-1.20.1" → "1.20.1")
+fn recorded_minecraft_version(instance_id: &str, instance_dir: &Path) -> Result<String, String> {
+    let version_id = crate::version_resolver::recorded_version_id(instance_dir)
+        .ok_or_else(|| format!("Instance {} has no recorded version: sync or launch it first", instance_id))?;
+    let version = crate::version_resolver::resolve_version(instance_dir, &version_id)?;
+    Ok(version.inherits.last().cloned().unwrap_or(version_id))
+}
+
+/// Hace toda la preparación de un lanzamiento (descargas incluidas) sin arrancar Java y devuelve
+/// la línea de comandos resultante. Las instancias remotas usan la versión que tienen registrada
+#[tauri::command]
+pub async fn resolve_launch_command(
+    instance_id: String,
+    write_script: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<ResolvedLaunchCommand, String> {
+    // La cuenta es la de la sesión activa; el token no sale del launcher (ver `TOKEN_SENTINEL`)
+    let session = crate::sessions::SessionManager::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize session manager: {}", e))?
+        .get_active_session()
+        .map_err(|e| format!("Failed to get active session: {}", e))?
+        .ok_or("No active session: log in to resolve the launch command")?;
+    let account = LaunchAccount {
+        username: session.username,
+        uuid: session.uuid,
+        access_token: TOKEN_SENTINEL.to_string(),
+    };
+
+    let is_local = crate::local_instances::get_local_instances_dir()?
+        .join(&instance_id)
+        .join("instance_local.json")
+        .exists();
+    let plan = if is_local {
+        crate::local_instances::prepare_local_launch(&app_handle, &instance_id, account, LaunchOverrides::default()).await?
+    } else {
+        let instance_dir = crate::launcher::get_instance_directory(&instance_id);
+        let minecraft_version = recorded_minecraft_version(&instance_id, &instance_dir)?;
+        prepare_remote_launch(&app_handle, &instance_id, &minecraft_version, account, LaunchOverrides::default()).await?
+    };
+
+    let separator = if cfg!(target_os = "windows") { ';' } else { ':' };
+    let classpath: Vec<ClasspathEntry> = plan
+        .classpath
+        .split(separator)
+        .filter(|p| !p.is_empty())
+        .map(|p| ClasspathEntry { path: p.to_string(), exists: Path::new(p).exists() })
+        .collect();
+    let missing = classpath.iter().filter(|e| !e.exists).count();
+    if missing > 0 {
+        log::warn!("Resolved launch of {} has {} missing classpath entries", instance_id, missing);
+    }
+
+    let script_path = if write_script.unwrap_or(false) {
+        // El script necesita los nativos ya extraídos; los borra la limpieza de carpetas antiguas
+        if let Some(natives) = &plan.natives {
+            natives.extract()?;
+        }
+        Some(write_launch_script(&plan)?.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    Ok(ResolvedLaunchCommand {
+        instance_id: plan.instance_id.clone(),
+        version_id: plan.version_id.clone(),
+        java_path: plan.java_path.clone(),
+        jvm_args: plan.jvm_args.iter().map(|a| redact(a)).collect(),
+        main_class: plan.main_class.clone(),
+        classpath,
+        missing_classpath_entries: missing,
+        game_args: plan.game_args.iter().map(|a| redact(a)).collect(),
+        working_dir: plan.working_dir.to_string_lossy().to_string(),
+        env: plan.env.iter().cloned().collect(),
+        script_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_instance(name: &str) -> PathBuf {
+        crate::test_fixtures::temp_dir(&format!("launch-plan-{}", name))
+    }
+
+    fn write_version(instance_dir: &Path, id: &str, json: serde_json::Value) {
+        let dir = instance_dir.join("versions").join(id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{}.json", id)), json.to_string()).unwrap();
+    }
+
+    fn settings() -> ResolvedLaunchSettings {
+        ResolvedLaunchSettings {
+            min_ram_gb: 2.0,
+            max_ram_gb: 4.0,
+            garbage_collector: "G1".to_string(),
+            jvm_args: String::new(),
+            window_width: 854,
+            window_height: 480,
+            fullscreen: false,
+            java_path: None,
+        }
+    }
+
+    fn builder(instance_dir: &Path, minecraft_version: &str) -> LaunchPlanBuilder {
+        LaunchPlan::builder("test", instance_dir, minecraft_version)
+            .account(LaunchAccount {
+                username: "Steve".to_string(),
+                uuid: "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_string(),
+                access_token: "token".to_string(),
+            })
+            .settings(settings())
+            .java_path("java")
+            .asset_index("1.12")
+    }
+
+    #[test]
+    fn build_does_not_touch_the_instance() {
+        let dir = crate::test_fixtures::fixture_instance("launch-plan-pure", &["1.12.2"]);
+
+        let plan = builder(&dir, "1.12.2").build().unwrap();
+
+        assert_eq!(plan.version_id, "1.12.2");
+        assert!(!dir.join(".version_id").exists());
+        assert!(!dir.join(".natives").exists());
+        let natives = plan.natives.as_ref().expect("1.12.2 has natives to extract");
+        assert!(natives.target.starts_with(dir.join(".natives")));
+        let library_path = format!("-Djava.library.path={}", natives.target.display());
+        assert!(plan.jvm_args.contains(&library_path));
+        assert_eq!(
+            plan.game_args[..4],
+            ["--username", "Steve", "--version", "1.12.2"].map(String::from)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn remote_instances_use_their_recorded_version() {
+        let dir = crate::test_fixtures::fixture_instance("launch-plan-recorded", &["fabric-loader-0.15.11-1.20.1", "1.20.1"]);
+        assert!(recorded_minecraft_version("evento", &dir).unwrap_err().contains("no recorded version"));
+
+        crate::version_resolver::record_version_id(&dir, "fabric-loader-0.15.11-1.20.1").unwrap();
+        assert_eq!(recorded_minecraft_version("evento", &dir).unwrap(), "1.20.1");
+        crate::version_resolver::record_version_id(&dir, "1.20.1").unwrap();
+        assert_eq!(recorded_minecraft_version("evento", &dir).unwrap(), "1.20.1");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn build_detects_the_mod_loader_without_recording_it() {
+        let dir = temp_instance("detect");
+        write_version(&dir, "1.20.1", serde_json::json!({
+            "id": "1.20.1",
+            "mainClass": "net.minecraft.client.main.Main",
+            "arguments": { "game": ["--username", "${auth_player_name}"], "jvm": ["-cp", "${classpath}"] },
+            "libraries": []
+        }));
+        write_version(&dir, "fabric-loader-0.15.0-1.20.1", serde_json::json!({
+            "id": "fabric-loader-0.15.0-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "libraries": []
+        }));
+
+        let plan = builder(&dir, "1.20.1")
+            .quick_play(Some(QuickPlayTarget::Multiplayer { address: "play.example.net:25566".to_string() }))
+            .build()
+            .unwrap();
+
+        assert_eq!(plan.version_id, "fabric-loader-0.15.0-1.20.1");
+        assert_eq!(plan.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert!(plan.natives.is_none());
+        assert!(!dir.join(".version_id").exists());
+        // Sin la feature de Quick Play en el JSON se usa --server/--port
+        assert!(plan.game_args.ends_with(&["--server", "play.example.net", "--port", "25566"].map(String::from)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pre_1_6_versions_get_the_instance_resources_as_game_assets() {
+        let dir = crate::test_fixtures::fixture_instance("launch-plan-pre-1.6", &["1.5.2"]);
+
+        let plan = builder(&dir, "1.5.2").game_assets(Some(dir.join("resources"))).build().unwrap();
+
+        assert_eq!(plan.main_class, "net.minecraft.launchwrapper.Launch");
+        let game_dir = dir.to_string_lossy().to_string();
+        let resources = dir.join("resources").to_string_lossy().to_string();
+        assert_eq!(
+            plan.game_args,
+            [
+                "Steve", "token:token:069a79f444e94726a5befca90e38aaf5",
+                "--gameDir", game_dir.as_str(),
+                "--assetsDir", resources.as_str(),
+                "--width", "854", "--height", "480",
+            ]
+        );
+        assert!(plan.natives.is_some());
+        assert!(!dir.join(".version_id").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn quick_play_none_starts_at_the_main_menu() {
+        let dir = crate::test_fixtures::fixture_instance("launch-plan-no-quick-play", &["1.20.1"]);
+
+        let plan = builder(&dir, "1.20.1").quick_play(Some(QuickPlayTarget::None)).build().unwrap();
+
+        assert!(!plan.game_args.iter().any(|a| a.starts_with("--quickPlay") || a == "--server"));
+        assert!(plan.game_args.ends_with(&["--width", "854", "--height", "480"].map(String::from)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn script_quoting_keeps_the_token_out() {
+        let arg = format!("-Dtoken={}", TOKEN_SENTINEL);
+        assert_eq!(batch_quote(&arg), "\"-Dtoken=%MC_ACCESS_TOKEN%\"");
+        assert_eq!(posix_quote(&arg), "'-Dtoken='\"$MC_ACCESS_TOKEN\"''");
+        assert_eq!(batch_quote("say \"hi\" 100%"), "\"say \"\"hi\"\" 100%%\"");
+        assert_eq!(posix_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn build_requires_an_account() {
+        let dir = temp_instance("account");
+        let result = LaunchPlan::builder("test", &dir, "1.20.1")
+            .settings(settings())
+            .java_path("java")
+            .asset_index("1.20")
+            .build();
+        assert_eq!(result.unwrap_err(), "Launch plan needs an account");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
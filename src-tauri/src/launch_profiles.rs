@@ -0,0 +1,129 @@
+use crate::models::LaunchProfile;
+use std::path::PathBuf;
+
+const PROFILE_FILE: &str = "launch_profile.json";
+
+const GARBAGE_COLLECTORS: &[&str] = &["G1", "ZGC", "Parallel"];
+
+/// Ajustes finales de un lanzamiento tras aplicar el perfil de la instancia sobre los globales
+#[derive(Debug, Clone)]
+pub struct ResolvedLaunchSettings {
+    pub min_ram_gb: f64,
+    pub max_ram_gb: f64,
+    pub garbage_collector: String,
+    pub jvm_args: String,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub fullscreen: bool,
+    /// Java del perfil; si es `None` se elige según la versión de Minecraft
+    pub java_path: Option<String>,
+}
+
+fn profile_path(instance_id: &str) -> PathBuf {
+    crate::local_instances::get_instance_directory_smart(instance_id).join(PROFILE_FILE)
+}
+
+pub fn load_launch_profile(instance_id: &str) -> LaunchProfile {
+    std::fs::read_to_string(profile_path(instance_id))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_launch_profile(instance_id: &str, profile: &LaunchProfile) -> Result<(), String> {
+    let path = profile_path(instance_id);
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            return Err(format!("Instance directory does not exist: {}", parent.display()));
+        }
+    }
+    let json = serde_json::to_string_pretty(profile).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write launch profile: {}", e))
+}
+
+fn validate_profile(profile: &LaunchProfile) -> Result<(), String> {
+    for ram in [profile.min_ram_gb, profile.max_ram_gb].into_iter().flatten() {
+        if ram < 1.0 {
+            return Err("RAM must be at least 1 GB".to_string());
+        }
+    }
+    if let (Some(min), Some(max)) = (profile.min_ram_gb, profile.max_ram_gb) {
+        if min > max {
+            return Err("Minimum RAM cannot be greater than maximum RAM".to_string());
+        }
+    }
+    if let Some(gc) = &profile.garbage_collector {
+        if !GARBAGE_COLLECTORS.contains(&gc.as_str()) {
+            return Err(format!("Unknown garbage collector: {}", gc));
+        }
+    }
+    if profile.window_width == Some(0) || profile.window_height == Some(0) {
+        return Err("Window size must be greater than 0".to_string());
+    }
+    if let Some(java) = &profile.java_path {
+        if !std::path::Path::new(java).is_file() {
+            return Err(format!("Java executable not found: {}", java));
+        }
+    }
+    Ok(())
+}
+
+/// Ajustes de lanzamiento de una instancia: su perfil, después la RAM que manda la interfaz y
+/// por último la configuración global. La RAM del perfil tiene prioridad sobre la del lanzamiento:
+/// la interfaz siempre envía la global, y el perfil es justo lo que la sustituye para esta instancia
+pub async fn resolve_launch_settings(instance_id: &str, min_ram_gb: Option<f64>, max_ram_gb: Option<f64>) -> ResolvedLaunchSettings {
+    let profile = load_launch_profile(instance_id);
+    if (profile.min_ram_gb.is_some() && min_ram_gb.is_some()) || (profile.max_ram_gb.is_some() && max_ram_gb.is_some()) {
+        log::info!(
+            "Launch profile of {} overrides the requested RAM ({:?}-{:?} GB)",
+            instance_id,
+            min_ram_gb,
+            max_ram_gb
+        );
+    }
+    let (global_jvm_args, global_gc, global_width, global_height) = crate::commands::load_advanced_config()
+        .await
+        .unwrap_or((String::new(), "G1".to_string(), 1280, 720));
+
+    let global_ram = crate::commands::load_ram_config().await.ok();
+    let min_ram = profile.min_ram_gb.or(min_ram_gb).or(global_ram.map(|r| r.0)).unwrap_or(2.0);
+    let mut max_ram = profile.max_ram_gb.or(max_ram_gb).or(global_ram.map(|r| r.1)).unwrap_or(4.0);
+    // Un perfil que solo sube la mínima no puede dejarla por encima de la máxima global
+    if max_ram < min_ram {
+        max_ram = min_ram;
+    }
+
+    ResolvedLaunchSettings {
+        min_ram_gb: min_ram,
+        max_ram_gb: max_ram,
+        garbage_collector: profile.garbage_collector.unwrap_or(global_gc),
+        jvm_args: profile.jvm_args.unwrap_or(global_jvm_args),
+        window_width: profile.window_width.unwrap_or(global_width),
+        window_height: profile.window_height.unwrap_or(global_height),
+        fullscreen: profile.fullscreen.unwrap_or(false),
+        java_path: profile.java_path.filter(|p| std::path::Path::new(p).is_file()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_launch_profile(instance_id: String) -> Result<LaunchProfile, String> {
+    Ok(load_launch_profile(&instance_id))
+}
+
+#[tauri::command]
+pub async fn save_launch_profile(instance_id: String, profile: LaunchProfile) -> Result<(), String> {
+    validate_profile(&profile)?;
+    write_launch_profile(&instance_id, &profile)?;
+    log::info!("Saved launch profile for {}", instance_id);
+    Ok(())
+}
+
+/// Borra el perfil para que la instancia vuelva a usar la configuración global
+#[tauri::command]
+pub async fn reset_launch_profile(instance_id: String) -> Result<(), String> {
+    let path = profile_path(&instance_id);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove launch profile: {}", e))?;
+    }
+    Ok(())
+}
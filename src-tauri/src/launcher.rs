@@ -1,6 +1,5 @@
 use anyhow::Result;
 use tokio::fs;
-use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use tokio::io::AsyncWriteExt;
@@ -66,10 +65,10 @@ impl MinecraftLauncher {
             libraries: Vec<Library>,
         }
         let version_json: VersionJson = serde_json::from_str(&version_data)?;
-        let os_name = "windows";
+        let ctx = crate::arguments::RuleContext::current();
         let mut classpath = Vec::new();
         for lib in &version_json.libraries {
-            if !crate::versions::is_library_allowed(lib, os_name) { continue; }
+            if !lib.is_allowed(&ctx) { continue; }
             if let Some(downloads) = &lib.downloads {
                 if let Some(artifact) = &downloads.artifact {
                     let lib_path = self.config.libraries_dir.join(&artifact.path);
@@ -154,9 +153,9 @@ impl MinecraftLauncher {
             out.write_all(&bytes)?;
         }
 
-        let os_name = "windows";
+        let ctx = crate::arguments::RuleContext::current();
         for lib in &version_json.libraries {
-            if !crate::versions::is_library_allowed(lib, os_name) { continue; }
+            if !lib.is_allowed(&ctx) { continue; }
             if let Some(downloads) = &lib.downloads {
                 if let Some(artifact) = &downloads.artifact {
                     let lib_path = self.config.libraries_dir.join(&artifact.path);
@@ -442,11 +441,7 @@ pub async fn find_java_executable() -> Result<String, String> {
     }
 
     // Si no se encuentra, intentar usar Java 8 como fallback desde runtime
-    let kindly_dir = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map(|p| std::path::PathBuf::from(p))
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join(".kindlyklanklient");
+    let kindly_dir = crate::paths::data_root();
     
     let java_8_path = kindly_dir.join("runtime").join("java-8").join("bin").join(if cfg!(target_os = "windows") { "java.exe" } else { "java" });
     
@@ -457,19 +452,20 @@ pub async fn find_java_executable() -> Result<String, String> {
     Err("Java executable not found. Please ensure Java is installed.".to_string())
 }
 
-/// Busca o instala automáticamente el ejecutable de Java para una versión específica de Minecraft
+/// Busca o instala automáticamente el ejecutable de Java para una versión específica de Minecraft.
+/// La versión de Java sale del `javaVersion` del JSON de la versión; se instala desde los runtimes
+/// de Mojang y Adoptium queda como alternativa
 pub async fn find_or_install_java_for_minecraft(mc_version: &str) -> Result<String, String> {
-    let required_java_version = get_required_java_version_for_minecraft(mc_version);
-    
-    // Verificar si ya existe la versión requerida en runtime
-    let kindly_dir = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map(|p| std::path::PathBuf::from(p))
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join(".kindlyklanklient");
-    
-    let java_path = kindly_dir
-        .join("runtime")
+    let requirement = crate::java_runtime::java_requirement_for_version(mc_version);
+    let required_java_version = requirement.major_version;
+
+    // Runtime de Mojang ya instalado para este componente
+    if let Some(java) = crate::java_runtime::installed_runtime_java(&requirement.component) {
+        return Ok(java.to_string_lossy().to_string());
+    }
+
+    // Verificar si ya existe la versión requerida en runtime (instalada desde Adoptium)
+    let java_path = crate::paths::runtime_dir()
         .join(format!("java-{}", required_java_version))
         .join("bin")
         .join(if cfg!(target_os = "windows") { "java.exe" } else { "java" });
@@ -478,7 +474,23 @@ pub async fn find_or_install_java_for_minecraft(mc_version: &str) -> Result<Stri
         return Ok(java_path.to_string_lossy().to_string());
     }
     
-    log::warn!("⚠️  Java {} no encontrado, se requiere para Minecraft {}", required_java_version, mc_version);
+    // Java del sistema ya registrada con la versión exacta
+    if let Some(java) = crate::java_discovery::find_registered_java(required_java_version) {
+        log::info!("Using registered Java {} at {}", required_java_version, java);
+        return Ok(java);
+    }
+    
+    log::warn!("⚠️  Java {} ({}) no encontrado, se requiere para Minecraft {}", required_java_version, requirement.component, mc_version);
+
+    let use_mojang_runtime = crate::commands::load_java_config().await.map(|c| c.use_mojang_runtime).unwrap_or(true);
+    if use_mojang_runtime {
+        log::info!("🔽 Descargando runtime de Java {} de Mojang...", requirement.component);
+        match crate::java_runtime::install_mojang_runtime(&requirement.component).await {
+            Ok(java) => return Ok(java.to_string_lossy().to_string()),
+            Err(e) => log::warn!("Mojang Java runtime unavailable, falling back to Adoptium: {}", e),
+        }
+    }
+
     log::info!("🔽 Descargando Java {} automáticamente...", required_java_version);
     
     download_java_silent(required_java_version).await?;
@@ -494,14 +506,10 @@ pub async fn find_or_install_java_for_minecraft(mc_version: &str) -> Result<Stri
 }
 
 /// Descarga e instala Java sin interfaz de usuario
-async fn download_java_silent(java_version: u8) -> Result<(), String> {
+async fn download_java_silent(java_version: u32) -> Result<(), String> {
     let version_str = java_version.to_string();
     
-    let kindly_dir = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map(|p| std::path::PathBuf::from(p))
-        .unwrap_or_else(|_| std::path::PathBuf::from("."))
-        .join(".kindlyklanklient");
+    let kindly_dir = crate::paths::data_root();
     
     let runtime_dir = kindly_dir.join("runtime");
     let java_dir = runtime_dir.join(format!("java-{}", version_str));
@@ -509,18 +517,8 @@ async fn download_java_silent(java_version: u8) -> Result<(), String> {
     tokio::fs::create_dir_all(&runtime_dir).await
         .map_err(|e| format!("Failed to create runtime directory: {}", e))?;
     
-    let (os, arch, extension) = if cfg!(target_os = "windows") {
-        ("windows", "x64", "zip")
-    } else if cfg!(target_os = "macos") {
-        ("mac", "x64", "tar.gz")
-    } else {
-        ("linux", "x64", "tar.gz")
-    };
-    
-    let jre_url = format!(
-        "https://api.adoptium.net/v3/binary/latest/{}/ga/{}/{}/jdk/hotspot/normal/eclipse",
-        version_str, os, arch
-    );
+    let (_, _, extension) = crate::java_runtime::adoptium_platform()?;
+    let jre_url = crate::java_runtime::adoptium_url(&version_str)?;
     
     log::info!("Downloading Java {} from: {}", version_str, jre_url);
     
@@ -544,67 +542,16 @@ async fn download_java_silent(java_version: u8) -> Result<(), String> {
     tokio::fs::write(&temp_file, &bytes).await
         .map_err(|e| format!("Failed to write temp file: {}", e))?;
     
-    if java_dir.exists() {
-        let _ = std::fs::remove_dir_all(&java_dir);
-    }
-    
-    if temp_file.extension().map_or(false, |e| e == "zip") {
-        let reader = std::fs::File::open(&temp_file)
-            .map_err(|e| format!("Open zip failed: {}", e))?;
-        let mut archive = zip::ZipArchive::new(reader)
-            .map_err(|e| format!("Read zip failed: {}", e))?;
-        
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)
-                .map_err(|e| format!("Zip index failed: {}", e))?;
-            let outpath = runtime_dir.join(file.mangled_name());
-            
-            if file.name().ends_with('/') {
-                std::fs::create_dir_all(&outpath)
-                    .map_err(|e| format!("Create dir failed: {}", e))?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    std::fs::create_dir_all(p)
-                        .map_err(|e| format!("Create parent failed: {}", e))?;
-                }
-                let mut outfile = std::fs::File::create(&outpath)
-                    .map_err(|e| format!("Create file failed: {}", e))?;
-                std::io::copy(&mut file, &mut outfile)
-                    .map_err(|e| format!("Write file failed: {}", e))?;
-            }
-        }
-    }
-    
-    // Renombrar el directorio extraído al nombre esperado
-    let all_entries = std::fs::read_dir(&runtime_dir)
-        .map_err(|e| format!("Failed to read runtime directory: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to read directory entries: {}", e))?;
-    
-    let extracted_dirs: Vec<_> = all_entries
-        .into_iter()
-        .filter(|entry| {
-            let path = entry.path();
-            path.is_dir() && path != java_dir
-        })
-        .map(|entry| entry.path())
-        .collect();
-    
-    if let Some(extracted_dir) = extracted_dirs.first() {
-        if java_dir.exists() {
-            let _ = std::fs::remove_dir_all(&java_dir);
-        }
-        std::fs::rename(extracted_dir, &java_dir)
-            .map_err(|e| format!("Failed to move Java directory: {}", e))?;
-        
-        for dir in extracted_dirs.iter().skip(1) {
-            let _ = std::fs::remove_dir_all(dir);
-        }
-    } else {
-        return Err("No Java directory found after extraction".to_string());
-    }
+    // Extraer (zip o tar.gz) y comprobar que bin/java arranca con la versión pedida
+    let archive = temp_file.clone();
+    let install_result = tauri::async_runtime::spawn_blocking(move || {
+        crate::java_runtime::install_java_archive(&archive, &java_dir, Some(java_version), &mut |_, _| {})
+    })
+    .await
+    .map_err(|e| format!("Java extraction task failed: {}", e))?;
     
     let _ = std::fs::remove_file(&temp_file);
+    install_result?;
     
     log::info!("Java {} installed successfully", version_str);
     Ok(())
@@ -630,68 +577,39 @@ fn get_java_path_from_env() -> String {
         })
 }
 
-pub fn build_minecraft_classpath_from_json(instance_dir: &Path, version_json_path: &Path) -> Result<String, String> {
-    let json_content = std::fs::read_to_string(version_json_path)
-        .map_err(|e| format!("Failed to read version JSON: {}", e))?;
-    let version_info: serde_json::Value = serde_json::from_str(&json_content)
-        .map_err(|e| format!("Failed to parse version JSON: {}", e))?;
-    
+pub fn build_classpath_for_version(instance_dir: &Path, version: &crate::version_resolver::ResolvedVersion) -> Result<String, String> {
     // Use HashMap to deduplicate by artifact (not by full path)
     // Key = "groupId:artifactId:classifier" to allow different versions but keep classifiers separate
     // This ensures Fabric's asm-9.9 overrides vanilla's asm-9.6, but lwjgl:natives-windows != lwjgl
     let mut jar_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-    let libs_dir = instance_dir.join("libraries");
+    // Librerías y jar del cliente salen del store compartido; los JSON siguen en la instancia
+    let libs_dir = crate::store::libraries_dir();
     let classpath_separator = if cfg!(target_os = "windows") { ";" } else { ":" };
     
-    // Check if this JSON inherits from another (e.g., NeoForge inherits from vanilla MC)
-    if let Some(inherits_from) = version_info.get("inheritsFrom").and_then(|v| v.as_str()) {
-        // Parse the base JSON first (e.g., 1.21.8.json) to get LWJGL and other base libraries
-        let base_json_path = instance_dir
-            .join("versions")
-            .join(inherits_from)
-            .join(format!("{}.json", inherits_from));
-        
-        if base_json_path.exists() {
-            let base_json_content = std::fs::read_to_string(&base_json_path)
-                .map_err(|e| format!("Failed to read base version JSON: {}", e))?;
-            let base_version_info: serde_json::Value = serde_json::from_str(&base_json_content)
-                .map_err(|e| format!("Failed to parse base version JSON: {}", e))?;
-            
-            // Parse libraries from base JSON
-            if let Some(libraries) = base_version_info.get("libraries").and_then(|v| v.as_array()) {
-                for lib in libraries {
-                    add_library_to_classpath(lib, &libs_dir, &mut jar_map)?;
-                }
-            }
-        }
-    }
-    
-    // Parse libraries from the mod loader JSON (NeoForge/Forge/Fabric specific libraries)
-    // These OVERRIDE base libraries with same artifact ID (e.g., asm-9.9 overrides asm-9.6)
-    if let Some(libraries) = version_info.get("libraries").and_then(|v| v.as_array()) {
-        for lib in libraries {
-            add_library_to_classpath(lib, &libs_dir, &mut jar_map)?;
+    // Libraries come base first (vanilla), then the mod loader ones, which OVERRIDE
+    // base libraries with the same artifact ID (e.g., asm-9.9 overrides asm-9.6)
+    // Libraries restricted to another OS (old LWJGL builds for osx, etc.) are skipped
+    let ctx = crate::arguments::RuleContext::current();
+    for lib in &version.libraries {
+        if !crate::arguments::rules_allow(lib.get("rules"), &ctx) {
+            continue;
         }
+        add_library_to_classpath(lib, &libs_dir, &mut jar_map)?;
     }
     
     // Add client JAR (from versions directory)
     // CRITICAL: NeoForge/Forge DON'T need the client JAR in classpath because BootstrapLauncher loads it specially
     // Only Fabric (and vanilla) need the client JAR in the classpath
     // Detect NeoForge/Forge by checking if mainClass is BootstrapLauncher
-    let main_class = version_info.get("mainClass").and_then(|v| v.as_str()).unwrap_or("");
-    let is_neoforge_or_forge = main_class.contains("bootstraplauncher.BootstrapLauncher");
+    let is_neoforge_or_forge = version.main_class.contains("bootstraplauncher.BootstrapLauncher");
     
     if !is_neoforge_or_forge {
-        // For Fabric/Vanilla: Add the client JAR
-        // For Fabric with inheritsFrom, use the vanilla MC client JAR (inheritsFrom value)
+        // For Fabric/Vanilla: Add the client JAR of the base version
         // Example: Fabric JSON has id="fabric-loader-0.17.3-1.21.8" and inheritsFrom="1.21.8"
         //          The client JAR is at versions/1.21.8/1.21.8.jar
-        let client_version = version_info.get("inheritsFrom")
-            .and_then(|v| v.as_str())
-            .or_else(|| version_info.get("id").and_then(|v| v.as_str()))
-            .ok_or("Version ID not found in JSON")?;
+        let client_version = version.jar.as_str();
         
-        let client_jar = instance_dir.join("versions").join(client_version).join(format!("{}.jar", client_version));
+        let client_jar = crate::store::client_jar_path(client_version);
         if client_jar.exists() {
             let normalized = dunce::canonicalize(&client_jar)
                 .unwrap_or(client_jar.clone());
@@ -814,7 +732,7 @@ fn add_library_to_classpath(lib: &serde_json::Value, libs_dir: &Path, jars: &mut
 
 /// Converts Maven coordinates to file path
 /// Example: org.example:artifact:1.0 -> org/example/artifact/1.0/artifact-1.0.jar
-fn maven_to_path(maven_coords: &str) -> Result<String, String> {
+pub(crate) fn maven_to_path(maven_coords: &str) -> Result<String, String> {
     let parts: Vec<&str> = maven_coords.split(':').collect();
     if parts.len() < 3 {
         return Err(format!("Invalid Maven coordinates: {}", maven_coords));
@@ -909,368 +827,8 @@ fn collect_jars_recursively_excluding(dir: &Path, out: &mut Vec<String>, exclude
 }
 
 
-pub fn select_main_class(instance_dir: &Path, version_id: Option<&str>) -> String {
-    if let Some(vid) = version_id {
-        let versions_dir = instance_dir.join("versions");
-        let json_path = versions_dir.join(vid).join(format!("{}.json", vid));
-        
-        if json_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&json_path) {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    if let Some(main_class) = json.get("mainClass").and_then(|v| v.as_str()) {
-                        return main_class.to_string();
-                    }
-                }
-            }
-        }
-    }
-    let versions_dir = instance_dir.join("versions");
-    if versions_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&versions_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let dir_name = entry.file_name();
-                    let json_path = path.join(format!("{}.json", dir_name.to_string_lossy()));
-                    
-                    if json_path.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&json_path) {
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                                if let Some(main_class) = json.get("mainClass").and_then(|v| v.as_str()) {
-                                    if main_class.contains("neoforge") || main_class.contains("neoforged") {
-                                        return main_class.to_string();
-                                    } else if main_class.contains("minecraftforge") || main_class.contains("forge") {
-                                        return main_class.to_string();
-                                    } else if main_class.contains("fabricmc") || main_class.contains("fabric") {
-                                        return main_class.to_string();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    let neoforge_loader_dir = instance_dir.join("libraries").join("net").join("neoforged");
-    if neoforge_loader_dir.exists() { 
-        return "cpw.mods.bootstraplauncher.BootstrapLauncher".to_string(); 
-    }
-    
-    let forge_loader_dir = instance_dir.join("libraries").join("net").join("minecraftforge");
-    if forge_loader_dir.exists() { 
-        return "cpw.mods.bootstraplauncher.BootstrapLauncher".to_string();
-    }
-    
-	let fabric_loader_dir = instance_dir.join("libraries").join("net").join("fabricmc");
-    if fabric_loader_dir.exists() { 
-        return "net.fabricmc.loader.impl.launch.knot.KnotClient".to_string();
-    }
-    
-    "net.minecraft.client.main.Main".to_string()
-}
-
-pub fn get_mod_loader_jvm_args(instance_dir: &Path, version_id: Option<&str>, mod_loader_type: Option<&str>, _mod_loader_version: Option<&str>) -> Vec<String> {
-    let mut additional_args = Vec::new();
-    let loader_type = mod_loader_type;
-    
-    let selected_json = if let Some(vid) = version_id {
-        let versions_dir = instance_dir.join("versions");
-        let json_path = versions_dir.join(vid).join(format!("{}.json", vid));
-        
-        if json_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&json_path) {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    Some((json_path, json))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    
-    let selected_json = selected_json.or_else(|| {
-        let versions_dir = instance_dir.join("versions");
-        if !versions_dir.exists() {
-            return None;
-        }
-        
-        let mut candidate_json: Option<(std::path::PathBuf, serde_json::Value)> = None;
-        let mut fallback_json: Option<(std::path::PathBuf, serde_json::Value)> = None;
-        
-        if let Ok(entries) = std::fs::read_dir(&versions_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    let dir_name = entry.file_name();
-                    let dir_name_str = dir_name.to_string_lossy();
-                    let json_path = path.join(format!("{}.json", dir_name_str));
-                    
-                    if json_path.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&json_path) {
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                                let is_mod_loader = json.get("mainClass")
-                                    .and_then(|v| v.as_str())
-                                    .map(|mc| mc.contains("forge") || mc.contains("neoforge") || mc.contains("fabric"))
-                                    .unwrap_or(false)
-                                    || json.get("arguments")
-                                        .and_then(|a| a.get("jvm"))
-                                        .is_some();
-                                
-                                if is_mod_loader {
-                                    let json_id = json.get("id").and_then(|v| v.as_str()).unwrap_or("");
-                                    let matches_loader = if let Some(loader) = loader_type {
-                                        match loader {
-                                            "neoforge" => json_id.starts_with("neoforge-") || dir_name_str.starts_with("neoforge-"),
-                                            "forge" => (json_id.starts_with("forge-") && !json_id.starts_with("neoforge-")) || (dir_name_str.starts_with("forge-") && !dir_name_str.starts_with("neoforge-")),
-                                            "fabric" => json_id.starts_with("fabric-loader-") || dir_name_str.starts_with("fabric-loader-"),
-                                            _ => false,
-                                        }
-                                    } else {
-                                        false
-                                    };
-                                    
-                                    if matches_loader {
-                                        candidate_json = Some((json_path.clone(), json));
-                                        break;
-                                    } else {
-                                        if fallback_json.is_none() {
-                                            fallback_json = Some((json_path.clone(), json));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        candidate_json.or(fallback_json)
-    });
-    
-    if let Some((json_path, json)) = selected_json {
-        let path = json_path.parent().unwrap();
-        let dir_name = path.file_name().unwrap();
-        let library_directory = instance_dir.join("libraries").to_string_lossy().to_string();
-        let natives_directory = path.join("natives").to_string_lossy().to_string();
-        let version_name = dir_name.to_string_lossy().to_string();
-        let classpath_separator = if cfg!(target_os = "windows") { ";" } else { ":" };
-        
-        if let Some(arguments) = json.get("arguments") {
-            if let Some(jvm_args) = arguments.get("jvm") {
-                if let Some(jvm_array) = jvm_args.as_array() {
-                    for arg in jvm_array {
-                        let arg_str_opt = if let Some(arg_str) = arg.as_str() {
-                            Some(arg_str.to_string())
-                        } else if let Some(obj) = arg.as_object() {
-                            // Argumentos condicionales - procesar value
-                            if let Some(value) = obj.get("value") {
-                                if let Some(value_str) = value.as_str() {
-                                    Some(value_str.to_string())
-                                } else if let Some(value_arr) = value.as_array() {
-                                    // Si es un array, tomar el primer valor o concatenar
-                                    value_arr.first()
-                                        .and_then(|v| v.as_str())
-                                        .map(|s| s.to_string())
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
-                        
-                        if let Some(arg_str) = arg_str_opt {
-                            // Reemplazar TODOS los placeholders
-                            let processed_arg = arg_str
-                                .replace("${library_directory}", &library_directory)
-                                .replace("${natives_directory}", &natives_directory)
-                                .replace("${classpath_separator}", classpath_separator)
-                                .replace("${version_name}", &version_name)
-                                .replace("${launcher_name}", "KindlyKlanKlient")
-                                .replace("${launcher_version}", "1.0.0")
-                                .replace("${classpath}", ""); // Se pasa por separado, eliminar
-                            
-                            // NO MODIFICAR NADA - usar el JSON tal cual lo proporciona NeoForge
-                            // Filtrar argumentos incompatibles
-                            #[cfg(target_os = "windows")]
-                            {
-                                if processed_arg == "-XstartOnFirstThread" {
-                                    continue;
-                                }
-                            }
-                            
-                            // Eliminar -cp y classpath ya que se pasa por separado
-                            if processed_arg == "-cp" || processed_arg.starts_with("-cp ") {
-                                continue;
-                            }
-                            if processed_arg.contains("${classpath}") || processed_arg == "${classpath}" {
-                                continue;
-                            }
-                            
-                            // Validar rutas de módulos si es -p (module path)
-                            if processed_arg == "-p" {
-                                additional_args.push(processed_arg);
-                                continue;
-                            }
-                            
-                            // Si el argumento anterior era -p, validar que los JARs existan y normalizar rutas
-                            if !additional_args.is_empty() && additional_args.last() == Some(&"-p".to_string()) {
-                                let module_paths: Vec<&str> = processed_arg.split(classpath_separator).collect();
-                                let mut valid_paths = Vec::new();
-                                
-                                for jar_path in module_paths {
-                                    let jar_path_trimmed = jar_path.trim();
-                                    // Normalizar la ruta: reemplazar / por \ en Windows y quitar prefijo \\?\
-                                    let normalized_path = if cfg!(target_os = "windows") {
-                                        jar_path_trimmed
-                                            .strip_prefix("\\\\?\\").unwrap_or(jar_path_trimmed)
-                                            .replace("/", "\\")
-                                    } else {
-                                        jar_path_trimmed.to_string()
-                                    };
-                                    
-                                    let path = std::path::Path::new(&normalized_path);
-                                    if path.exists() {
-                                        valid_paths.push(normalized_path);
-                                    } else {
-                                    }
-                                }
-                                
-                                if !valid_paths.is_empty() {
-                                    additional_args.pop();
-                                    additional_args.push("-p".to_string());
-                                    let final_module_path = valid_paths.join(classpath_separator);
-                                    additional_args.push(final_module_path);
-                                }
-                                continue;
-                            }
-                            
-                            if !processed_arg.trim().is_empty() {
-                                additional_args.push(processed_arg);
-                            }
-                        }
-                    }
-                    
-                    if !additional_args.is_empty() {
-                        ensure_required_add_opens(loader_type, &mut additional_args);
-                        return additional_args;
-                    }
-                }
-            }
-        }
-    }
-    
-    if additional_args.is_empty() {
-        if let Some(loader) = loader_type {
-            match loader {
-                "neoforge" | "forge" => {
-                    additional_args.extend(vec![
-                        "--add-opens".to_string(),
-                        "java.base/java.lang.invoke=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.nio=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.lang=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.util=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.util.concurrent=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.util.concurrent.locks=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.lang.reflect=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.text=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.time=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.io=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.net=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.security=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.security.cert=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/sun.nio.ch=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/sun.util.calendar=ALL-UNNAMED".to_string(),
-                        "--add-opens".to_string(),
-                        "java.base/java.util.jar=ALL-UNNAMED".to_string(),
-                    ]);
-                }
-                "fabric" => {
-                    additional_args.extend(vec![
-                        "--add-opens".to_string(),
-                        "java.base/java.lang.invoke=ALL-UNNAMED".to_string(),
-                    ]);
-                }
-                _ => {}
-            }
-        }
-    }
-    
-    if !additional_args.is_empty() {
-        ensure_required_add_opens(loader_type, &mut additional_args);
-    }
-    
-    additional_args
-}
-
-pub fn get_mod_loader_game_args(instance_dir: &Path, version_id: Option<&str>) -> Vec<String> {
-    let mut game_args = Vec::new();
-    
-    let selected_json = if let Some(vid) = version_id {
-        let versions_dir = instance_dir.join("versions");
-        let json_path = versions_dir.join(vid).join(format!("{}.json", vid));
-        
-        if json_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&json_path) {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    Some(json)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-    
-    if let Some(json) = selected_json {
-        if let Some(arguments) = json.get("arguments") {
-            if let Some(game_args_json) = arguments.get("game") {
-                if let Some(game_array) = game_args_json.as_array() {
-                    for arg in game_array {
-                        if let Some(arg_str) = arg.as_str() {
-                            game_args.push(arg_str.to_string());
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    game_args
-}
-
-
-fn ensure_required_add_opens(loader_type: Option<&str>, args: &mut Vec<String>) {
+/// Red de seguridad para Forge/NeoForge: su JSON debería abrir java.lang.invoke, pero no siempre lo hace
+pub(crate) fn ensure_required_add_opens(loader_type: Option<&str>, args: &mut Vec<String>) {
     if let Some(loader) = loader_type {
         if loader == "neoforge" || loader == "forge" {
             let mut has_all_unnamed = false;
@@ -1322,10 +880,7 @@ pub fn build_minecraft_jvm_args(
 }
 
 pub fn get_instance_directory(instance_id: &str) -> PathBuf {
-	let base = std::env::var("USERPROFILE")
-		.map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-		.unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
-	base.join(instance_id)
+	crate::paths::data_root().join(instance_id)
 }
 
 // Launcher directory configuration
@@ -1338,8 +893,7 @@ pub struct LauncherConfig {
 
 impl LauncherConfig {
     pub fn new() -> Result<Self> {
-        let home = env::var("USERPROFILE").unwrap_or_else(|_| "C:\\Users\\Default".to_string());
-        let minecraft_dir = PathBuf::from(home).join(".kindlyklanklient");
+        let minecraft_dir = crate::paths::data_root();
         Ok(Self {
             versions_dir: minecraft_dir.join("versions"),
             assets_dir: minecraft_dir.join("assets"),
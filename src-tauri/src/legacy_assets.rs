@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Deserialize)]
+struct AssetObject {
+    hash: String,
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+/// Índice de assets. Los antiguos marcan con `virtual` (1.6 a 1.7.2, índice "legacy") o
+/// `map_to_resources` (anteriores a 1.6, "pre-1.6") que el juego los busca por nombre y no por hash
+#[derive(Deserialize)]
+struct AssetIndex {
+    #[serde(default, rename = "virtual")]
+    is_virtual: bool,
+    #[serde(default)]
+    map_to_resources: bool,
+    objects: HashMap<String, AssetObject>,
+}
+
+/// Carpeta donde el juego espera los assets de un índice antiguo, o `None` si usa `objects/` directamente
+fn legacy_target(index: &AssetIndex, index_id: &str, assets_dir: &Path, instance_dir: &Path) -> Option<PathBuf> {
+    if index.map_to_resources {
+        Some(instance_dir.join("resources"))
+    } else if index.is_virtual {
+        Some(assets_dir.join("virtual").join(index_id))
+    } else {
+        None
+    }
+}
+
+/// Un nombre del índice convertido en ruta relativa, sin `..` ni rutas absolutas
+fn relative_asset_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| path.to_path_buf())
+}
+
+fn reconstruct_in(assets_dir: &Path, instance_dir: &Path, index_id: &str) -> Result<Option<PathBuf>, String> {
+    let index_path = assets_dir.join("indexes").join(format!("{}.json", index_id));
+    let content = std::fs::read_to_string(&index_path)
+        .map_err(|e| format!("Failed to read asset index {}: {}", index_path.display(), e))?;
+    let index: AssetIndex = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse asset index {}: {}", index_path.display(), e))?;
+    let Some(target) = legacy_target(&index, index_id, assets_dir, instance_dir) else {
+        return Ok(None);
+    };
+
+    let objects_dir = assets_dir.join("objects");
+    let mut placed = 0;
+    for (name, object) in &index.objects {
+        let Some(relative) = relative_asset_path(name) else {
+            log::warn!("Skipping asset with unsafe name: {}", name);
+            continue;
+        };
+        if object.hash.len() < 2 {
+            continue;
+        }
+        let source = objects_dir.join(&object.hash[..2]).join(&object.hash);
+        let destination = target.join(relative);
+        let up_to_date = std::fs::metadata(&destination)
+            .ok()
+            .is_some_and(|m| object.size.is_none_or(|size| m.len() == size));
+        if up_to_date {
+            continue;
+        }
+        if !source.exists() {
+            return Err(format!("Asset object missing for {}: {}", name, source.display()));
+        }
+        crate::dedup::link_or_copy(&source, &destination, true)?;
+        placed += 1;
+    }
+    if placed > 0 {
+        log::info!("Placed {} legacy assets of index {} in {}", placed, index_id, target.display());
+    }
+    Ok(Some(target))
+}
+
+/// Rehace la copia por nombre que esperan los índices antiguos (`assets/virtual/<índice>` o
+/// `resources/` de la instancia) a partir de `objects/`. Devuelve la carpeta que hay que pasar
+/// como `${game_assets}`, o `None` si el índice es moderno
+pub async fn prepare_legacy_assets(instance_dir: &Path, index_id: &str) -> Result<Option<PathBuf>, String> {
+    let assets_dir = crate::store::assets_dir();
+    let instance_dir = instance_dir.to_path_buf();
+    let index_id = index_id.to_string();
+    tauri::async_runtime::spawn_blocking(move || reconstruct_in(&assets_dir, &instance_dir, &index_id))
+        .await
+        .map_err(|e| format!("Legacy asset task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha1::{Digest, Sha1};
+
+    /// Carpeta de assets temporal con el índice del fixture y los objetos con estos contenidos
+    fn assets_with(name: &str, index_id: &str, contents: &[&[u8]]) -> PathBuf {
+        let assets = crate::test_fixtures::temp_dir(name);
+        std::fs::create_dir_all(assets.join("indexes")).unwrap();
+        std::fs::copy(
+            Path::new(crate::test_fixtures::FIXTURES).join("assets").join(format!("{}.json", index_id)),
+            assets.join("indexes").join(format!("{}.json", index_id)),
+        )
+        .unwrap();
+        for content in contents {
+            let hash = format!("{:x}", Sha1::digest(content));
+            let dir = assets.join("objects").join(&hash[..2]);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(&hash), content).unwrap();
+        }
+        assets
+    }
+
+    #[test]
+    fn legacy_index_is_rebuilt_under_assets_virtual() {
+        let assets = assets_with("legacy-assets-virtual", "legacy", &[b"click", b"gui.done=Done\n", b"icon16"]);
+        let instance = crate::test_fixtures::temp_dir("legacy-assets-virtual-instance");
+
+        let target = reconstruct_in(&assets, &instance, "legacy").unwrap();
+
+        assert_eq!(target, Some(assets.join("virtual").join("legacy")));
+        let virtual_dir = assets.join("virtual").join("legacy");
+        assert_eq!(std::fs::read(virtual_dir.join("sounds/random/click.ogg")).unwrap(), b"click");
+        assert_eq!(std::fs::read(virtual_dir.join("lang/en_US.lang")).unwrap(), b"gui.done=Done\n");
+        assert!(!instance.join("resources").exists());
+        // Una segunda pasada no necesita los objetos: lo ya colocado se reutiliza
+        std::fs::remove_dir_all(assets.join("objects")).unwrap();
+        assert!(reconstruct_in(&assets, &instance, "legacy").is_ok());
+        let _ = std::fs::remove_dir_all(&assets);
+        let _ = std::fs::remove_dir_all(&instance);
+    }
+
+    #[test]
+    fn pre_1_6_index_is_mapped_to_instance_resources() {
+        let readme: &[u8] = b"The resources folder is not used anymore.\n";
+        let assets = assets_with("legacy-assets-resources", "pre-1.6", &[b"click", b"calm1", readme]);
+        let instance = crate::test_fixtures::temp_dir("legacy-assets-resources-instance");
+
+        let target = reconstruct_in(&assets, &instance, "pre-1.6").unwrap();
+
+        assert_eq!(target, Some(instance.join("resources")));
+        assert_eq!(std::fs::read(instance.join("resources/sound/random/click.ogg")).unwrap(), b"click");
+        assert_eq!(std::fs::read(instance.join("resources/music/calm1.ogg")).unwrap(), b"calm1");
+        assert!(!assets.join("virtual").exists());
+        let _ = std::fs::remove_dir_all(&assets);
+        let _ = std::fs::remove_dir_all(&instance);
+    }
+
+    #[test]
+    fn missing_objects_and_modern_indexes() {
+        let assets = assets_with("legacy-assets-missing", "legacy", &[b"click"]);
+        let instance = crate::test_fixtures::temp_dir("legacy-assets-missing-instance");
+        assert!(reconstruct_in(&assets, &instance, "legacy").unwrap_err().starts_with("Asset object missing"));
+
+        std::fs::write(assets.join("indexes").join("5.json"), r#"{"objects":{}}"#).unwrap();
+        assert_eq!(reconstruct_in(&assets, &instance, "5").unwrap(), None);
+        let _ = std::fs::remove_dir_all(&assets);
+        let _ = std::fs::remove_dir_all(&instance);
+    }
+
+    #[test]
+    fn asset_names_cannot_escape_the_target() {
+        assert_eq!(relative_asset_path("sounds/random/click.ogg"), Some(PathBuf::from("sounds/random/click.ogg")));
+        assert_eq!(relative_asset_path("../options.txt"), None);
+        assert_eq!(relative_asset_path("sounds/../../options.txt"), None);
+        assert_eq!(relative_asset_path("/etc/passwd"), None);
+    }
+}
@@ -3,13 +3,9 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::env;
  
-use std::process::Command;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
  
 use tauri::{Emitter, Manager};
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
 
 mod logging;
 mod sessions;
@@ -27,6 +23,30 @@ mod local_instances;
 mod modrinth;
 mod http_client;
 mod discord_rpc;
+mod authoring;
+mod publishing;
+mod scheduling;
+mod sync_engine;
+mod predownload;
+mod store;
+mod dedup;
+mod paths;
+mod java_runtime;
+mod java_discovery;
+mod launch_profiles;
+mod launch_plan;
+mod arguments;
+mod version_resolver;
+mod natives;
+mod legacy_assets;
+#[cfg(test)]
+mod test_fixtures;
+mod server_ping;
+mod nbt;
+mod server_list;
+mod worlds;
+mod backups;
+mod game_logs;
 pub use models::*;
 pub use versions::*;
 pub use whitelist::*;
@@ -37,6 +57,27 @@ pub use auth_ms::*;
 pub use commands::*;
 pub use admins::*;
 pub use local_instances::*;
+pub use authoring::*;
+pub use publishing::*;
+pub use scheduling::*;
+pub use predownload::*;
+pub use store::*;
+pub use dedup::*;
+pub use paths::*;
+pub use java_runtime::*;
+pub use java_discovery::*;
+pub use launch_profiles::*;
+pub use launch_plan::*;
+pub use arguments::*;
+pub use version_resolver::*;
+pub use natives::*;
+pub use legacy_assets::*;
+pub use server_ping::*;
+pub use nbt::*;
+pub use server_list::*;
+pub use worlds::*;
+pub use backups::*;
+pub use game_logs::*;
  
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +115,7 @@ pub enum EnsureSessionResponse {
     Err { code: String, message: String },
 }
 
+/// `min_ram_gb`/`max_ram_gb` solo se usan si el perfil de la instancia no fija su propia RAM
 #[tauri::command]
 async fn launch_minecraft_with_java(
     app_handle: tauri::AppHandle,
@@ -83,262 +125,51 @@ async fn launch_minecraft_with_java(
     _java_version: String,
     access_token: String,
     min_ram_gb: Option<f64>,
-    max_ram_gb: Option<f64>
+    max_ram_gb: Option<f64>,
+    quick_play: Option<crate::models::QuickPlayTarget>
 ) -> Result<String, String> {
     let instance_dir = crate::launcher::get_instance_directory(&instance_id);
     if !instance_dir.exists() {
         return Err(format!("Instance directory does not exist: {}", instance_dir.display()));
     }
 
-    launch_minecraft_with_auth(&app_handle, &instance_id, &minecraft_version, &java_path, &access_token, min_ram_gb, max_ram_gb).await
-}
+    crate::scheduling::ensure_instance_launchable(&app_handle, &instance_id).await?;
 
-/// Busca el JSON del mod loader o usa el de la versión vanilla como fallback
-/// Usa la misma lógica que get_mod_loader_jvm_args para detectar mod loaders
-fn find_version_json_path(instance_dir: &std::path::Path, minecraft_version: &str) -> Result<std::path::PathBuf, String> {
-    use std::path::PathBuf;
-    
-    let versions_dir = instance_dir.join("versions");
-    if !versions_dir.exists() {
-        // Fallback: usar el JSON de la versión vanilla
-        let vanilla_json = versions_dir.join(minecraft_version).join(format!("{}.json", minecraft_version));
-        if vanilla_json.exists() {
-            return Ok(vanilla_json);
-        }
-        return Err(format!("Versions directory does not exist: {}", versions_dir.display()));
-    }
-    
-    // Buscar el JSON del mod loader usando la misma lógica que get_mod_loader_jvm_args
-    let mut candidate_json: Option<PathBuf> = None;
-    let mut fallback_json: Option<PathBuf> = None;
-    
-    if let Ok(entries) = std::fs::read_dir(&versions_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let dir_name = entry.file_name();
-                let dir_name_str = dir_name.to_string_lossy();
-                let json_path = path.join(format!("{}.json", dir_name_str));
-                
-                if json_path.exists() {
-                    if let Ok(content) = std::fs::read_to_string(&json_path) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                            // Verificar que sea un mod loader (tiene mainClass de mod loader o arguments.jvm)
-                            // Esta es la misma lógica que usa get_mod_loader_jvm_args
-                            let is_mod_loader = json.get("mainClass")
-                                .and_then(|v| v.as_str())
-                                .map(|mc| mc.contains("forge") || mc.contains("neoforge") || mc.contains("fabric") || mc.contains("Knot"))
-                                .unwrap_or(false)
-                                || json.get("arguments")
-                                    .and_then(|a| a.get("jvm"))
-                                    .is_some();
-                            
-                            if is_mod_loader {
-                                let json_id = json.get("id").and_then(|v| v.as_str()).unwrap_or("");
-                                
-                                let is_clear_mod_loader = json_id.starts_with("fabric-loader-") 
-                                    || json_id.starts_with("forge-") 
-                                    || json_id.starts_with("neoforge-")
-                                    || dir_name_str.starts_with("fabric-loader-")
-                                    || dir_name_str.starts_with("forge-")
-                                    || dir_name_str.starts_with("neoforge-");
-                                
-                                if is_clear_mod_loader {
-                                    candidate_json = Some(json_path);
-                                    break;
-                                } else {
-                                    if fallback_json.is_none() {
-                                        fallback_json = Some(json_path);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    if let Some(json_path) = candidate_json.or(fallback_json) {
-        return Ok(json_path);
-    }
-    
-    let vanilla_json = versions_dir.join(minecraft_version).join(format!("{}.json", minecraft_version));
-    if vanilla_json.exists() {
-        return Ok(vanilla_json);
-    }
-    
-    Err(format!("No version JSON found for {}", minecraft_version))
+    let overrides = crate::launch_plan::LaunchOverrides {
+        java_path: Some(java_path),
+        min_ram_gb,
+        max_ram_gb,
+        quick_play,
+    };
+    launch_minecraft_with_auth(&app_handle, &instance_id, &minecraft_version, &access_token, overrides).await
 }
 
 async fn launch_minecraft_with_auth(
     app_handle: &tauri::AppHandle,
     instance_id: &str,
     minecraft_version: &str,
-    java_path: &str,
     access_token: &str,
-    min_ram_gb: Option<f64>,
-    max_ram_gb: Option<f64>
+    overrides: crate::launch_plan::LaunchOverrides
 ) -> Result<String, String> {
-    let instance_dir = crate::launcher::get_instance_directory(instance_id);
-
-    ensure_minecraft_client_present(&instance_dir, minecraft_version).await?;
-    crate::instances::ensure_version_libraries(&instance_dir, minecraft_version).await?;
-
-    let _ = std::fs::create_dir_all(instance_dir.join("libraries"));
-    let _ = std::fs::create_dir_all(instance_dir.join("mods"));
-    
-    let version_json_path = find_version_json_path(&instance_dir, minecraft_version)?;
-    
-    let version_id = version_json_path
-        .parent()
-        .and_then(|p| p.file_name())
-        .and_then(|n| n.to_str())
-        .map(|s| s.to_string());
-    
-    let classpath = crate::launcher::build_minecraft_classpath_from_json(&instance_dir, &version_json_path)?;
-    {
-        let mut has_lwjgl = false;
-        for entry in walkdir::WalkDir::new(instance_dir.join("libraries")) {
-            let entry = entry.map_err(|e| e.to_string())?;
-            if entry.file_type().is_file() {
-                let p = entry.path();
-                if p.to_string_lossy().contains("lwjgl") {
-                    has_lwjgl = true;
-                    break;
-                }
-            }
-        }
-        if !has_lwjgl { ensure_minecraft_client_present(&instance_dir, minecraft_version).await?; }
-    }
-
-    let min_ram = min_ram_gb.unwrap_or(2.0);
-    let max_ram = max_ram_gb.unwrap_or(4.0);
-    
-    let (jvm_args_config, gc_config, window_width, window_height) = load_advanced_config().await.unwrap_or((
-        String::new(), "G1".to_string(), 1280, 720
-    ));
-    
-    let mut jvm_args = crate::launcher::build_minecraft_jvm_args(access_token, min_ram, max_ram, &gc_config, &jvm_args_config)?;
-    
-    let mod_loader_jvm_args = crate::launcher::get_mod_loader_jvm_args(&instance_dir, None, None, None);
-    if !mod_loader_jvm_args.is_empty() {
-        jvm_args.extend(mod_loader_jvm_args);
-    }
-    
-    let asset_index_id = ensure_assets_present(app_handle, &instance_dir, minecraft_version).await?;
-
     let profile = crate::auth_ms::get_minecraft_profile_from_token(access_token).await
         .map_err(|e| e.to_string())?;
-    let username = profile["name"].as_str().unwrap_or("Player");
-    let uuid = profile["id"].as_str().unwrap_or("00000000000000000000000000000000");
-
-    let assets_dir = instance_dir.join("assets");
-    let mut mc_args = vec![
-        "--username".to_string(), username.to_string(),
-        "--uuid".to_string(), uuid.to_string(),
-        "--accessToken".to_string(), access_token.to_string(),
-        "--version".to_string(), minecraft_version.to_string(),
-        "--gameDir".to_string(), instance_dir.to_string_lossy().to_string(),
-        "--assetsDir".to_string(), assets_dir.to_string_lossy().to_string(),
-        "--assetIndex".to_string(), asset_index_id,
-        "--userType".to_string(), "msa".to_string(),
-        "--versionType".to_string(), "release".to_string(),
-    ];
-
-    mc_args.push("--width".to_string());
-    mc_args.push(window_width.to_string());
-    mc_args.push("--height".to_string());
-    mc_args.push(window_height.to_string());
+    let account = crate::launch_plan::LaunchAccount {
+        username: profile["name"].as_str().unwrap_or("Player").to_string(),
+        uuid: profile["id"].as_str().unwrap_or("00000000000000000000000000000000").to_string(),
+        access_token: access_token.to_string(),
+    };
 
-    let main_class = crate::launcher::select_main_class(&instance_dir, version_id.as_deref());
-    
-    log::info!("Launching instance {} with main class: {}", instance_id, main_class);
-    
-    let mut command = Command::new(java_path);
-    #[cfg(target_os = "windows")]
-    {
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    
-    command
-        .args(&jvm_args)
-        .arg("-cp")
-        .arg(&classpath)
-        .arg(&main_class)
-        .args(&mc_args)
-        .current_dir(&instance_dir)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped());
-    
-    let mut child = command.spawn()
-        .map_err(|e| format!("Failed to start Minecraft: {}", e))?;
-    
-    let pid = child.id();
-    
-    if let Some(state) = app_handle.try_state::<Arc<Mutex<HashMap<String, u32>>>>() {
-        if let Ok(mut processes) = state.lock() {
-            processes.insert(instance_id.to_string(), pid);
-        }
-    } else {
-        log::warn!("Failed to get processes state");
-    }
-    
-    if let Some(stdout) = child.stdout.take() {
-        use std::io::{BufRead, BufReader};
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines().flatten() {
-                log::info!("[MC] {}", line);
-            }
-        });
-    }
-    
-    // Capturar stderr
-    if let Some(stderr) = child.stderr.take() {
-        use std::io::{BufRead, BufReader};
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().flatten() {
-                log::error!("[MC] {}", line);
-            }
-        });
+    let plan = crate::launch_plan::prepare_remote_launch(
+        app_handle,
+        instance_id,
+        minecraft_version,
+        account,
+        overrides,
+    ).await?;
+    if let Err(e) = crate::backups::auto_backup(app_handle, instance_id, crate::backups::BackupReason::Launch).await {
+        log::warn!("Backup before launching {} failed: {}", instance_id, e);
     }
-
-    let app = app_handle.clone();
-    let instance_id_owned = instance_id.to_string();
-    let processes_state = if let Some(state) = app_handle.try_state::<Arc<Mutex<HashMap<String, u32>>>>() {
-        state.inner().clone()
-    } else {
-        return Err("Failed to get processes state".to_string());
-    };
-    std::thread::spawn(move || {
-        match child.wait() {
-            Ok(status) => {
-                log::info!("Minecraft exited for instance {} with status: {:?}", instance_id_owned, status.code());
-                if let Ok(mut processes) = processes_state.lock() {
-                    processes.remove(&instance_id_owned);
-                }
-                let _ = app.emit("minecraft_exited", serde_json::json!({ 
-                    "instance_id": instance_id_owned,
-                    "status": "exited",
-                    "code": status.code()
-                }));
-            }
-            Err(e) => {
-                log::error!("Error waiting for Minecraft process {}: {}", instance_id_owned, e);
-                if let Ok(mut processes) = processes_state.lock() {
-                    processes.remove(&instance_id_owned);
-                }
-                let _ = app.emit("minecraft_exited", serde_json::json!({ 
-                    "instance_id": instance_id_owned,
-                    "status": "error",
-                    "error": e.to_string()
-                }));
-            }
-        }
-    });
+    crate::launch_plan::spawn_launch_plan(app_handle, &plan)?;
 
     Ok("Minecraft launched".to_string())
 }
@@ -354,6 +185,10 @@ pub fn run() {
     
     log::info!("Starting KindlyKlanKlient...");
     
+    // Mover los datos de la carpeta antigua (~/.kindlyklanklient) antes de usar cualquier ruta
+    paths::migrate_legacy_data();
+    log::info!("Data directory: {}", paths::data_root().display());
+    
     // Global state for tracking active downloads
     use std::sync::{Arc, Mutex};
     let is_downloading = Arc::new(Mutex::new(false));
@@ -372,6 +207,8 @@ pub fn run() {
                 let title = format!("Kindly Klan Klient v{}", version);
                 let _ = window.set_title(&title);
             }
+            // Mover al store compartido las librerías y assets de instancias antiguas
+            crate::store::migrate_to_store_on_startup(app.handle());
             Ok(())
         })
         .manage(is_downloading)
@@ -402,6 +239,17 @@ pub fn run() {
             create_instance_directory,
             launch_minecraft_with_java,
             get_required_java_version_command,
+            get_java_requirement,
+            discover_java_installations,
+            get_java_installations,
+            add_java_installation,
+            set_instance_java,
+            get_instance_java,
+            // Launch profiles
+            get_launch_profile,
+            save_launch_profile,
+            reset_launch_profile,
+            resolve_launch_command,
             check_java_version,
             download_java,
             set_downloading_state,
@@ -411,6 +259,10 @@ pub fn run() {
             get_system_ram,
             save_ram_config,
             load_ram_config,
+            save_java_config,
+            load_java_config,
+            save_backup_config,
+            load_backup_config,
             save_advanced_config,
             load_advanced_config,
             check_for_updates,
@@ -439,8 +291,27 @@ pub fn run() {
             clear_update_state,
             download_instance_assets,
             test_manifest_url,
+            // Event scheduling
+            get_server_time_offset,
+            get_instance_availability,
+            start_instance_predownload,
+            get_predownload_status,
+            cancel_instance_predownload,
+            // Server status
+            ping_server,
+            ping_distribution_servers,
+            get_instance_servers,
+            save_instance_servers,
+            // Shared store
+            garbage_collect_store,
+            migrate_instances_to_store,
+            get_dedup_savings_report,
+            get_data_directory_info,
+            move_data_directory,
             // Admin system
             check_is_admin,
+            get_user_permissions,
+            check_permission,
             // Versions
             get_minecraft_versions,
             get_fabric_loader_versions,
@@ -452,6 +323,9 @@ pub fn run() {
             rename_local_instance,
             launch_local_instance,
             delete_local_instance,
+            // Instance authoring
+            generate_instance_manifest,
+            publish_instance_build,
             // Forge and NeoForge
             get_forge_versions,
             get_recommended_forge_version,
@@ -481,7 +355,17 @@ pub fn run() {
             // Copy folders
             copy_instance_folders,
             list_minecraft_worlds,
+            export_world,
+            import_world,
             list_installed_mods,
+            // World backups
+            create_world_backup,
+            list_world_backups,
+            restore_world_backup,
+            delete_world_backup,
+            // Game logs
+            get_game_log_tail,
+            search_game_log,
             // Discord RPC
             initialize_discord_rpc,
             update_discord_presence,
@@ -1,6 +1,7 @@
 use crate::models::{LocalInstance, LocalInstanceMetadata};
+use crate::sync_engine::SyncOptions;
 use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter};
 use serde_json;
 
 fn generate_instance_id(name: &str) -> String {
@@ -40,19 +41,12 @@ fn generate_instance_id(name: &str) -> String {
 }
 
 // Get the local instances directory
-fn get_local_instances_dir() -> Result<PathBuf, String> {
-    let base = std::env::var("USERPROFILE")
-        .or_else(|_| std::env::var("HOME"))
-        .map(|p| PathBuf::from(p))
-        .unwrap_or_else(|_| PathBuf::from("."));
-    
-    Ok(base.join(".kindlyklanklient").join("local_instances"))
+pub(crate) fn get_local_instances_dir() -> Result<PathBuf, String> {
+    Ok(crate::paths::data_root().join("local_instances"))
 }
 
 pub fn get_instance_directory_smart(instance_id: &str) -> PathBuf {
-    let base = std::env::var("USERPROFILE")
-        .map(|p| std::path::Path::new(&p).join(".kindlyklanklient"))
-        .unwrap_or_else(|_| std::path::Path::new(".").join(".kindlyklanklient"));
+    let base = crate::paths::data_root();
     
     let local_instances_dir = base.join("local_instances");
     let local_instance_dir = local_instances_dir.join(instance_id);
@@ -73,6 +67,8 @@ pub async fn create_local_instance(
     app_handle: AppHandle,
 ) -> Result<LocalInstance, String> {
     log::info!("Creating local instance: {} (MC: {}, Loader: {} {})", name, minecraft_version, mod_loader_type, mod_loader_version);
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
     
     let instance_id = generate_instance_id(&name);
     
@@ -98,7 +94,7 @@ pub async fn create_local_instance(
         "message": "Descargando cliente de Minecraft..."
     }));
     
-    crate::instances::ensure_minecraft_client_present(&instance_dir, &minecraft_version).await?;
+    crate::instances::ensure_minecraft_client_present(&instance_dir, &minecraft_version, &SyncOptions::default()).await?;
     
     
     // Download Minecraft libraries
@@ -109,7 +105,7 @@ pub async fn create_local_instance(
         "message": "Descargando librerías de Minecraft..."
     }));
     
-    crate::instances::ensure_version_libraries(&instance_dir, &minecraft_version).await?;
+    crate::instances::ensure_version_libraries(&instance_dir, &minecraft_version, &SyncOptions::default()).await?;
     
     let _ = app_handle.emit("local-instance-progress", serde_json::json!({
         "instance_id": instance_id,
@@ -147,7 +143,7 @@ pub async fn create_local_instance(
             version: mod_loader_version.clone(),
         };
         
-        let vid = crate::instances::install_mod_loader(&minecraft_version, &mod_loader, &instance_dir).await?;
+        let vid = crate::instances::install_mod_loader(&minecraft_version, &mod_loader, &instance_dir, &SyncOptions::default()).await?;
         
         log::info!("{} {} installed", loader_display_name, mod_loader_version);
         vid
@@ -297,6 +293,12 @@ pub async fn sync_mods_from_remote(
     app_handle: AppHandle,
 ) -> Result<String, String> {
     log::info!("Syncing mods from remote {} to local {}", remote_instance_id, local_instance_id);
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
+
+    if let Err(e) = crate::backups::auto_backup(&app_handle, &local_instance_id, crate::backups::BackupReason::Update).await {
+        log::warn!("Backup before updating {} failed: {}", local_instance_id, e);
+    }
     
     let _ = app_handle.emit("mod-sync-progress", serde_json::json!({
         "local_id": local_instance_id,
@@ -328,7 +330,9 @@ pub async fn sync_mods_from_remote(
     let total_mods = manifest.files.mods.len();
     let mut downloaded_mods = 0;
     let mut skipped_mods = 0;
+    let mut linked_mods = 0;
     
+    crate::dedup::begin_sync().await?;
     let _ = app_handle.emit("mod-sync-progress", serde_json::json!({
         "local_id": local_instance_id,
         "remote_id": remote_instance_id,
@@ -365,12 +369,19 @@ pub async fn sync_mods_from_remote(
         };
         
         if should_download {
-            crate::instances::download_file_with_retry(&asset.url, &target_path).await?;
-            downloaded_mods += 1;
+            // Si otra instancia ya tiene el mismo jar, enlazarlo en lugar de descargarlo
+            if crate::dedup::place_existing_in_background(mod_file.sha256.clone(), target_path.clone()).await.is_some() {
+                linked_mods += 1;
+            } else {
+                crate::instances::download_file_with_retry(&asset.url, &target_path).await?;
+                crate::dedup::register_files(vec![target_path.clone()]).await?;
+                downloaded_mods += 1;
+            }
         } else {
             skipped_mods += 1;
         }
     }
+    crate::dedup::persist_index().await?;
     
     let total_configs = manifest.files.configs.len();
     let mut downloaded_configs = 0;
@@ -417,12 +428,19 @@ pub async fn sync_mods_from_remote(
         "remote_id": remote_instance_id,
         "stage": "completed",
         "percentage": 100,
-        "message": format!("¡Sincronización completada! {} mods, {} configs", downloaded_mods, downloaded_configs)
+        "message": format!("¡Sincronización completada! {} mods, {} configs", downloaded_mods + linked_mods, downloaded_configs)
     }));
     
-    log::info!("Sync completed: {} mods downloaded ({} skipped), {} configs downloaded", downloaded_mods, skipped_mods, downloaded_configs);
+    log::info!("Sync completed: {} mods downloaded ({} skipped, {} linked from other instances), {} configs downloaded", downloaded_mods, skipped_mods, linked_mods, downloaded_configs);
     
-    Ok(format!("Successfully synced {} mods ({} skipped, {} new) and {} configs", downloaded_mods + skipped_mods, skipped_mods, downloaded_mods, downloaded_configs))
+    Ok(format!(
+        "Successfully synced {} mods ({} skipped, {} downloaded, {} linked) and {} configs",
+        downloaded_mods + linked_mods + skipped_mods,
+        skipped_mods,
+        downloaded_mods,
+        linked_mods,
+        downloaded_configs
+    ))
 }
 
 #[tauri::command]
@@ -498,6 +516,7 @@ pub async fn rename_local_instance(instance_id: String, new_name: String) -> Res
     Ok(())
 }
 
+/// `min_ram_gb`/`max_ram_gb` solo se usan si el perfil de la instancia no fija su propia RAM
 #[tauri::command]
 pub async fn launch_local_instance(
     instance_id: String,
@@ -506,12 +525,9 @@ pub async fn launch_local_instance(
     uuid: String,
     min_ram_gb: f64,
     max_ram_gb: f64,
+    quick_play: Option<crate::models::QuickPlayTarget>,
     app_handle: AppHandle,
 ) -> Result<String, String> {
-    use std::process::Command;
-    #[cfg(target_os = "windows")]
-    use std::os::windows::process::CommandExt;
-    
     log::info!("Launching local instance: {}", instance_id);
     
     let (validated_access_token, validated_uuid) = match crate::sessions_api::validate_and_refresh_token(app_handle.clone(), username.clone()).await {
@@ -528,8 +544,35 @@ pub async fn launch_local_instance(
         }
     };
     
+    let account = crate::launch_plan::LaunchAccount {
+        username,
+        uuid: validated_uuid,
+        access_token: validated_access_token,
+    };
+    let overrides = crate::launch_plan::LaunchOverrides {
+        min_ram_gb: Some(min_ram_gb),
+        max_ram_gb: Some(max_ram_gb),
+        quick_play,
+        ..Default::default()
+    };
+    let plan = prepare_local_launch(&app_handle, &instance_id, account, overrides).await?;
+    if let Err(e) = crate::backups::auto_backup(&app_handle, &instance_id, crate::backups::BackupReason::Launch).await {
+        log::warn!("Backup before launching {} failed: {}", instance_id, e);
+    }
+    crate::launch_plan::spawn_launch_plan(&app_handle, &plan)?;
+    
+    Ok(format!("Local instance {} launched successfully", instance_id))
+}
+
+/// Prepara el lanzamiento de una instancia local (descarga lo que falte) sin arrancar el juego
+pub(crate) async fn prepare_local_launch(
+    app_handle: &AppHandle,
+    instance_id: &str,
+    account: crate::launch_plan::LaunchAccount,
+    overrides: crate::launch_plan::LaunchOverrides,
+) -> Result<crate::launch_plan::LaunchPlan, String> {
     let local_instances_dir = get_local_instances_dir()?;
-    let instance_dir = local_instances_dir.join(&instance_id);
+    let instance_dir = local_instances_dir.join(instance_id);
     
     if !instance_dir.exists() {
         return Err(format!("Instance directory does not exist: {}", instance_dir.display()));
@@ -561,222 +604,18 @@ pub async fn launch_local_instance(
             }
         }
     
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 0,
-        "total": 100,
-        "percentage": 0,
-        "current_file": "",
-        "status": "Verificando archivos..."
-    }));
-    
-    // Ensure Minecraft client is present
-    crate::instances::ensure_minecraft_client_present(&instance_dir, &metadata.minecraft_version).await?;
-    
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 33,
-        "total": 100,
-        "percentage": 33,
-        "current_file": "",
-        "status": "Verificando librerías..."
-    }));
-    
-    // Ensure libraries are present (vanilla MC)
-    crate::instances::ensure_version_libraries(&instance_dir, &metadata.minecraft_version).await?;
-    
-    // Ensure mod loader libraries are present (Fabric/NeoForge/Forge specific libraries)
-    if let Some(version_id) = &metadata.version_id {
-        crate::instances::ensure_mod_loader_libraries(&instance_dir, version_id).await?;
-    }
-    
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 66,
-        "total": 100,
-        "percentage": 66,
-        "current_file": "",
-        "status": "Verificando assets..."
-    }));
-    
-    // Ensure assets are present
-    crate::instances::ensure_assets_present(&app_handle, &instance_dir, &metadata.minecraft_version).await?;
-    
-    let _ = app_handle.emit("asset-download-progress", serde_json::json!({
-        "current": 100,
-        "total": 100,
-        "percentage": 100,
-        "current_file": "",
-        "status": "Completado"
-    }));
-    
-    let _ = app_handle.emit("asset-download-completed", serde_json::json!({ "phase": "complete" }));
-    
-    let _ = tokio::fs::create_dir_all(instance_dir.join("mods")).await;
-    
-    let mod_loader_jvm_args = crate::launcher::get_mod_loader_jvm_args(
-        &instance_dir,
-        metadata.version_id.as_deref(),
-        metadata.mod_loader.as_ref().map(|ml| ml.r#type.as_str()),
-        metadata.mod_loader.as_ref().map(|ml| ml.version.as_str()),
-    );
-    
-    // Build classpath FROM JSON, respecting include_in_classpath field (como Modrinth)
-    let version_json_path = instance_dir
-        .join("versions")
-        .join(metadata.version_id.as_ref().unwrap_or(&metadata.minecraft_version))
-        .join(format!("{}.json", metadata.version_id.as_ref().unwrap_or(&metadata.minecraft_version)));
-    
-    let classpath = crate::launcher::build_minecraft_classpath_from_json(&instance_dir, &version_json_path)?;
-    
-    {
-        let mut has_lwjgl = false;
-        if let Ok(entries) = std::fs::read_dir(instance_dir.join("libraries")) {
-            for entry in entries.flatten() {
-                if entry.path().to_string_lossy().contains("lwjgl") {
-                    has_lwjgl = true;
-                    break;
-                }
-            }
-        }
-        if !has_lwjgl {
-            crate::instances::ensure_minecraft_client_present(&instance_dir, &metadata.minecraft_version).await?;
-        }
-    }
-    
-    let (jvm_args_config, gc_config, window_width, window_height) = crate::commands::load_advanced_config()
-        .await
-        .unwrap_or((String::new(), "G1".to_string(), 1280, 720));
-    
-    let mut jvm_args = crate::launcher::build_minecraft_jvm_args(
-        &validated_access_token,
-        min_ram_gb,
-        max_ram_gb,
-        &gc_config,
-        &jvm_args_config,
-    )?;
-    
-    if !mod_loader_jvm_args.is_empty() {
-        jvm_args.extend(mod_loader_jvm_args);
-    }
-    
-    let asset_index_id = crate::instances::ensure_assets_present(&app_handle, &instance_dir, &metadata.minecraft_version).await?;
-    let user_properties = "{}".to_string();
-    
-    let assets_dir = instance_dir.join("assets");
-    
-    let uuid_simple = validated_uuid.replace("-", "");
-    
-    let mut mc_args = vec![
-        "--username".to_string(), username,
-        "--uuid".to_string(), uuid_simple,
-        "--accessToken".to_string(), validated_access_token,
-        "--version".to_string(), metadata.minecraft_version.clone(),
-        "--gameDir".to_string(), instance_dir.to_string_lossy().to_string(),
-        "--assetsDir".to_string(), assets_dir.to_string_lossy().to_string(),
-        "--assetIndex".to_string(), asset_index_id,
-        "--userType".to_string(), "msa".to_string(),
-        "--userProperties".to_string(), user_properties.clone(),
-        "--versionType".to_string(), "release".to_string(),
-        "--width".to_string(), window_width.to_string(),
-        "--height".to_string(), window_height.to_string(),
-    ];
-    
-    let mod_loader_game_args = crate::launcher::get_mod_loader_game_args(&instance_dir, metadata.version_id.as_deref());
-    if !mod_loader_game_args.is_empty() {
-        mc_args.extend(mod_loader_game_args);
-    }
-    
-    let main_class = crate::launcher::select_main_class(&instance_dir, metadata.version_id.as_deref());
-    let java_path = crate::launcher::find_or_install_java_for_minecraft(&metadata.minecraft_version).await?;
-    
-    let mut command = Command::new(&java_path);
-    #[cfg(target_os = "windows")]
-    {
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    
-    
-    command.args(&jvm_args);
-    command.arg("-cp").arg(&classpath);
-    command.arg(&main_class).args(&mc_args);
-    command.current_dir(&instance_dir);
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::piped());
-    
-    let mut child = command.spawn()
-        .map_err(|e| format!("Failed to start Minecraft: {}", e))?;
-    
-    let pid = child.id();
-    
-    if let Some(state) = app_handle.try_state::<std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, u32>>>>() {
-        if let Ok(mut processes) = state.lock() {
-            processes.insert(instance_id.clone(), pid);
-        }
-    } else {
-        log::warn!("Failed to get processes state");
-    }
-    
-    if let Some(stdout) = child.stdout.take() {
-        use std::io::{BufRead, BufReader};
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines().flatten() {
-                if line.contains("ERROR") || line.contains("FATAL") || line.contains("Exception") {
-                    log::error!("[MC] {}", line);
-                }
-            }
-        });
-    }
-    
-    if let Some(stderr) = child.stderr.take() {
-        use std::io::{BufRead, BufReader};
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().flatten() {
-                log::error!("[MC] {}", line);
-            }
-        });
-    }
-    
-    let app = app_handle.clone();
-    let instance_id_clone = instance_id.clone();
-    let processes_state = if let Some(state) = app_handle.try_state::<std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, u32>>>>() {
-        state.inner().clone()
-    } else {
-        return Err("Failed to get processes state".to_string());
+    let target = crate::launch_plan::LaunchTarget {
+        instance_id,
+        instance_dir: &instance_dir,
+        minecraft_version: &metadata.minecraft_version,
+        version_id: metadata.version_id.as_deref().or(metadata.mod_loader.is_none().then_some(metadata.minecraft_version.as_str())),
+        mod_loader: metadata.mod_loader.as_ref().map(|m| (m.r#type.as_str(), m.version.as_str())),
     };
-    std::thread::spawn(move || {
-        match child.wait() {
-            Ok(status) => {
-                log::info!("Minecraft exited for instance {} with status: {:?}", instance_id_clone, status.code());
-                if let Ok(mut processes) = processes_state.lock() {
-                    processes.remove(&instance_id_clone);
-                }
-                let _ = app.emit("minecraft_exited", serde_json::json!({ 
-                    "instance_id": instance_id_clone,
-                    "status": "exited",
-                    "code": status.code()
-                }));
-            }
-            Err(e) => {
-                log::error!("Error waiting for Minecraft process {}: {}", instance_id_clone, e);
-                if let Ok(mut processes) = processes_state.lock() {
-                    processes.remove(&instance_id_clone);
-                }
-                let _ = app.emit("minecraft_exited", serde_json::json!({ 
-                    "instance_id": instance_id_clone,
-                    "status": "error",
-                    "error": e.to_string()
-                }));
-            }
-        }
-    });
-    
-    
-    Ok(format!("Local instance {} launched successfully", instance_id))
+    crate::launch_plan::prepare_launch(app_handle, target, account, overrides).await
 }
 
 #[tauri::command]
-pub async fn delete_local_instance(instance_id: String) -> Result<String, String> {
+pub async fn delete_local_instance(instance_id: String, app_handle: AppHandle) -> Result<String, String> {
     log::info!("Deleting local instance: {}", instance_id);
     
     let local_instances_dir = get_local_instances_dir()?;
@@ -786,6 +625,11 @@ pub async fn delete_local_instance(instance_id: String) -> Result<String, String
         return Err(format!("Instance directory does not exist: {}", instance_dir.display()));
     }
     
+    // Los mundos solo existen aquí: si la copia falla la instancia no se borra
+    crate::backups::auto_backup(&app_handle, &instance_id, crate::backups::BackupReason::Delete)
+        .await
+        .map_err(|e| format!("Instance not deleted, backup of its worlds failed: {}", e))?;
+    
     tokio::fs::remove_dir_all(&instance_dir)
         .await
         .map_err(|e| format!("Failed to delete instance directory: {}", e))?;
@@ -4,13 +4,23 @@ use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use chrono::Local;
+use once_cell::sync::OnceCell;
 
 pub struct Logger {
     file: Mutex<BufWriter<File>>,
 }
 
+/// Logger instalado, para poder cambiar su archivo si se mueve la carpeta de datos
+static LOGGER: OnceCell<&'static Logger> = OnceCell::new();
+
 impl Logger {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Logger {
+            file: Mutex::new(BufWriter::new(Self::open_log_file()?)),
+        })
+    }
+
+    fn open_log_file() -> Result<File, Box<dyn std::error::Error>> {
         let log_dir = Self::get_log_directory()?;
         std::fs::create_dir_all(&log_dir)?;
         
@@ -18,24 +28,14 @@ impl Logger {
         let timestamp = Local::now().format("%Y-%m-%d");
         let log_file = log_dir.join(format!("launcher-{}.log", timestamp));
         
-        let file = OpenOptions::new()
+        Ok(OpenOptions::new()
             .create(true)
             .append(true)
-            .open(log_file)?;
-        
-        Ok(Logger {
-            file: Mutex::new(BufWriter::new(file)),
-        })
+            .open(log_file)?)
     }
     
     pub fn get_log_directory() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let mut log_dir = dirs::data_dir()
-            .ok_or("No data directory found")?;
-        
-        log_dir.push("KindlyKlanKlient");
-        log_dir.push("logs");
-        
-        Ok(log_dir)
+        Ok(crate::paths::logs_dir())
     }
     
     pub fn compress_old_logs(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -118,7 +118,9 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     // Compress old logs
     logger.compress_old_logs()?;
     
-    log::set_boxed_logger(Box::new(logger))?;
+    let logger: &'static Logger = Box::leak(Box::new(logger));
+    log::set_logger(logger)?;
+    let _ = LOGGER.set(logger);
     log::set_max_level(LevelFilter::Info);
     
     log::info!("Logging system initialized");
@@ -126,3 +128,13 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     
     Ok(())
 }
+
+/// Cierra el archivo de log actual y abre el de `logs_dir()`, tras mover la carpeta de datos
+pub fn reopen_log_file() -> Result<(), String> {
+    let Some(logger) = LOGGER.get() else { return Ok(()) };
+    let file = Logger::open_log_file().map_err(|e| e.to_string())?;
+    let mut current = logger.file.lock().map_err(|e| e.to_string())?;
+    let _ = current.flush();
+    *current = BufWriter::new(file);
+    Ok(())
+}
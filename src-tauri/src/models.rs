@@ -28,6 +28,15 @@ pub struct InstanceSummary {
     pub last_updated: Option<String>,
     pub instance_url: String,
     pub mod_loader: Option<ModLoader>,
+    /// Servidor del evento, para mostrar su estado sin descargar el manifest de la instancia
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Inicio de la ventana de disponibilidad del evento (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_from: Option<String>,
+    /// Cierre de la ventana de disponibilidad del evento (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_until: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +65,17 @@ pub struct IgnoredFilesConfig {
 pub struct ManifestHistory {
     pub last_updated: String,
     pub files: ManifestHistoryFiles,
+    /// Servidor declarado en el manifest sincronizado
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// IPs de `servers.dat` que gestiona el launcher; el resto son del jugador y no se tocan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub managed_servers: Vec<String>,
+    /// Ventana de disponibilidad del manifest de distribución, para aplicarla aunque no se haya cargado
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_until: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -195,6 +215,24 @@ pub struct InstanceInfo {
     pub mod_loader: Option<ModLoader>,
     pub icon: Option<String>,
     pub background: Option<String>,
+    /// Servidor del evento ("host" o "host:puerto") al que se entra directamente al lanzar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Servidores que se añaden a la lista multijugador del juego (`servers.dat`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ManifestServer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestServer {
+    pub name: String,
+    pub ip: String,
+    /// PNG de 64x64 en base64, con o sin el prefijo `data:image/png;base64,`
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Sin valor el juego pregunta al entrar si se aceptan los resource packs del servidor
+    #[serde(default, rename = "acceptTextures")]
+    pub accept_textures: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -230,10 +268,395 @@ pub struct LaunchSettings {
     pub jvm_args: Option<Vec<String>>,
 }
 
+// Authoring structures
+/// Opciones para generar una instancia publicable a partir de una instancia local
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestAuthoringOptions {
+    /// ID con el que se publicará la instancia (por defecto el de la instancia local)
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub version: String,
+    /// Globs relativos a la instancia. Vacío = usar los patrones por defecto
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub background: Option<String>,
+    #[serde(default)]
+    pub min_ram: Option<u32>,
+    #[serde(default)]
+    pub recommended_ram: Option<u32>,
+    #[serde(default)]
+    pub jvm_args: Option<Vec<String>>,
+    #[serde(default)]
+    pub ignored_files: Option<IgnoredFilesConfig>,
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub servers: Vec<ManifestServer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestAuthoringResult {
+    pub instance_id: String,
+    pub output_dir: String,
+    pub instance_manifest_path: String,
+    pub distribution_manifest_path: String,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+// Publishing structures
+/// Destino de publicación de builds de instancias
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublishTarget {
+    /// Bucket S3 o compatible (MinIO, R2...). `endpoint` incluye esquema, ej. "http://localhost:9000"
+    S3 {
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key: String,
+        secret_key: String,
+        #[serde(default)]
+        prefix: Option<String>,
+        /// Usar URLs "endpoint/bucket/key" en lugar de "bucket.endpoint/key" (necesario para MinIO)
+        #[serde(default)]
+        path_style: bool,
+    },
+    /// Supabase Storage. Si no se indica `url`/`api_key` se usan los del launcher
+    SupabaseStorage {
+        bucket: String,
+        #[serde(default)]
+        prefix: Option<String>,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PublishReport {
+    pub instance_id: String,
+    pub uploaded_files: Vec<String>,
+    pub unchanged_files: usize,
+    pub uploaded_bytes: u64,
+    pub distribution_updated: bool,
+    pub dry_run: bool,
+}
+
+// Event scheduling structures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceAvailability {
+    pub instance_id: String,
+    /// "open", "upcoming" o "closed"
+    pub status: String,
+    pub available_from: Option<String>,
+    pub available_until: Option<String>,
+    /// Segundos hasta la apertura según la hora del servidor (solo si status = "upcoming")
+    pub seconds_until_open: Option<i64>,
+    /// Segundos hasta el cierre según la hora del servidor (solo si status = "open" y hay cierre)
+    pub seconds_until_close: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTimeOffset {
+    /// Diferencia hora del servidor - hora local, en milisegundos
+    pub offset_ms: i64,
+    pub server_time: String,
+    pub local_time: String,
+}
+
+/// Estado de la pre-descarga en segundo plano de una instancia de evento
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredownloadState {
+    pub instance_id: String,
+    /// "downloading", "ready", "synced", "failed" o "cancelled"
+    pub status: String,
+    pub started_at: String,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+// Shared store structures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreGcReport {
+    pub dry_run: bool,
+    /// Rutas relativas a la raíz del store que se borraron (o se borrarían en dry run)
+    pub removed_files: Vec<String>,
+    pub freed_bytes: u64,
+    pub kept_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreMigrationReport {
+    pub instances_migrated: usize,
+    pub moved_files: usize,
+    /// Archivos que ya estaban en el store y se borraron de la instancia
+    pub duplicates_removed: usize,
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupReport {
+    pub indexed_files: usize,
+    pub unique_files: usize,
+    /// Tamaño total de los archivos indexados, contando cada copia
+    pub logical_bytes: u64,
+    /// Bytes que no ocupan disco gracias a reflinks y hardlinks
+    pub saved_bytes: u64,
+    pub reflinked_files: usize,
+    pub hardlinked_files: usize,
+    /// Bytes de duplicados que siguen siendo copias completas
+    pub duplicate_copy_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDirectoryInfo {
+    pub path: String,
+    /// "env", "portable", "custom" o "default"
+    pub source: String,
+    pub portable: bool,
+    pub default_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataMoveReport {
+    pub old_path: String,
+    pub new_path: String,
+    /// "rename" (mismo disco) o "copy"
+    pub method: String,
+    pub moved_files: usize,
+    pub moved_bytes: u64,
+    /// false si la carpeta antigua no se pudo borrar del todo (archivos en uso)
+    pub old_removed: bool,
+}
+
+// Java runtime structures
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JavaRequirement {
+    /// Componente del manifest de Mojang (java-runtime-delta, jre-legacy...)
+    pub component: String,
+    pub major_version: u32,
+    /// false si se estimó por el número de versión porque el JSON no estaba disponible
+    pub from_version_json: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JavaInstallation {
+    pub path: String,
+    /// `java.version` completo, p. ej. "21.0.2" o "1.8.0_392"
+    pub version: String,
+    pub major_version: u32,
+    pub vendor: Option<String>,
+    /// `os.arch` de la JVM (amd64, aarch64, x86...)
+    pub arch: Option<String>,
+    /// "path", "java_home", "system", "sdkman", "runtime" o "manual"
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaCompatibility {
+    pub path: String,
+    pub major_version: Option<u32>,
+    pub required_major: u32,
+    /// false si esa JVM no puede arrancar esta versión de Minecraft
+    pub compatible: bool,
+    pub warnings: Vec<String>,
+}
+
+// Launch debugging structures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClasspathEntry {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Línea de comandos resuelta de un lanzamiento, con el token de acceso oculto
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedLaunchCommand {
+    pub instance_id: String,
+    pub version_id: String,
+    pub java_path: String,
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub classpath: Vec<ClasspathEntry>,
+    pub missing_classpath_entries: usize,
+    pub game_args: Vec<String>,
+    pub working_dir: String,
+    pub env: std::collections::HashMap<String, String>,
+    /// Script que reproduce el lanzamiento, si se pidió
+    pub script_path: Option<String>,
+}
+
+// Quick Play structures
+/// Dónde entra el juego nada más arrancar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum QuickPlayTarget {
+    /// "host" o "host:puerto"
+    Multiplayer { address: String },
+    /// Nombre de la carpeta del mundo en `saves/`
+    Singleplayer { world: String },
+    Realms { realm_id: String },
+    /// Al menú principal, aunque el manifest de la instancia declare un servidor
+    None,
+}
+
+// Server status structures
+/// Respuesta de un servidor al Server List Ping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub address: String,
+    /// Host y puerto a los que se conectó (tras resolver el registro SRV)
+    pub host: String,
+    pub port: u16,
+    pub motd: String,
+    pub version_name: Option<String>,
+    pub protocol: Option<i32>,
+    pub players_online: i64,
+    pub players_max: i64,
+    pub player_sample: Vec<String>,
+    /// Icono como data URI (`data:image/png;base64,...`)
+    pub favicon: Option<String>,
+    pub latency_ms: u64,
+    /// Solo respondió al ping antiguo (0xFE), anterior a 1.7
+    pub legacy: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceServerStatus {
+    pub instance_id: String,
+    pub address: String,
+    pub status: Option<ServerStatus>,
+    pub error: Option<String>,
+}
+
+// Server list structures
+/// Entrada de la lista multijugador del juego (`servers.dat`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerListEntry {
+    pub name: String,
+    pub ip: String,
+    /// Icono como data URI (`data:image/png;base64,...`)
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub accept_textures: Option<bool>,
+    #[serde(default)]
+    pub hidden: bool,
+    /// La añadió el launcher desde el manifest de la instancia. Se ignora al guardar
+    #[serde(default)]
+    pub managed: bool,
+}
+
+// World structures
+/// Datos de `level.dat` de un mundo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorldMetadata {
+    /// Nombre que muestra el juego (`LevelName`), puede diferir de la carpeta
+    pub display_name: Option<String>,
+    /// "survival", "creative", "adventure" o "spectator"
+    pub game_mode: Option<String>,
+    pub hardcore: bool,
+    /// "peaceful", "easy", "normal" o "hard"
+    pub difficulty: Option<String>,
+    /// Milisegundos desde epoch
+    pub last_played: Option<i64>,
+    pub version_name: Option<String>,
+    pub data_version: Option<i32>,
+    /// Como texto: un i64 no cabe sin perder precisión en un número de JavaScript
+    pub seed: Option<String>,
+    pub cheats: bool,
+}
+
+// Backup structures
+/// Copia de mundos de una instancia. Se guarda fuera de la instancia para que sobreviva a su borrado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub instance_id: String,
+    /// Mundo copiado; `None` si es toda la carpeta `saves`
+    pub world: Option<String>,
+    /// Mundos que contiene el zip
+    pub worlds: Vec<String>,
+    /// "manual", "launch", "update" o "delete"
+    pub reason: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+// Game log structures
+/// Línea de la salida del juego, con los campos del layout de log4j si se pudieron reconocer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameLogLine {
+    pub instance_id: String,
+    /// Orden de la línea dentro del lanzamiento
+    pub seq: u64,
+    /// "stdout" o "stderr"
+    pub stream: String,
+    /// Hora tal como la escribe el juego (p. ej. "12:34:56")
+    pub time: Option<String>,
+    /// TRACE, DEBUG, INFO, WARN, ERROR o FATAL
+    pub level: String,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub message: String,
+}
+
 // Admin system structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdminEntry {
     pub minecraft_username: String,
+    /// Rol del staff (admin, moderator, builder...). Las filas antiguas sin rol son admins
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Permisos extra concedidos a este usuario además de los de su rol
+    #[serde(default)]
+    pub permissions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleEntry {
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UserPermissions {
+    pub username: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl UserPermissions {
+    /// Soporta comodines: "*" concede todo y "instances.*" concede todo bajo "instances."
+    pub fn has(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| {
+            if granted == "*" || granted == permission {
+                return true;
+            }
+            match granted.strip_suffix(".*") {
+                Some(prefix) => permission.starts_with(&format!("{}.", prefix)),
+                None => false,
+            }
+        })
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.roles.iter().any(|r| r == "admin")
+    }
 }
 
 // Local instances structures
@@ -326,6 +749,10 @@ pub struct LauncherConfig {
     #[serde(default)]
     pub advanced_config: AdvancedConfig,
     #[serde(default)]
+    pub java_config: JavaConfig,
+    #[serde(default)]
+    pub backup_config: BackupConfig,
+    #[serde(default)]
     pub last_updated: String,
 }
 
@@ -349,11 +776,60 @@ pub struct AdvancedConfig {
     pub window_height: u32,
 }
 
+/// Ajustes de lanzamiento propios de una instancia. Lo que quede en `None` usa la configuración global
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LaunchProfile {
+    #[serde(default)]
+    pub min_ram_gb: Option<f64>,
+    #[serde(default)]
+    pub max_ram_gb: Option<f64>,
+    #[serde(default)]
+    pub garbage_collector: Option<String>,
+    /// Sustituye a los argumentos JVM extra globales
+    #[serde(default)]
+    pub jvm_args: Option<String>,
+    #[serde(default)]
+    pub window_width: Option<u32>,
+    #[serde(default)]
+    pub window_height: Option<u32>,
+    #[serde(default)]
+    pub fullscreen: Option<bool>,
+    #[serde(default)]
+    pub java_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaConfig {
+    /// Instalar Java desde los runtimes de Mojang; si es false (o falla) se usa Adoptium
+    #[serde(default = "default_use_mojang_runtime")]
+    pub use_mojang_runtime: bool,
+}
+
+/// Copias automáticas de `saves/`. Las manuales no se borran nunca por retención
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    #[serde(default)]
+    pub before_launch: bool,
+    #[serde(default)]
+    pub before_update: bool,
+    #[serde(default = "default_backup_before_delete")]
+    pub before_delete: bool,
+    /// Copias automáticas que se conservan por instancia (0 = todas)
+    #[serde(default = "default_backup_keep_last")]
+    pub keep_last: u32,
+    /// Las copias automáticas más antiguas se borran
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
 fn default_min_ram() -> f64 { 2.0 }
 fn default_max_ram() -> f64 { 4.0 }
 fn default_gc() -> String { "G1".to_string() }
 fn default_width() -> u32 { 1280 }
 fn default_height() -> u32 { 720 }
+fn default_use_mojang_runtime() -> bool { true }
+fn default_backup_before_delete() -> bool { true }
+fn default_backup_keep_last() -> u32 { 10 }
 
 impl Default for LauncherConfig {
     fn default() -> Self {
@@ -369,6 +845,8 @@ impl Default for LauncherConfig {
             },
             ram_config: RamConfig::default(),
             advanced_config: AdvancedConfig::default(),
+            java_config: JavaConfig::default(),
+            backup_config: BackupConfig::default(),
             last_updated: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -383,6 +861,26 @@ impl Default for RamConfig {
     }
 }
 
+impl Default for JavaConfig {
+    fn default() -> Self {
+        Self {
+            use_mojang_runtime: true,
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            before_launch: false,
+            before_update: false,
+            before_delete: true,
+            keep_last: 10,
+            max_age_days: None,
+        }
+    }
+}
+
 impl Default for AdvancedConfig {
     fn default() -> Self {
         Self {
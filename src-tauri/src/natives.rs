@@ -0,0 +1,185 @@
+use crate::arguments::RuleContext;
+use crate::version_resolver::ResolvedVersion;
+use crate::versions::Library;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Carpeta de la instancia donde se extraen los nativos de cada lanzamiento
+const NATIVES_DIR: &str = ".natives";
+
+/// Carpetas de nativos que sobreviven a un cierre inesperado del launcher
+const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Borra carpetas de lanzamientos anteriores que nadie limpió. Las recientes se dejan: pueden
+/// ser de un juego que sigue abierto
+fn cleanup_stale_natives(natives_root: &Path) {
+    let Ok(entries) = std::fs::read_dir(natives_root) else { return };
+    for entry in entries.flatten() {
+        let is_stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > STALE_AFTER);
+        if is_stale {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+fn extract_native_jar(jar: &Path, target: &Path, exclude: &[String]) -> Result<usize, String> {
+    let reader = std::fs::File::open(jar).map_err(|e| format!("Failed to open {}: {}", jar.display(), e))?;
+    let mut zip = zip::ZipArchive::new(reader).map_err(|e| format!("Failed to read {}: {}", jar.display(), e))?;
+    let mut extracted = 0;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
+        if file.is_dir() || exclude.iter().any(|prefix| file.name().starts_with(prefix.as_str())) {
+            continue;
+        }
+        let Some(rel) = file.enclosed_name().map(|p| p.to_path_buf()) else { continue };
+        let outpath = target.join(rel);
+        if let Some(parent) = outpath.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Create parent failed: {}", e))?;
+        }
+        let mut outfile = std::fs::File::create(&outpath).map_err(|e| format!("Create file failed: {}", e))?;
+        std::io::copy(&mut file, &mut outfile).map_err(|e| format!("Write file failed: {}", e))?;
+        extracted += 1;
+    }
+    Ok(extracted)
+}
+
+/// Nativos de un lanzamiento: los jars a extraer y la carpeta propia donde acabarán
+#[derive(Debug, Clone)]
+pub struct NativesPlan {
+    pub target: PathBuf,
+    jars: Vec<(PathBuf, Vec<String>)>,
+}
+
+/// Decide qué jars de nativos (versiones con `natives`, anteriores a 1.19) hay que extraer y en qué
+/// carpeta, sin tocar el disco. Devuelve `None` si la versión no tiene nativos que extraer
+pub fn plan_natives(instance_dir: &Path, version: &ResolvedVersion) -> Option<NativesPlan> {
+    plan_natives_in(instance_dir, version, &crate::store::libraries_dir(), &RuleContext::current())
+}
+
+fn plan_natives_in(instance_dir: &Path, version: &ResolvedVersion, libraries_dir: &Path, ctx: &RuleContext) -> Option<NativesPlan> {
+    let mut jars: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for value in &version.libraries {
+        let Ok(lib) = serde_json::from_value::<Library>(value.clone()) else { continue };
+        if !lib.is_allowed(ctx) {
+            continue;
+        }
+        if let Some(artifact) = lib.native_artifact(&ctx.os_name) {
+            let exclude = lib.get_extract().map(|e| e.exclude.clone()).unwrap_or_default();
+            jars.push((libraries_dir.join(&artifact.path), exclude));
+        }
+    }
+    if jars.is_empty() {
+        return None;
+    }
+
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let target = instance_dir.join(NATIVES_DIR).join(format!("{}-{}", version.id, stamp));
+    Some(NativesPlan { target, jars })
+}
+
+impl NativesPlan {
+    /// Extrae los jars en `target`. Se llama justo antes de arrancar el juego
+    pub fn extract(&self) -> Result<(), String> {
+        if let Some(natives_root) = self.target.parent() {
+            cleanup_stale_natives(natives_root);
+        }
+        std::fs::create_dir_all(&self.target).map_err(|e| format!("Failed to create natives directory: {}", e))?;
+
+        let mut extracted = 0;
+        for (jar, exclude) in &self.jars {
+            if !jar.exists() {
+                let _ = std::fs::remove_dir_all(&self.target);
+                return Err(format!("Native library missing: {}", jar.display()));
+            }
+            extracted += extract_native_jar(jar, &self.target, exclude)?;
+        }
+        log::info!("Extracted {} native files from {} jars into {}", extracted, self.jars.len(), self.target.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn ctx(os_name: &str) -> RuleContext {
+        RuleContext {
+            os_name: os_name.to_string(),
+            os_arch: "x86_64".to_string(),
+            os_version: String::new(),
+            features: Default::default(),
+        }
+    }
+
+    fn jar_names(plan: &NativesPlan) -> Vec<String> {
+        plan.jars
+            .iter()
+            .map(|(jar, _)| jar.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn old_versions_pick_the_natives_of_each_os() {
+        let (dir, version) = crate::test_fixtures::fixture_version("natives-plan", &["1.7.2"]);
+        let libraries = Path::new("/libraries");
+
+        let linux = plan_natives_in(&dir, &version, libraries, &ctx("linux")).unwrap();
+        assert_eq!(jar_names(&linux), ["lwjgl-platform-2.9.0-natives-linux.jar", "jinput-platform-2.0.5-natives-linux.jar"]);
+        assert!(linux.target.starts_with(dir.join(NATIVES_DIR)));
+        assert!(linux.jars.iter().all(|(_, exclude)| exclude == &["META-INF/".to_string()]));
+
+        let osx = plan_natives_in(&dir, &version, libraries, &ctx("osx")).unwrap();
+        assert_eq!(
+            jar_names(&osx),
+            ["lwjgl-platform-2.9.1-nightly-20131017-natives-osx.jar", "jinput-platform-2.0.5-natives-osx.jar"]
+        );
+        // Planificar no crea nada en la instancia
+        assert!(!dir.join(NATIVES_DIR).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn modern_versions_have_nothing_to_extract() {
+        let (dir, version) = crate::test_fixtures::fixture_version("natives-modern", &["1.20.1"]);
+        assert!(plan_natives_in(&dir, &version, Path::new("/libraries"), &ctx("linux")).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extract_skips_excluded_entries_and_fails_on_missing_jars() {
+        let (dir, version) = crate::test_fixtures::fixture_version("natives-extract", &["1.5.2"]);
+        let libraries = dir.join("libraries");
+        let plan = plan_natives_in(&dir, &version, &libraries, &ctx("linux")).unwrap();
+
+        for (jar, _) in &plan.jars {
+            std::fs::create_dir_all(jar.parent().unwrap()).unwrap();
+            let mut zip = zip::ZipWriter::new(std::fs::File::create(jar).unwrap());
+            let options = zip::write::FileOptions::default();
+            let name = jar.file_name().unwrap().to_string_lossy().replace(".jar", ".so");
+            zip.start_file(name, options).unwrap();
+            zip.write_all(b"native").unwrap();
+            zip.start_file("META-INF/MANIFEST.MF", options).unwrap();
+            zip.write_all(b"Manifest-Version: 1.0\n").unwrap();
+            zip.finish().unwrap();
+        }
+        plan.extract().unwrap();
+        assert!(plan.target.join("lwjgl-platform-2.9.0-natives-linux.so").exists());
+        assert!(plan.target.join("jinput-platform-2.0.5-natives-linux.so").exists());
+        assert!(!plan.target.join("META-INF").exists());
+
+        std::fs::remove_file(&plan.jars[0].0).unwrap();
+        let missing = plan_natives_in(&dir, &version, &libraries, &ctx("linux")).unwrap();
+        assert!(missing.extract().unwrap_err().starts_with("Native library missing"));
+        assert!(!missing.target.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+/// Igual que el juego: evita desbordar la pila con archivos corruptos o maliciosos
+const MAX_DEPTH: usize = 512;
+
+/// Valor NBT (el formato binario de Minecraft para `level.dat`, `servers.dat`, etc.)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// Las listas vacías se escriben con tipo TAG_End, como hace el juego
+    List(Vec<Tag>),
+    Compound(BTreeMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => TAG_BYTE,
+            Tag::Short(_) => TAG_SHORT,
+            Tag::Int(_) => TAG_INT,
+            Tag::Long(_) => TAG_LONG,
+            Tag::Float(_) => TAG_FLOAT,
+            Tag::Double(_) => TAG_DOUBLE,
+            Tag::ByteArray(_) => TAG_BYTE_ARRAY,
+            Tag::String(_) => TAG_STRING,
+            Tag::List(_) => TAG_LIST,
+            Tag::Compound(_) => TAG_COMPOUND,
+            Tag::IntArray(_) => TAG_INT_ARRAY,
+            Tag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        self.as_compound()?.get(key)
+    }
+
+    /// Sigue una ruta de claves separadas por `/` (p. ej. "Data/Version/Name")
+    pub fn path(&self, path: &str) -> Option<&Tag> {
+        path.split('/').try_fold(self, |tag, key| tag.get(key))
+    }
+
+    pub fn as_compound(&self) -> Option<&BTreeMap<String, Tag>> {
+        match self {
+            Tag::Compound(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut BTreeMap<String, Tag>> {
+        match self {
+            Tag::Compound(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Cualquier entero como i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(v) => Some(*v as i64),
+            Tag::Short(v) => Some(*v as i64),
+            Tag::Int(v) => Some(*v as i64),
+            Tag::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Los booleanos de NBT son bytes 0/1
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_i64().map(|v| v != 0)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len()).ok_or("Unexpected end of NBT data")?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn read_len(&mut self) -> Result<usize, String> {
+        let len = self.i32()?;
+        // Cada elemento ocupa al menos un byte: una longitud mayor que lo que queda es un archivo corrupto
+        if len < 0 || len as usize > self.data.len() - self.pos {
+            return Err(format!("Invalid NBT length: {}", len));
+        }
+        Ok(len as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        decode_modified_utf8(self.take(len)?)
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT nested too deeply".to_string());
+        }
+        Ok(match id {
+            TAG_BYTE => Tag::Byte(self.u8()? as i8),
+            TAG_SHORT => Tag::Short(i16::from_be_bytes(self.array()?)),
+            TAG_INT => Tag::Int(self.i32()?),
+            TAG_LONG => Tag::Long(i64::from_be_bytes(self.array()?)),
+            TAG_FLOAT => Tag::Float(f32::from_be_bytes(self.array()?)),
+            TAG_DOUBLE => Tag::Double(f64::from_be_bytes(self.array()?)),
+            TAG_BYTE_ARRAY => {
+                let len = self.read_len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            TAG_STRING => Tag::String(self.string()?),
+            TAG_LIST => {
+                let item_id = self.u8()?;
+                let len = self.read_len()?;
+                if item_id == TAG_END && len > 0 {
+                    return Err("NBT list of TAG_End with elements".to_string());
+                }
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.payload(item_id, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            TAG_COMPOUND => {
+                let mut map = BTreeMap::new();
+                loop {
+                    let child_id = self.u8()?;
+                    if child_id == TAG_END {
+                        break;
+                    }
+                    let name = self.string()?;
+                    map.insert(name, self.payload(child_id, depth + 1)?);
+                }
+                Tag::Compound(map)
+            }
+            TAG_INT_ARRAY => {
+                let len = self.read_len()?;
+                let bytes = self.take(len.checked_mul(4).ok_or("Invalid NBT length")?)?;
+                Tag::IntArray(bytes.chunks_exact(4).map(|c| i32::from_be_bytes([c[0], c[1], c[2], c[3]])).collect())
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.read_len()?;
+                let bytes = self.take(len.checked_mul(8).ok_or("Invalid NBT length")?)?;
+                Tag::LongArray(
+                    bytes
+                        .chunks_exact(8)
+                        .map(|c| i64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+                        .collect(),
+                )
+            }
+            other => return Err(format!("Unknown NBT tag type: {}", other)),
+        })
+    }
+}
+
+/// Java guarda las cadenas en "modified UTF-8": el nulo es C0 80 y los caracteres fuera del BMP van
+/// como pares suplentes de 3 bytes cada uno
+fn decode_modified_utf8(bytes: &[u8]) -> Result<String, String> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Ok(s.to_string());
+    }
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let (unit, width) = if b & 0x80 == 0 {
+            (b as u16, 1)
+        } else if b & 0xE0 == 0xC0 && i + 1 < bytes.len() {
+            ((((b & 0x1F) as u16) << 6) | (bytes[i + 1] & 0x3F) as u16, 2)
+        } else if b & 0xF0 == 0xE0 && i + 2 < bytes.len() {
+            ((((b & 0x0F) as u16) << 12) | (((bytes[i + 1] & 0x3F) as u16) << 6) | (bytes[i + 2] & 0x3F) as u16, 3)
+        } else {
+            return Err("Invalid modified UTF-8 in NBT string".to_string());
+        };
+        units.push(unit);
+        i += width;
+    }
+    Ok(String::from_utf16_lossy(&units))
+}
+
+fn encode_modified_utf8(value: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => out.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                out.push(0xC0 | (unit >> 6) as u8);
+                out.push(0x80 | (unit & 0x3F) as u8);
+            }
+            _ => {
+                out.push(0xE0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                out.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+    out
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) -> Result<(), String> {
+    let bytes = encode_modified_utf8(value);
+    let len = u16::try_from(bytes.len()).map_err(|_| "NBT string too long".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(&bytes);
+    Ok(())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = i32::try_from(len).map_err(|_| "NBT array too long".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) -> Result<(), String> {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            write_len(out, values.len())?;
+            out.extend(values.iter().map(|v| *v as u8));
+        }
+        Tag::String(s) => write_string(out, s)?,
+        Tag::List(items) => {
+            let item_id = items.first().map(|t| t.id()).unwrap_or(TAG_END);
+            if items.iter().any(|t| t.id() != item_id) {
+                return Err("NBT list with mixed tag types".to_string());
+            }
+            out.push(item_id);
+            write_len(out, items.len())?;
+            for item in items {
+                write_payload(out, item)?;
+            }
+        }
+        Tag::Compound(map) => {
+            for (name, child) in map {
+                out.push(child.id());
+                write_string(out, name)?;
+                write_payload(out, child)?;
+            }
+            out.push(TAG_END);
+        }
+        Tag::IntArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            write_len(out, values.len())?;
+            for v in values {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lee NBT sin comprimir. Devuelve el nombre de la raíz (casi siempre vacío) y su compound
+pub fn from_bytes(data: &[u8]) -> Result<(String, Tag), String> {
+    let mut reader = Reader { data, pos: 0 };
+    let id = reader.u8()?;
+    if id != TAG_COMPOUND {
+        return Err(format!("NBT root is not a compound (tag {})", id));
+    }
+    let name = reader.string()?;
+    let root = reader.payload(id, 0)?;
+    Ok((name, root))
+}
+
+pub fn to_bytes(name: &str, root: &Tag) -> Result<Vec<u8>, String> {
+    if !matches!(root, Tag::Compound(_)) {
+        return Err("NBT root must be a compound".to_string());
+    }
+    let mut out = vec![TAG_COMPOUND];
+    write_string(&mut out, name)?;
+    write_payload(&mut out, root)?;
+    Ok(out)
+}
+
+/// NBT comprimido con gzip (`level.dat`) o no (`servers.dat`)
+pub fn read_bytes(raw: &[u8]) -> Result<(String, Tag), String> {
+    if !raw.starts_with(&[0x1F, 0x8B]) {
+        return from_bytes(raw);
+    }
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(raw)
+        .read_to_end(&mut decoded)
+        .map_err(|e| format!("Failed to decompress NBT: {}", e))?;
+    from_bytes(&decoded)
+}
+
+pub fn read_file(path: &Path) -> Result<(String, Tag), String> {
+    let raw = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    read_bytes(&raw).map_err(|e| format!("Invalid NBT in {}: {}", path.display(), e))
+}
+
+/// Escribe a un temporal y lo renombra, para no dejar el archivo a medias si algo falla
+pub fn write_file(path: &Path, name: &str, root: &Tag, gzip: bool) -> Result<(), String> {
+    let bytes = to_bytes(name, root)?;
+    let data = if gzip {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&bytes).map_err(|e| e.to_string())?;
+        encoder.finish().map_err(|e| e.to_string())?
+    } else {
+        bytes
+    };
+    let tmp = path.with_extension("dat_tmp");
+    std::fs::write(&tmp, data).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(crate::test_fixtures::FIXTURES).join("nbt").join(name)
+    }
+
+    #[test]
+    fn servers_dat_round_trips() {
+        let raw = std::fs::read(fixture("servers.dat")).unwrap();
+        let (name, root) = read_bytes(&raw).unwrap();
+        assert_eq!(name, "");
+
+        let servers = root.get("servers").and_then(|t| t.as_list()).unwrap();
+        assert_eq!(servers.len(), 3);
+        assert_eq!(servers[0].get("name").and_then(|t| t.as_str()), Some("Kindly ✦ Klan 🎮"));
+        assert_eq!(servers[0].get("acceptTextures").and_then(|t| t.as_bool()), Some(true));
+        assert_eq!(servers[2].get("name").and_then(|t| t.as_str()), Some("LAN\0casa"));
+        assert_eq!(servers[2].get("hidden").and_then(|t| t.as_bool()), Some(true));
+
+        // Las claves salen ordenadas, así que se compara el árbol y no los bytes
+        let written = to_bytes(&name, &root).unwrap();
+        assert_eq!(from_bytes(&written).unwrap(), (name, root));
+        // El nulo y los caracteres fuera del BMP se escriben como el juego (modified UTF-8)
+        assert!(written.windows(2).any(|w| w == [0xC0, 0x80]));
+        assert!(written.windows(3).any(|w| w == [0xED, 0xA0, 0xBC]));
+    }
+
+    #[test]
+    fn level_dat_round_trips_through_gzip() {
+        let (name, root) = read_file(&fixture("level.dat")).unwrap();
+        let data = root.get("Data").unwrap();
+        assert_eq!(data.get("LevelName").and_then(|t| t.as_str()), Some("Nuevo mundo"));
+        assert_eq!(data.path("WorldGenSettings/seed").and_then(|t| t.as_i64()), Some(-4172144997902289642));
+        assert_eq!(data.path("Version/Name").and_then(|t| t.as_str()), Some("1.20.1"));
+        assert_eq!(data.path("DataPacks/Disabled").and_then(|t| t.as_list()).map(|l| l.len()), Some(0));
+        assert_eq!(
+            data.path("Player/UUID"),
+            Some(&Tag::IntArray(vec![107641332, 1156138790, -1522597718, -115345931]))
+        );
+
+        let dir = crate::test_fixtures::temp_dir("nbt-level");
+        let copy = dir.join("level.dat");
+        write_file(&copy, &name, &root, true).unwrap();
+        assert!(std::fs::read(&copy).unwrap().starts_with(&[0x1F, 0x8B]));
+        assert_eq!(read_file(&copy).unwrap(), (name, root));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let raw = std::fs::read(fixture("servers.dat")).unwrap();
+        assert!(from_bytes(&raw[..raw.len() - 1]).is_err());
+        assert!(from_bytes(&[TAG_LIST, 0, 0]).unwrap_err().contains("not a compound"));
+        assert!(to_bytes("", &Tag::Int(1)).is_err());
+        let mixed = Tag::Compound(BTreeMap::from([("l".to_string(), Tag::List(vec![Tag::Int(1), Tag::Byte(1)]))]));
+        assert!(to_bytes("", &mixed).is_err());
+    }
+}
@@ -0,0 +1,512 @@
+use crate::models::{DataDirectoryInfo, DataMoveReport};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Nombre de la carpeta de datos en la ubicación por defecto de cada sistema
+const APP_DIR_NAME: &str = "KindlyKlanKlient";
+
+/// Carpeta que usaban las versiones anteriores (`USERPROFILE`/`HOME` o el directorio de trabajo)
+const LEGACY_DIR_NAME: &str = ".kindlyklanklient";
+
+/// Variable de entorno para forzar la carpeta de datos (tiene prioridad sobre todo lo demás)
+const DATA_DIR_ENV: &str = "KINDLYKLANKLIENT_DATA_DIR";
+
+/// Si existe junto al ejecutable, los datos se guardan en `<exe>/data` (modo portable)
+const PORTABLE_MARKERS: &[&str] = &["portable", "portable.txt"];
+
+/// Archivo de arranque con la carpeta de datos elegida por el usuario. Vive en el directorio
+/// de configuración del sistema porque tiene que leerse antes de saber dónde están los datos
+const BOOTSTRAP_FILE: &str = "data_location.json";
+
+/// Marca de que ya se movieron los datos de la carpeta antigua
+const LEGACY_MIGRATION_MARKER: &str = ".legacy_migrated";
+
+#[derive(Debug, Clone)]
+struct ResolvedRoot {
+    path: PathBuf,
+    /// "env", "portable", "custom" o "default"
+    source: &'static str,
+}
+
+static DATA_ROOT: Lazy<Arc<Mutex<Option<ResolvedRoot>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+/// Activo mientras se mueve la carpeta de datos
+static DATA_MOVE_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+pub struct BootstrapConfig {
+    #[serde(default)]
+    pub data_dir: Option<String>,
+}
+
+fn bootstrap_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join(APP_DIR_NAME).join(BOOTSTRAP_FILE))
+}
+
+pub fn load_bootstrap_config() -> BootstrapConfig {
+    bootstrap_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_bootstrap_config(config: &BootstrapConfig) -> Result<(), String> {
+    let path = bootstrap_path().ok_or("Could not find config directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn portable_root() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    PORTABLE_MARKERS
+        .iter()
+        .any(|m| exe_dir.join(m).exists())
+        .then(|| exe_dir.join("data"))
+}
+
+/// Ubicación por defecto: `%LOCALAPPDATA%` en Windows, `~/Library/Application Support` en macOS
+/// y `$XDG_DATA_HOME` (`~/.local/share`) en Linux
+fn default_root() -> PathBuf {
+    dirs::data_local_dir()
+        .map(|d| d.join(APP_DIR_NAME))
+        .or_else(|| dirs::home_dir().map(|h| h.join(LEGACY_DIR_NAME)))
+        .unwrap_or_else(|| std::env::temp_dir().join(APP_DIR_NAME))
+}
+
+fn resolve_root() -> ResolvedRoot {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return ResolvedRoot { path: PathBuf::from(dir), source: "env" };
+    }
+    if let Some(path) = portable_root() {
+        return ResolvedRoot { path, source: "portable" };
+    }
+    if let Some(dir) = load_bootstrap_config().data_dir.filter(|d| !d.trim().is_empty()) {
+        return ResolvedRoot { path: PathBuf::from(dir), source: "custom" };
+    }
+    ResolvedRoot { path: default_root(), source: "default" }
+}
+
+fn resolved() -> ResolvedRoot {
+    let mut guard = DATA_ROOT.lock().unwrap_or_else(|e| e.into_inner());
+    guard.get_or_insert_with(resolve_root).clone()
+}
+
+/// Carpeta raíz de todos los datos del launcher (instancias, store, runtimes, skins, logs...)
+pub fn data_root() -> PathBuf {
+    resolved().path
+}
+
+/// Cambia la carpeta de datos en caliente (tras moverla); no la persiste
+pub fn set_data_root(path: PathBuf, source: &'static str) {
+    let mut guard = DATA_ROOT.lock().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(ResolvedRoot { path, source });
+}
+
+pub fn runtime_dir() -> PathBuf {
+    data_root().join("runtime")
+}
+
+pub fn logs_dir() -> PathBuf {
+    data_root().join("logs")
+}
+
+/// Carpetas antiguas de las que se migran los datos, sin duplicados
+fn legacy_roots() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for var in ["USERPROFILE", "HOME"] {
+        if let Some(home) = std::env::var_os(var) {
+            candidates.push(PathBuf::from(home).join(LEGACY_DIR_NAME));
+        }
+    }
+    // Sin USERPROFILE las versiones anteriores escribían en el directorio de trabajo
+    if let Ok(cwd) = std::env::current_dir() {
+        candidates.push(cwd.join(LEGACY_DIR_NAME));
+    }
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for candidate in candidates {
+        let canonical = dunce::canonicalize(&candidate).unwrap_or(candidate);
+        if canonical.is_dir() && !roots.contains(&canonical) {
+            roots.push(canonical);
+        }
+    }
+    roots
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> std::io::Result<()> {
+    for entry in walkdir::WalkDir::new(source) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let rel = entry.path().strip_prefix(source).map_err(std::io::Error::other)?;
+        let dest = target.join(rel);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Mueve `source` a `target`. Si el destino ya existe y ambos son carpetas, mezcla su contenido;
+/// los archivos que ya existen en el destino se dejan en el origen
+pub(crate) fn merge_move(source: &Path, target: &Path) -> std::io::Result<()> {
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if std::fs::rename(source, target).is_ok() {
+            return Ok(());
+        }
+        // rename no funciona entre volúmenes: copiar y borrar el original
+        if source.is_dir() {
+            copy_recursive(source, target)?;
+            return std::fs::remove_dir_all(source);
+        }
+        std::fs::copy(source, target)?;
+        return std::fs::remove_file(source);
+    }
+    if source.is_dir() && target.is_dir() {
+        for entry in std::fs::read_dir(source)?.flatten() {
+            merge_move(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        let _ = std::fs::remove_dir(source);
+    } else {
+        log::warn!("Not migrating {}: {} already exists", source.display(), target.display());
+    }
+    Ok(())
+}
+
+/// Migración única de la carpeta antigua a la nueva raíz de datos. Se ejecuta al arrancar,
+/// antes de que nada lea rutas. En modo portable no se mueve nada
+pub fn migrate_legacy_data() {
+    let root = resolved();
+    if root.source == "portable" || root.path.join(LEGACY_MIGRATION_MARKER).exists() {
+        return;
+    }
+    let target = dunce::canonicalize(&root.path).unwrap_or(root.path.clone());
+    let mut all_ok = true;
+    for legacy in legacy_roots() {
+        // Una carpeta dentro de la otra no se puede mover así; se usa tal cual
+        if target.starts_with(&legacy) || legacy.starts_with(&target) {
+            continue;
+        }
+        log::info!("Migrating launcher data from {} to {}", legacy.display(), target.display());
+        match merge_move(&legacy, &target) {
+            Ok(()) => log::info!("Launcher data migrated from {}", legacy.display()),
+            Err(e) => {
+                all_ok = false;
+                log::error!("Failed to migrate launcher data from {}: {}", legacy.display(), e);
+            }
+        }
+    }
+    // Si algo falló se reintenta en el próximo arranque
+    if !all_ok {
+        return;
+    }
+    if let Err(e) = std::fs::create_dir_all(&root.path)
+        .and_then(|_| std::fs::write(root.path.join(LEGACY_MIGRATION_MARKER), chrono::Utc::now().to_rfc3339()))
+    {
+        log::warn!("Failed to write legacy migration marker: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_data_directory_info() -> Result<DataDirectoryInfo, String> {
+    let root = resolved();
+    Ok(DataDirectoryInfo {
+        path: root.path.to_string_lossy().to_string(),
+        source: root.source.to_string(),
+        portable: root.source == "portable",
+        default_path: default_root().to_string_lossy().to_string(),
+    })
+}
+
+/// Espacio libre del disco que contiene `path` (el punto de montaje más largo que lo contenga)
+fn available_space(path: &Path) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().components().count())
+        .map(|d| d.available_space())
+}
+
+/// Primer ancestro existente de `path`, para canonicalizar rutas que aún no existen
+fn canonical_target(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut rest: Vec<std::ffi::OsString> = Vec::new();
+    while !existing.exists() {
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                rest.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+    let mut canonical = dunce::canonicalize(&existing).unwrap_or(existing);
+    for name in rest.into_iter().rev() {
+        canonical.push(name);
+    }
+    canonical
+}
+
+fn check_can_move(app_handle: &AppHandle) -> Result<(), String> {
+    let downloading = match app_handle.try_state::<Arc<Mutex<bool>>>() {
+        Some(state) => state.lock().map(|d| *d).unwrap_or(false),
+        None => false,
+    };
+    if downloading || crate::predownload::has_active_predownloads() {
+        return Err("Cannot move the data directory while a download is in progress".to_string());
+    }
+    let running = match app_handle.try_state::<Arc<Mutex<HashMap<String, u32>>>>() {
+        Some(state) => state.lock().map(|p| !p.is_empty()).unwrap_or(false),
+        None => false,
+    };
+    if running {
+        return Err("Cannot move the data directory while Minecraft is running".to_string());
+    }
+    Ok(())
+}
+
+/// Lanzamientos, sincronizaciones y pre-descargas lo comprueban antes de tocar la carpeta de datos
+pub fn ensure_data_not_moving() -> Result<(), String> {
+    if DATA_MOVE_IN_PROGRESS.load(Ordering::SeqCst) {
+        return Err("The data directory is being moved, try again when it finishes".to_string());
+    }
+    Ok(())
+}
+
+/// Bloquea lanzamientos, sincronizaciones y pre-descargas durante todo el movimiento. También
+/// marca el estado de descarga para que no se pueda cerrar el launcher a medias
+struct DataMoveGuard {
+    app_handle: AppHandle,
+}
+
+impl DataMoveGuard {
+    fn acquire(app_handle: &AppHandle) -> Result<Self, String> {
+        if DATA_MOVE_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return Err("The data directory is already being moved".to_string());
+        }
+        // Desde aquí nada nuevo puede empezar; el guard libera la marca si algo ya estaba en curso
+        let guard = Self { app_handle: app_handle.clone() };
+        check_can_move(app_handle)?;
+        if let Some(state) = app_handle.try_state::<Arc<Mutex<bool>>>() {
+            if let Ok(mut downloading) = state.lock() {
+                *downloading = true;
+            }
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for DataMoveGuard {
+    fn drop(&mut self) {
+        if let Some(state) = self.app_handle.try_state::<Arc<Mutex<bool>>>() {
+            if let Ok(mut downloading) = state.lock() {
+                *downloading = false;
+            }
+        }
+        DATA_MOVE_IN_PROGRESS.store(false, Ordering::SeqCst);
+    }
+}
+
+fn emit_move_progress(app_handle: &AppHandle, current: usize, total: usize, current_file: &str, status: &str) {
+    let percentage = if total > 0 { (current as f64 / total as f64 * 100.0) as u32 } else { 100 };
+    let _ = app_handle.emit(
+        "data-move-progress",
+        serde_json::json!({
+            "current": current,
+            "total": total,
+            "percentage": percentage,
+            "current_file": current_file,
+            "status": status,
+        }),
+    );
+}
+
+/// Comprueba que `copied` tiene el tamaño y el sha1 del original
+fn verify_copied_file(original: &Path, copied: &Path, size: u64) -> Result<(), String> {
+    let copied_size = std::fs::metadata(copied).map(|m| m.len()).map_err(|e| e.to_string())?;
+    if copied_size != size {
+        return Err(format!("{} bytes copied, {} expected", copied_size, size));
+    }
+    if crate::store::sha1_file(original)? != crate::store::sha1_file(copied)? {
+        return Err("content differs from the original".to_string());
+    }
+    Ok(())
+}
+
+/// Copia toda la carpeta de datos y comprueba la copia. Devuelve (archivos, bytes)
+fn copy_and_verify(app_handle: &AppHandle, source: &Path, target: &Path) -> Result<(usize, u64), String> {
+    let files: Vec<(PathBuf, u64)> = walkdir::WalkDir::new(source)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| {
+            let size = e.metadata().map(|m| m.len()).unwrap_or(0);
+            (e.into_path(), size)
+        })
+        .collect();
+    let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+
+    if let Some(free) = available_space(target) {
+        if free < total_bytes {
+            return Err(format!(
+                "Not enough free space in {}: {} MB needed, {} MB available",
+                target.display(),
+                total_bytes / (1024 * 1024),
+                free / (1024 * 1024)
+            ));
+        }
+    }
+
+    std::fs::create_dir_all(target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let total = files.len();
+    for (i, (path, _)) in files.iter().enumerate() {
+        let rel = path.strip_prefix(source).map_err(|e| e.to_string())?;
+        let dest = target.join(rel);
+        emit_move_progress(app_handle, i, total, &rel.to_string_lossy(), "copying");
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::copy(path, &dest).map_err(|e| format!("Failed to copy {}: {}", rel.display(), e))?;
+    }
+
+    // Verificación del contenido antes de borrar nada: un archivo truncado o corrupto puede
+    // tener el tamaño correcto
+    for (i, (path, size)) in files.iter().enumerate() {
+        let rel = path.strip_prefix(source).map_err(|e| e.to_string())?;
+        emit_move_progress(app_handle, i, total, &rel.to_string_lossy(), "verifying");
+        verify_copied_file(path, &target.join(rel), *size).map_err(|e| format!("Verification failed for {}: {}", rel.display(), e))?;
+    }
+    Ok((total, total_bytes))
+}
+
+/// Mueve toda la carpeta de datos (instancias, store, runtimes, skins, sesiones...) a `new_path`
+/// y la guarda como carpeta elegida. Entre discos distintos copia, verifica y solo entonces
+/// cambia; si algo falla la carpeta antigua sigue siendo la buena
+#[tauri::command]
+pub async fn move_data_directory(new_path: String, app_handle: AppHandle) -> Result<DataMoveReport, String> {
+    let _guard = DataMoveGuard::acquire(&app_handle)?;
+
+    let current = resolved();
+    if current.source == "env" || current.source == "portable" {
+        return Err(format!("The data directory is fixed by {} mode and cannot be moved", current.source));
+    }
+
+    let requested = PathBuf::from(new_path.trim());
+    if !requested.is_absolute() {
+        return Err("The new data directory must be an absolute path".to_string());
+    }
+    let old_root = dunce::canonicalize(&current.path).unwrap_or(current.path.clone());
+    let new_root = canonical_target(&requested);
+    if new_root == old_root {
+        return Err("The data directory is already there".to_string());
+    }
+    if new_root.starts_with(&old_root) || old_root.starts_with(&new_root) {
+        return Err("The new data directory cannot be inside the current one or contain it".to_string());
+    }
+    if new_root.exists() {
+        let empty = std::fs::read_dir(&new_root).map(|mut d| d.next().is_none()).unwrap_or(false);
+        if !empty {
+            return Err(format!("{} already exists and is not empty", new_root.display()));
+        }
+    }
+
+    log::info!("Moving data directory from {} to {}", old_root.display(), new_root.display());
+
+    let handle = app_handle.clone();
+    let (old, new) = (old_root.clone(), new_root.clone());
+    let (method, moved_files, moved_bytes) = tauri::async_runtime::spawn_blocking(move || -> Result<(String, usize, u64), String> {
+        std::fs::create_dir_all(&new).map_err(|e| format!("Failed to create {}: {}", new.display(), e))?;
+        if !old.exists() {
+            return Ok(("rename".to_string(), 0, 0));
+        }
+        // En el mismo disco basta con renombrar (la carpeta destino vacía se sustituye)
+        let _ = std::fs::remove_dir(&new);
+        if std::fs::rename(&old, &new).is_ok() {
+            let files = walkdir::WalkDir::new(&new).into_iter().flatten().filter(|e| e.file_type().is_file());
+            let (count, bytes) = files.fold((0usize, 0u64), |(c, b), e| (c + 1, b + e.metadata().map(|m| m.len()).unwrap_or(0)));
+            return Ok(("rename".to_string(), count, bytes));
+        }
+        match copy_and_verify(&handle, &old, &new) {
+            Ok((count, bytes)) => Ok(("copy".to_string(), count, bytes)),
+            Err(e) => {
+                // Deshacer la copia a medias; la carpeta antigua no se ha tocado
+                let _ = std::fs::remove_dir_all(&new);
+                Err(e)
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Data move task failed: {}", e))??;
+
+    save_bootstrap_config(&BootstrapConfig { data_dir: Some(new_root.to_string_lossy().to_string()) })?;
+    set_data_root(new_root.clone(), "custom");
+    crate::dedup::reset_index();
+    // El log del launcher sigue abierto en la carpeta antigua: se cambia antes de borrarla
+    if let Err(e) = crate::logging::reopen_log_file() {
+        log::warn!("Failed to reopen the launcher log in {}: {}", new_root.display(), e);
+    }
+
+    let old_removed = if method == "rename" {
+        true
+    } else {
+        emit_move_progress(&app_handle, moved_files, moved_files, "", "cleaning");
+        let old = old_root.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || std::fs::remove_dir_all(&old))
+            .await
+            .map_err(|e| e.to_string())?;
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Data moved but the old directory {} could not be removed: {}", old_root.display(), e);
+                false
+            }
+        }
+    };
+
+    emit_move_progress(&app_handle, moved_files, moved_files, "", "completed");
+    log::info!("Data directory moved to {} ({}, {} files)", new_root.display(), method, moved_files);
+
+    Ok(DataMoveReport {
+        old_path: old_root.to_string_lossy().to_string(),
+        new_path: new_root.to_string_lossy().to_string(),
+        method,
+        moved_files,
+        moved_bytes,
+        old_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_are_verified_by_content() {
+        let dir = crate::test_fixtures::temp_dir("data-move-verify");
+        let original = dir.join("sessions.db");
+        let copied = dir.join("copy.db");
+        std::fs::write(&original, b"session-token").unwrap();
+
+        std::fs::write(&copied, b"session-token").unwrap();
+        assert!(verify_copied_file(&original, &copied, 13).is_ok());
+        // Mismo tamaño, contenido distinto
+        std::fs::write(&copied, b"session-tokeX").unwrap();
+        assert!(verify_copied_file(&original, &copied, 13).unwrap_err().contains("content differs"));
+        std::fs::write(&copied, b"session").unwrap();
+        assert!(verify_copied_file(&original, &copied, 13).is_err());
+        std::fs::remove_file(&copied).unwrap();
+        assert!(verify_copied_file(&original, &copied, 13).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
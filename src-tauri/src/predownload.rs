@@ -0,0 +1,279 @@
+use crate::models::PredownloadState;
+use crate::sync_engine::{InstanceSyncRequest, SyncOptions};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// Límite por defecto de la pre-descarga: 1 MiB/s para no saturar la conexión del jugador
+const DEFAULT_PREDOWNLOAD_BYTES_PER_SEC: u64 = 1024 * 1024;
+
+/// Margen aleatorio tras la apertura antes de la sincronización final, para no llegar todos a la vez
+const FINAL_SYNC_MAX_JITTER_SECS: u64 = 120;
+
+struct ActivePredownload {
+    cancel: Arc<AtomicBool>,
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+/// Pre-descargas en curso por instancia, con su flag de cancelación y la tarea que la ejecuta.
+/// Solo están aquí mientras descargan: es lo que bloquea mover datos o limpiar el store
+static ACTIVE_PREDOWNLOADS: Lazy<Arc<Mutex<HashMap<String, ActivePredownload>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/// Instancias ya pre-descargadas que esperan a la apertura para la sincronización final, con su
+/// flag de cancelación. No escriben nada mientras esperan
+static SCHEDULED_FINAL_SYNCS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn state_path(instance_id: &str) -> PathBuf {
+    crate::launcher::get_instance_directory(instance_id).join(".predownload.json")
+}
+
+pub fn load_predownload_state(instance_id: &str) -> Option<PredownloadState> {
+    let content = std::fs::read_to_string(state_path(instance_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_predownload_state(app_handle: &AppHandle, state: &PredownloadState) {
+    let path = state_path(&state.instance_id);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(state) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                log::warn!("Failed to write predownload state for {}: {}", state.instance_id, e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize predownload state: {}", e),
+    }
+    let _ = app_handle.emit("predownload-status", state);
+}
+
+/// Detiene la pre-descarga de una instancia si hay una en curso y espera a que termine, para que
+/// quien la cancela no escriba en la instancia a la vez que ella
+pub async fn cancel_predownload(instance_id: &str) {
+    if let Some(cancel) = SCHEDULED_FINAL_SYNCS.lock().ok().and_then(|mut s| s.remove(instance_id)) {
+        log::info!("Cancelling scheduled final sync of {}", instance_id);
+        cancel.store(true, Ordering::Relaxed);
+    }
+    let task = {
+        let Ok(mut active) = ACTIVE_PREDOWNLOADS.lock() else { return };
+        let Some(predownload) = active.remove(instance_id) else { return };
+        log::info!("Cancelling background predownload of {}", instance_id);
+        predownload.cancel.store(true, Ordering::Relaxed);
+        predownload.task
+    };
+    if let Some(task) = task {
+        if let Err(e) = task.await {
+            log::warn!("Background predownload of {} ended abnormally: {}", instance_id, e);
+        }
+    }
+}
+
+pub fn has_active_predownloads() -> bool {
+    ACTIVE_PREDOWNLOADS.lock().map(|a| !a.is_empty()).unwrap_or(false)
+}
+
+/// Duerme `secs` segundos comprobando la cancelación cada segundo. Devuelve false si se canceló
+async fn sleep_unless_cancelled(secs: u64, cancel: &AtomicBool) -> bool {
+    for _ in 0..secs {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    !cancel.load(Ordering::Relaxed)
+}
+
+/// Espera hasta que abra la ventana de la instancia. Devuelve false si se canceló
+async fn wait_until_open(instance_id: &str, cancel: &AtomicBool) -> bool {
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        let availability = crate::scheduling::instance_availability(instance_id);
+        let Some(seconds) = availability.seconds_until_open.filter(|_| availability.status == "upcoming") else {
+            return true;
+        };
+        // Se revisa al menos cada minuto por si cambia el manifest
+        if !sleep_unless_cancelled(seconds.clamp(1, 60) as u64, cancel).await {
+            return false;
+        }
+    }
+}
+
+/// Quita la entrada de la pre-descarga si sigue siendo la de este `cancel`. Si se canceló, la
+/// entrada ya no está o pertenece a una pre-descarga nueva
+fn unregister_active(instance_id: &str, cancel: &Arc<AtomicBool>) {
+    if let Ok(mut active) = ACTIVE_PREDOWNLOADS.lock() {
+        if active.get(instance_id).is_some_and(|p| Arc::ptr_eq(&p.cancel, cancel)) {
+            active.remove(instance_id);
+        }
+    }
+}
+
+async fn run_predownload(app_handle: AppHandle, request: InstanceSyncRequest, max_bytes_per_sec: u64, cancel: Arc<AtomicBool>) {
+    let instance_id = request.instance_id.clone();
+    let mut state = PredownloadState {
+        instance_id: instance_id.clone(),
+        status: "downloading".to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+        completed_at: None,
+        last_error: None,
+    };
+    save_predownload_state(&app_handle, &state);
+
+    log::info!("Starting background predownload of {} ({} bytes/s)", instance_id, max_bytes_per_sec);
+    let options = SyncOptions::background(Some(max_bytes_per_sec), cancel.clone());
+    let result = crate::sync_engine::sync_instance(&app_handle, &request, &options).await;
+
+    match result {
+        Ok(()) => {
+            // Lista pero no lanzable hasta que abra la ventana (lo comprueba scheduling al lanzar)
+            state.status = "ready".to_string();
+            state.completed_at = Some(chrono::Utc::now().to_rfc3339());
+            save_predownload_state(&app_handle, &state);
+            log::info!("Background predownload of {} is ready", instance_id);
+
+            // La espera hasta la apertura puede durar días: deja de contar como descarga activa
+            let scheduled = {
+                let Ok(mut active) = ACTIVE_PREDOWNLOADS.lock() else { return };
+                let Ok(mut scheduled) = SCHEDULED_FINAL_SYNCS.lock() else { return };
+                let ours = active.get(&instance_id).is_some_and(|p| Arc::ptr_eq(&p.cancel, &cancel));
+                let schedule = ours && !cancel.load(Ordering::Relaxed);
+                if schedule {
+                    active.remove(&instance_id);
+                    scheduled.insert(instance_id.clone(), cancel.clone());
+                }
+                schedule
+            };
+            if scheduled {
+                tauri::async_runtime::spawn(run_scheduled_final_sync(app_handle, request, state, cancel));
+                return;
+            }
+        }
+        Err(e) if options.is_cancelled() => {
+            state.status = "cancelled".to_string();
+            save_predownload_state(&app_handle, &state);
+            log::info!("Background predownload of {} cancelled: {}", instance_id, e);
+        }
+        Err(e) => {
+            state.status = "failed".to_string();
+            state.last_error = Some(e.clone());
+            save_predownload_state(&app_handle, &state);
+            log::warn!("Background predownload of {} failed: {}", instance_id, e);
+        }
+    }
+
+    unregister_active(&instance_id, &cancel);
+}
+
+/// Espera a la apertura (más un margen aleatorio) y entonces lanza la sincronización final como
+/// una descarga activa más
+async fn run_scheduled_final_sync(app_handle: AppHandle, request: InstanceSyncRequest, state: PredownloadState, cancel: Arc<AtomicBool>) {
+    let instance_id = request.instance_id.clone();
+    let open = wait_until_open(&instance_id, &cancel).await && {
+        use rand::Rng;
+        let jitter = rand::thread_rng().gen_range(0..=FINAL_SYNC_MAX_JITTER_SECS);
+        sleep_unless_cancelled(jitter, &cancel).await
+    };
+
+    let Ok(mut active) = ACTIVE_PREDOWNLOADS.lock() else { return };
+    let Ok(mut scheduled) = SCHEDULED_FINAL_SYNCS.lock() else { return };
+    if !scheduled.get(&instance_id).is_some_and(|c| Arc::ptr_eq(c, &cancel)) {
+        return;
+    }
+    scheduled.remove(&instance_id);
+    if !open || active.contains_key(&instance_id) {
+        return;
+    }
+    active.insert(instance_id.clone(), ActivePredownload { cancel: cancel.clone(), task: None });
+    let task = tauri::async_runtime::spawn(run_final_sync(app_handle, request, state, cancel));
+    if let Some(predownload) = active.get_mut(&instance_id) {
+        predownload.task = Some(task);
+    }
+}
+
+/// Sincronización final: solo baja lo que haya cambiado desde la pre-descarga
+async fn run_final_sync(app_handle: AppHandle, request: InstanceSyncRequest, mut state: PredownloadState, cancel: Arc<AtomicBool>) {
+    let instance_id = request.instance_id.clone();
+    let final_options = SyncOptions {
+        parallelism: Some(4),
+        limiter: None,
+        progress_event: Some("predownload-progress"),
+        cancel: Some(cancel.clone()),
+    };
+    match crate::sync_engine::sync_instance(&app_handle, &request, &final_options).await {
+        Ok(()) => {
+            state.status = "synced".to_string();
+            state.completed_at = Some(chrono::Utc::now().to_rfc3339());
+            save_predownload_state(&app_handle, &state);
+        }
+        Err(e) if final_options.is_cancelled() => log::info!("Final sync of {} cancelled: {}", instance_id, e),
+        Err(e) => log::warn!("Final sync of {} failed, it will run on launch: {}", instance_id, e),
+    }
+    unregister_active(&instance_id, &cancel);
+}
+
+/// Descarga en segundo plano una instancia publicada que aún no ha abierto
+#[tauri::command]
+pub async fn start_instance_predownload(
+    instance_id: String,
+    minecraft_version: String,
+    base_url: String,
+    instance_url: String,
+    max_bytes_per_sec: Option<u64>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
+    let availability = crate::scheduling::instance_availability(&instance_id);
+    if availability.status == "closed" {
+        return Err(format!("{}: instance '{}' is closed", crate::scheduling::INSTANCE_NOT_AVAILABLE, instance_id));
+    }
+
+    // Una pre-descarga nueva sustituye a la sincronización final que estuviera esperando
+    if let Some(previous) = SCHEDULED_FINAL_SYNCS.lock().ok().and_then(|mut s| s.remove(&instance_id)) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = ACTIVE_PREDOWNLOADS.lock().map_err(|e| e.to_string())?;
+        if active.contains_key(&instance_id) {
+            log::info!("Predownload of {} already running", instance_id);
+            return Ok(());
+        }
+        active.insert(instance_id.clone(), ActivePredownload { cancel: cancel.clone(), task: None });
+    }
+
+    let request = InstanceSyncRequest {
+        instance_id,
+        minecraft_version,
+        base_url: Some(base_url),
+        instance_url: Some(instance_url),
+    };
+    let limit = max_bytes_per_sec.unwrap_or(DEFAULT_PREDOWNLOAD_BYTES_PER_SEC);
+    let instance_id = request.instance_id.clone();
+    let task = tauri::async_runtime::spawn(run_predownload(app_handle, request, limit, cancel.clone()));
+    if let Ok(mut active) = ACTIVE_PREDOWNLOADS.lock() {
+        if let Some(predownload) = active.get_mut(&instance_id).filter(|p| Arc::ptr_eq(&p.cancel, &cancel)) {
+            predownload.task = Some(task);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_predownload_status(instance_id: String) -> Result<Option<PredownloadState>, String> {
+    Ok(load_predownload_state(&instance_id))
+}
+
+#[tauri::command]
+pub async fn cancel_instance_predownload(instance_id: String) -> Result<(), String> {
+    cancel_predownload(&instance_id).await;
+    Ok(())
+}
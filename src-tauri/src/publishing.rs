@@ -0,0 +1,515 @@
+use crate::models::{DistributionManifest, InstanceManifest, InstanceSummary, PublishReport, PublishTarget};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+const DISTRIBUTION_MANIFEST_FILE: &str = "manifest.json";
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn content_type_for(key: &str) -> &'static str {
+    let lower = key.to_lowercase();
+    if lower.ends_with(".json") {
+        "application/json"
+    } else if lower.ends_with(".jar") {
+        "application/java-archive"
+    } else if lower.ends_with(".zip") {
+        "application/zip"
+    } else if lower.ends_with(".png") {
+        "image/png"
+    } else if lower.ends_with(".mp4") {
+        "video/mp4"
+    } else if lower.ends_with(".txt") || lower.ends_with(".toml") || lower.ends_with(".cfg") {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Codifica cada segmento de la ruta según RFC 3986 (lo que exige SigV4), manteniendo las barras
+fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn with_prefix(prefix: &Option<String>, key: &str) -> String {
+    match prefix.as_deref().map(|p| p.trim_matches('/')).filter(|p| !p.is_empty()) {
+        Some(p) => format!("{}/{}", p, key.trim_start_matches('/')),
+        None => key.trim_start_matches('/').to_string(),
+    }
+}
+
+/// Solo un "no existe" claro cuenta como objeto ausente; cualquier otro error debe abortar la
+/// publicación para no sobrescribir el manifest remoto con el local.
+/// Supabase Storage responde 400 con "Object not found" en lugar de 404
+fn is_missing_object(target: &PublishTarget, status: reqwest::StatusCode, body: &str) -> bool {
+    match target {
+        PublishTarget::S3 { .. } => status == reqwest::StatusCode::NOT_FOUND,
+        PublishTarget::SupabaseStorage { .. } => {
+            status == reqwest::StatusCode::NOT_FOUND
+                || (status == reqwest::StatusCode::BAD_REQUEST && body.contains("Object not found"))
+        }
+    }
+}
+
+/// Cliente mínimo para subir y leer objetos del destino de publicación
+struct Publisher {
+    target: PublishTarget,
+    client: reqwest::Client,
+}
+
+impl Publisher {
+    fn new(target: PublishTarget) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .user_agent("KindlyKlanKlient/1.0")
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .timeout(std::time::Duration::from_secs(600))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(Self { target, client })
+    }
+
+    fn s3_url(&self, key: &str) -> Result<url::Url, String> {
+        let PublishTarget::S3 { endpoint, bucket, prefix, path_style, .. } = &self.target else {
+            return Err("Not an S3 target".to_string());
+        };
+        let full_key = encode_key(&with_prefix(prefix, key));
+        let endpoint = url::Url::parse(endpoint.trim_end_matches('/'))
+            .map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+        let raw = if *path_style {
+            format!("{}/{}/{}", endpoint.as_str().trim_end_matches('/'), bucket, full_key)
+        } else {
+            let host = endpoint.host_str().ok_or("S3 endpoint has no host")?;
+            let port = endpoint.port().map(|p| format!(":{}", p)).unwrap_or_default();
+            format!("{}://{}.{}{}/{}", endpoint.scheme(), bucket, host, port, full_key)
+        };
+        url::Url::parse(&raw).map_err(|e| format!("Invalid S3 object URL: {}", e))
+    }
+
+    /// Firma AWS Signature V4 para una petición sin query string
+    fn s3_signed_headers(&self, method: &str, url: &url::Url, payload_hash: &str) -> Result<Vec<(String, String)>, String> {
+        let PublishTarget::S3 { region, access_key, secret_key, .. } = &self.target else {
+            return Err("Not an S3 target".to_string());
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = now.format("%Y%m%d").to_string();
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method,
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date_stamp, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+
+        let k_date = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date_stamp.as_bytes());
+        let k_region = hmac_sha256(&k_date, region.as_bytes());
+        let k_service = hmac_sha256(&k_region, b"s3");
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hmac_sha256(&k_signing, string_to_sign.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+
+        Ok(vec![
+            ("x-amz-date".to_string(), amz_date),
+            ("x-amz-content-sha256".to_string(), payload_hash.to_string()),
+            (
+                "Authorization".to_string(),
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+                    access_key, scope, signature
+                ),
+            ),
+        ])
+    }
+
+    fn supabase_config(&self) -> Result<(String, String, String), String> {
+        let PublishTarget::SupabaseStorage { bucket, url, api_key, .. } = &self.target else {
+            return Err("Not a Supabase Storage target".to_string());
+        };
+        let (default_url, default_key) = crate::whitelist::get_supabase_config();
+        let url = url.clone().unwrap_or(default_url);
+        let key = api_key.clone().unwrap_or(default_key);
+        if url == "https://your-project.supabase.co" || key == "your-anon-key" {
+            return Err("Supabase not configured for publishing".to_string());
+        }
+        Ok((url.trim_end_matches('/').to_string(), key, bucket.clone()))
+    }
+
+    async fn put_object(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        let content_type = content_type_for(key);
+        let response = match &self.target {
+            PublishTarget::S3 { .. } => {
+                let url = self.s3_url(key)?;
+                let payload_hash = sha256_hex(&body);
+                let mut request = self.client.put(url.clone()).header("Content-Type", content_type).body(body);
+                for (name, value) in self.s3_signed_headers("PUT", &url, &payload_hash)? {
+                    request = request.header(name, value);
+                }
+                request.send().await
+            }
+            PublishTarget::SupabaseStorage { prefix, .. } => {
+                let (url, key_auth, bucket) = self.supabase_config()?;
+                let object_url = format!("{}/storage/v1/object/{}/{}", url, bucket, encode_key(&with_prefix(prefix, key)));
+                self.client
+                    .post(&object_url)
+                    .header("apikey", &key_auth)
+                    .header("Authorization", format!("Bearer {}", key_auth))
+                    .header("Content-Type", content_type)
+                    .header("x-upsert", "true")
+                    .body(body)
+                    .send()
+                    .await
+            }
+        }
+        .map_err(|e| format!("Failed to upload {}: {}", key, e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!("Upload of {} failed: {} - {}", key, status, error_text));
+        }
+        Ok(())
+    }
+
+    /// Devuelve `None` si el objeto no existe en el destino
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = match &self.target {
+            PublishTarget::S3 { .. } => {
+                let url = self.s3_url(key)?;
+                let payload_hash = sha256_hex(b"");
+                let mut request = self.client.get(url.clone());
+                for (name, value) in self.s3_signed_headers("GET", &url, &payload_hash)? {
+                    request = request.header(name, value);
+                }
+                request.send().await
+            }
+            PublishTarget::SupabaseStorage { prefix, .. } => {
+                let (url, key_auth, bucket) = self.supabase_config()?;
+                let object_url = format!("{}/storage/v1/object/{}/{}", url, bucket, encode_key(&with_prefix(prefix, key)));
+                self.client
+                    .get(&object_url)
+                    .header("apikey", &key_auth)
+                    .header("Authorization", format!("Bearer {}", key_auth))
+                    .send()
+                    .await
+            }
+        }
+        .map_err(|e| format!("Failed to fetch {}: {}", key, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            if is_missing_object(&self.target, status, &error_text) {
+                return Ok(None);
+            }
+            return Err(format!("Fetching {} failed: {} - {}", key, status, error_text));
+        }
+
+        let bytes = response.bytes().await.map_err(|e| format!("Failed to read {}: {}", key, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Comprueba que todos los archivos del manifest están en el directorio generado antes de subir nada
+/// Actualiza la entrada remota con la del build local. Lo que el build no fija (programación,
+/// vídeo de fondo, imágenes) se mantiene tal como esté en el manifest remoto
+fn merge_published_summary(remote: &mut InstanceSummary, local: InstanceSummary) {
+    let icon = local.icon.clone().or_else(|| remote.icon.take());
+    let background = local.background.clone().or_else(|| remote.background.take());
+    let background_video = local.background_video.clone().or_else(|| remote.background_video.take());
+    let available_from = local.available_from.clone().or_else(|| remote.available_from.take());
+    let available_until = local.available_until.clone().or_else(|| remote.available_until.take());
+    *remote = InstanceSummary { icon, background, background_video, available_from, available_until, ..local };
+}
+
+fn verify_build(publish_dir: &Path, manifest: &InstanceManifest, checksums: &HashMap<String, String>) -> Result<(), String> {
+    let mut entries = manifest.files.mods.iter().chain(manifest.files.configs.iter()).collect::<Vec<_>>();
+    if let Some(rp) = &manifest.files.resourcepacks { entries.extend(rp.iter()); }
+    if let Some(sp) = &manifest.files.shaderpacks { entries.extend(sp.iter()); }
+
+    for entry in entries {
+        if entry.url.starts_with("http://") || entry.url.starts_with("https://") {
+            continue;
+        }
+        if !checksums.contains_key(&entry.path) || !publish_dir.join(&entry.path).exists() {
+            return Err(format!("Build is incomplete, missing file: {}", entry.path));
+        }
+    }
+    Ok(())
+}
+
+/// Sube una build generada por `generate_instance_manifest`. Sólo se suben los archivos cuyo sha256
+/// difiere del `checksums.json` remoto, después el manifest de la instancia y por último el de la distribución,
+/// de forma que un jugador nunca descarga un manifest que apunte a archivos aún no subidos
+#[tauri::command]
+pub async fn publish_instance_build(
+    output_dir: String,
+    instance_id: String,
+    target: PublishTarget,
+    dry_run: Option<bool>,
+    app_handle: AppHandle,
+) -> Result<PublishReport, String> {
+    let perms = crate::admins::require_permission(&app_handle, crate::admins::permissions::INSTANCES_PUBLISH).await?;
+    if !perms.is_admin() {
+        return Err(crate::admins::permission_denied_error(&perms.username, "admin"));
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    let output_root = PathBuf::from(&output_dir);
+    let publish_dir = output_root.join("instances").join(&instance_id);
+    let instance_prefix = format!("instances/{}", instance_id);
+
+    let manifest: InstanceManifest = read_json_file(&publish_dir.join("instance.json"))?;
+    let checksums: HashMap<String, String> = read_json_file(&publish_dir.join("checksums.json"))?;
+    let local_distribution: DistributionManifest = read_json_file(&output_root.join(DISTRIBUTION_MANIFEST_FILE))?;
+    let summary = local_distribution
+        .instances
+        .iter()
+        .find(|i| i.id == instance_id)
+        .cloned()
+        .ok_or_else(|| format!("Instance {} not found in distribution manifest", instance_id))?;
+
+    verify_build(&publish_dir, &manifest, &checksums)?;
+
+    let publisher = Publisher::new(target)?;
+
+    let remote_checksums: HashMap<String, String> = match publisher.get_object(&format!("{}/checksums.json", instance_prefix)).await? {
+        Some(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            log::warn!("Remote checksums for {} are invalid, uploading everything: {}", instance_id, e);
+            HashMap::new()
+        }),
+        None => HashMap::new(),
+    };
+
+    let mut changed: Vec<String> = checksums
+        .iter()
+        .filter(|(path, sha)| remote_checksums.get(*path) != Some(*sha))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.sort();
+
+    let mut report = PublishReport {
+        instance_id: instance_id.clone(),
+        unchanged_files: checksums.len() - changed.len(),
+        dry_run,
+        ..Default::default()
+    };
+
+    log::info!("Publishing {}: {} changed files, {} unchanged", instance_id, changed.len(), report.unchanged_files);
+
+    if dry_run {
+        report.uploaded_files = changed;
+        return Ok(report);
+    }
+
+    let total = changed.len() + 3;
+    let emit_progress = |current: usize, file: &str, status: &str| {
+        let _ = app_handle.emit("publish-progress", serde_json::json!({
+            "current": current,
+            "total": total,
+            "percentage": (current as f32 / total as f32) * 100.0,
+            "current_file": file,
+            "status": status
+        }));
+    };
+
+    {
+        use futures_util::stream::{self, StreamExt};
+        let mut uploads = stream::iter(changed.iter().cloned())
+            .map(|rel| {
+                let publisher = &publisher;
+                let path = publish_dir.join(&rel);
+                let key = format!("{}/{}", instance_prefix, rel);
+                async move {
+                    let body = tokio::fs::read(&path)
+                        .await
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                    let size = body.len() as u64;
+                    publisher.put_object(&key, body).await?;
+                    Ok::<(String, u64), String>((rel, size))
+                }
+            })
+            .buffer_unordered(4);
+
+        let mut done = 0;
+        while let Some(result) = uploads.next().await {
+            let (rel, size) = result?;
+            done += 1;
+            emit_progress(done, &rel, "Files");
+            report.uploaded_bytes += size;
+            report.uploaded_files.push(rel);
+        }
+    }
+
+    // Manifest de la instancia y checksums, una vez que todos los archivos existen
+    for name in ["instance.json", "checksums.json"] {
+        let body = tokio::fs::read(publish_dir.join(name))
+            .await
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        publisher.put_object(&format!("{}/{}", instance_prefix, name), body).await?;
+    }
+    emit_progress(changed.len() + 2, "instance.json", "Instance");
+
+    // Por último el manifest de la distribución. Se parte del remoto para no borrar otras instancias
+    let mut distribution = match publisher.get_object(DISTRIBUTION_MANIFEST_FILE).await? {
+        Some(bytes) => serde_json::from_slice::<DistributionManifest>(&bytes)
+            .map_err(|e| format!("Remote distribution manifest is invalid, refusing to overwrite it: {}", e))?,
+        None => local_distribution.clone(),
+    };
+    match distribution.instances.iter_mut().find(|i| i.id == instance_id) {
+        Some(existing) => merge_published_summary(existing, summary),
+        None => distribution.instances.push(summary),
+    }
+    distribution.distribution.last_updated = chrono::Utc::now().to_rfc3339();
+
+    let distribution_json = serde_json::to_vec_pretty(&distribution)
+        .map_err(|e| format!("Failed to serialize distribution manifest: {}", e))?;
+    publisher.put_object(DISTRIBUTION_MANIFEST_FILE, distribution_json).await?;
+    report.distribution_updated = true;
+    emit_progress(total, DISTRIBUTION_MANIFEST_FILE, "Completado");
+
+    log::info!("Published {}: {} files, {} bytes", instance_id, report.uploaded_files.len(), report.uploaded_bytes);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn supabase_target() -> PublishTarget {
+        PublishTarget::SupabaseStorage { bucket: "launcher".to_string(), prefix: None, url: None, api_key: None }
+    }
+
+    fn s3_target(endpoint: &str, bucket: &str, access_key: &str, secret_key: &str) -> PublishTarget {
+        PublishTarget::S3 {
+            endpoint: endpoint.to_string(),
+            region: "us-east-1".to_string(),
+            bucket: bucket.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            prefix: Some("tests".to_string()),
+            path_style: true,
+        }
+    }
+
+    #[test]
+    fn supabase_bad_request_is_missing_only_for_object_not_found() {
+        let target = supabase_target();
+        let not_found = r#"{"statusCode":"404","error":"not_found","message":"Object not found"}"#;
+        assert!(is_missing_object(&target, StatusCode::BAD_REQUEST, not_found));
+        assert!(is_missing_object(&target, StatusCode::NOT_FOUND, ""));
+        let bad_jwt = r#"{"statusCode":"400","error":"InvalidJWT","message":"jwt malformed"}"#;
+        assert!(!is_missing_object(&target, StatusCode::BAD_REQUEST, bad_jwt));
+        assert!(!is_missing_object(&target, StatusCode::FORBIDDEN, not_found));
+        assert!(!is_missing_object(&target, StatusCode::INTERNAL_SERVER_ERROR, ""));
+    }
+
+    #[test]
+    fn s3_only_treats_404_as_missing() {
+        let target = s3_target("http://localhost:9000", "launcher", "key", "secret");
+        assert!(is_missing_object(&target, StatusCode::NOT_FOUND, ""));
+        assert!(!is_missing_object(&target, StatusCode::BAD_REQUEST, "Object not found"));
+        assert!(!is_missing_object(&target, StatusCode::FORBIDDEN, "<Code>AccessDenied</Code>"));
+    }
+
+    fn summary(version: &str) -> InstanceSummary {
+        InstanceSummary {
+            id: "evento".to_string(),
+            name: "Evento".to_string(),
+            description: String::new(),
+            version: version.to_string(),
+            minecraft_version: "1.20.1".to_string(),
+            icon: None,
+            background: None,
+            background_video: None,
+            last_updated: None,
+            instance_url: "instances/evento/instance.json".to_string(),
+            mod_loader: None,
+            server: None,
+            available_from: None,
+            available_until: None,
+        }
+    }
+
+    #[test]
+    fn republishing_keeps_the_remote_schedule() {
+        let mut remote = InstanceSummary {
+            background_video: Some("trailer.mp4".to_string()),
+            available_from: Some("2026-07-01T18:00:00Z".to_string()),
+            available_until: Some("2026-07-03T18:00:00Z".to_string()),
+            ..summary("1.0.0")
+        };
+        merge_published_summary(&mut remote, summary("1.1.0"));
+        assert_eq!(remote.version, "1.1.0");
+        assert_eq!(remote.background_video.as_deref(), Some("trailer.mp4"));
+        assert_eq!(remote.available_from.as_deref(), Some("2026-07-01T18:00:00Z"));
+        assert_eq!(remote.available_until.as_deref(), Some("2026-07-03T18:00:00Z"));
+
+        // Lo que fija el build local explícitamente tiene prioridad
+        let local = InstanceSummary { available_until: Some("2026-07-05T18:00:00Z".to_string()), ..summary("1.2.0") };
+        merge_published_summary(&mut remote, local);
+        assert_eq!(remote.available_from.as_deref(), Some("2026-07-01T18:00:00Z"));
+        assert_eq!(remote.available_until.as_deref(), Some("2026-07-05T18:00:00Z"));
+    }
+
+    /// Necesita un MinIO con el bucket creado, p. ej.
+    /// `MINIO_ENDPOINT=http://localhost:9000 MINIO_BUCKET=launcher MINIO_ACCESS_KEY=minioadmin MINIO_SECRET_KEY=minioadmin cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires a MinIO server (MINIO_ENDPOINT, MINIO_BUCKET, MINIO_ACCESS_KEY, MINIO_SECRET_KEY)"]
+    async fn minio_round_trip_and_errors() {
+        let var = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+        let (endpoint, bucket) = (var("MINIO_ENDPOINT"), var("MINIO_BUCKET"));
+        let (access_key, secret_key) = (var("MINIO_ACCESS_KEY"), var("MINIO_SECRET_KEY"));
+
+        let publisher = Publisher::new(s3_target(&endpoint, &bucket, &access_key, &secret_key)).unwrap();
+        let key = format!("publish-test-{}/manifest name.json", chrono::Utc::now().timestamp_millis());
+
+        assert_eq!(publisher.get_object(&key).await.unwrap(), None);
+        publisher.put_object(&key, b"{\"instances\":[]}".to_vec()).await.unwrap();
+        assert_eq!(publisher.get_object(&key).await.unwrap().as_deref(), Some(&b"{\"instances\":[]}"[..]));
+
+        // Credenciales erróneas: debe ser un error, nunca "no existe"
+        let wrong = Publisher::new(s3_target(&endpoint, &bucket, &access_key, "wrong-secret")).unwrap();
+        assert!(wrong.get_object(&key).await.is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::models::{DistributionManifest, InstanceAvailability, ServerTimeOffset};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Prefijo común de los errores de disponibilidad para que el frontend pueda detectarlos
+pub const INSTANCE_NOT_AVAILABLE: &str = "INSTANCE_NOT_AVAILABLE";
+
+#[derive(Debug, Clone, Default)]
+struct InstanceSchedule {
+    available_from: Option<String>,
+    available_until: Option<String>,
+}
+
+/// Ventanas de disponibilidad del último manifest de distribución cargado
+static SCHEDULES: Lazy<Arc<Mutex<HashMap<String, InstanceSchedule>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+
+/// Diferencia hora del servidor - hora local en milisegundos
+static SERVER_TIME_OFFSET_MS: Lazy<Arc<Mutex<i64>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
+
+/// Guarda las ventanas de disponibilidad de un manifest recién cargado. También se actualizan en el
+/// historial de las instancias ya sincronizadas, que es lo que se usa tras reiniciar el launcher
+pub fn record_distribution_schedule(manifest: &DistributionManifest) {
+    if let Ok(mut schedules) = SCHEDULES.lock() {
+        schedules.clear();
+        for instance in &manifest.instances {
+            schedules.insert(instance.id.clone(), InstanceSchedule {
+                available_from: instance.available_from.clone(),
+                available_until: instance.available_until.clone(),
+            });
+        }
+    }
+    for instance in &manifest.instances {
+        persist_schedule(&instance.id, &instance.available_from, &instance.available_until);
+    }
+}
+
+fn persist_schedule(instance_id: &str, available_from: &Option<String>, available_until: &Option<String>) {
+    let instance_dir = crate::launcher::get_instance_directory(instance_id);
+    let Ok(Some(mut history)) = crate::instances::load_manifest_history(&instance_dir) else { return };
+    if &history.available_from == available_from && &history.available_until == available_until {
+        return;
+    }
+    history.available_from = available_from.clone();
+    history.available_until = available_until.clone();
+    let result = serde_json::to_string_pretty(&history)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(instance_dir.join(".manifest_history.json"), json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::warn!("Failed to persist availability window of {}: {}", instance_id, e);
+    }
+}
+
+/// Ventana del manifest de distribución cargado en esta sesión, si incluye la instancia
+pub fn recorded_schedule(instance_id: &str) -> Option<(Option<String>, Option<String>)> {
+    let schedules = SCHEDULES.lock().ok()?;
+    schedules.get(instance_id).map(|s| (s.available_from.clone(), s.available_until.clone()))
+}
+
+/// Hora actual corregida con la última diferencia medida contra el servidor
+pub fn server_now() -> DateTime<Utc> {
+    let offset = SERVER_TIME_OFFSET_MS.lock().map(|o| *o).unwrap_or(0);
+    Utc::now() + chrono::Duration::milliseconds(offset)
+}
+
+/// `Err` si hay fecha pero no se puede interpretar
+fn parse_time(value: &Option<String>) -> Result<Option<DateTime<Utc>>, ()> {
+    let Some(value) = value.as_ref() else { return Ok(None) };
+    match DateTime::parse_from_rfc3339(value) {
+        Ok(dt) => Ok(Some(dt.with_timezone(&Utc))),
+        Err(e) => {
+            log::warn!("Invalid availability date '{}': {}", value, e);
+            Err(())
+        }
+    }
+}
+
+/// Una ventana con fechas inválidas se considera cerrada: es preferible bloquear el evento a abrirlo antes de tiempo
+fn compute_availability(instance_id: &str, schedule: &InstanceSchedule, now: DateTime<Utc>) -> InstanceAvailability {
+    let (status, seconds_until_open, seconds_until_close) =
+        match (parse_time(&schedule.available_from), parse_time(&schedule.available_until)) {
+            (Err(()), _) | (_, Err(())) => ("closed", None, None),
+            (Ok(Some(from)), _) if now < from => ("upcoming", Some((from - now).num_seconds()), None),
+            (_, Ok(Some(until))) if now >= until => ("closed", None, None),
+            (_, Ok(until)) => ("open", None, until.map(|u| (u - now).num_seconds())),
+        };
+
+    InstanceAvailability {
+        instance_id: instance_id.to_string(),
+        status: status.to_string(),
+        available_from: schedule.available_from.clone(),
+        available_until: schedule.available_until.clone(),
+        seconds_until_open,
+        seconds_until_close,
+    }
+}
+
+/// Si el manifest de distribución no se ha cargado se usa la ventana guardada al sincronizar.
+/// Las instancias sin ventana conocida (locales o no listadas) siempre están abiertas
+pub fn instance_availability(instance_id: &str) -> InstanceAvailability {
+    let recorded = SCHEDULES.lock().ok().and_then(|s| s.get(instance_id).cloned());
+    let schedule = recorded
+        .or_else(|| {
+            let instance_dir = crate::launcher::get_instance_directory(instance_id);
+            crate::instances::load_manifest_history(&instance_dir).ok().flatten().map(|h| InstanceSchedule {
+                available_from: h.available_from,
+                available_until: h.available_until,
+            })
+        })
+        .unwrap_or_default();
+    compute_availability(instance_id, &schedule, server_now())
+}
+
+async fn active_username(app_handle: &tauri::AppHandle) -> Option<String> {
+    let session_manager = crate::sessions::SessionManager::new(app_handle).ok()?;
+    session_manager.get_active_session().ok().flatten().map(|s| s.username)
+}
+
+/// Rechaza el lanzamiento de una instancia fuera de su ventana. Los admins (o quien tenga
+/// `instances.schedule_bypass`) pueden lanzarla igualmente para preparar el evento
+pub async fn ensure_instance_launchable(app_handle: &tauri::AppHandle, instance_id: &str) -> Result<(), String> {
+    let availability = instance_availability(instance_id);
+    if availability.status == "open" {
+        return Ok(());
+    }
+
+    if let Some(username) = active_username(app_handle).await {
+        match crate::admins::resolve_user_permissions(&username).await {
+            Ok(perms) if perms.is_admin() || perms.has(crate::admins::permissions::INSTANCES_SCHEDULE_BYPASS) => {
+                log::info!("Launching {} outside its availability window (staff bypass: {})", instance_id, username);
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Could not check schedule bypass for {}: {}", username, e),
+        }
+    }
+
+    let reason = match availability.status.as_str() {
+        "upcoming" => format!("opens at {}", availability.available_from.unwrap_or_default()),
+        _ => format!("closed at {}", availability.available_until.unwrap_or_default()),
+    };
+    log::warn!("Refusing to launch {}: {}", instance_id, reason);
+    Err(format!("{}: instance '{}' {}", INSTANCE_NOT_AVAILABLE, instance_id, reason))
+}
+
+/// Mide la diferencia con la hora del servidor usando la cabecera `Date` de una petición HEAD.
+/// Sin `url` se usa el servidor de Supabase
+#[tauri::command]
+pub async fn get_server_time_offset(url: Option<String>) -> Result<ServerTimeOffset, String> {
+    let url = match url {
+        Some(u) => u,
+        None => crate::whitelist::get_supabase_config().0,
+    };
+
+    let sent_at = Utc::now();
+    let response = crate::http_client::HTTP_CLIENT
+        .head(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach time server: {}", e))?;
+    let received_at = Utc::now();
+
+    let date_header = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("Server response has no Date header")?;
+    let server_time = DateTime::parse_from_rfc2822(date_header)
+        .map_err(|e| format!("Invalid Date header '{}': {}", date_header, e))?
+        .with_timezone(&Utc);
+
+    // La cabecera Date corresponde aproximadamente a la mitad del viaje de ida y vuelta
+    let round_trip = received_at - sent_at;
+    let local_midpoint = sent_at + round_trip / 2;
+    let offset_ms = (server_time - local_midpoint).num_milliseconds();
+
+    if let Ok(mut offset) = SERVER_TIME_OFFSET_MS.lock() {
+        *offset = offset_ms;
+    }
+    log::info!("Server time offset: {} ms (round trip {} ms)", offset_ms, round_trip.num_milliseconds());
+
+    Ok(ServerTimeOffset {
+        offset_ms,
+        server_time: server_time.to_rfc3339(),
+        local_time: local_midpoint.to_rfc3339(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_instance_availability(instance_id: String) -> Result<InstanceAvailability, String> {
+    Ok(instance_availability(&instance_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(from: Option<&str>, until: Option<&str>) -> InstanceSchedule {
+        InstanceSchedule { available_from: from.map(str::to_string), available_until: until.map(str::to_string) }
+    }
+
+    #[test]
+    fn windows_are_evaluated_against_now() {
+        let now = DateTime::parse_from_rfc3339("2026-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let status = |s: InstanceSchedule| compute_availability("event", &s, now).status;
+        assert_eq!(status(schedule(None, None)), "open");
+        assert_eq!(status(schedule(Some("2026-05-01T13:00:00Z"), None)), "upcoming");
+        assert_eq!(status(schedule(Some("2026-05-01T11:00:00Z"), Some("2026-05-01T12:00:00Z"))), "closed");
+        assert_eq!(status(schedule(Some("2026-05-01T11:00:00+00:00"), Some("2026-05-02T00:00:00+02:00"))), "open");
+    }
+
+    #[test]
+    fn unparseable_window_is_closed() {
+        let now = Utc::now();
+        assert_eq!(compute_availability("event", &schedule(Some("mañana"), None), now).status, "closed");
+        assert_eq!(compute_availability("event", &schedule(None, Some("2026-13-01")), now).status, "closed");
+    }
+}
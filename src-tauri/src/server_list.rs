@@ -0,0 +1,227 @@
+use crate::models::{InstanceInfo, ManifestServer, ServerListEntry};
+use crate::nbt::Tag;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Lista multijugador del juego: NBT sin comprimir en la raíz de la instancia
+const SERVERS_FILE: &str = "servers.dat";
+
+const ICON_PREFIX: &str = "data:image/png;base64,";
+
+/// Servidores que el manifest quiere en la lista. El `server` de Quick Play también se añade
+/// si no aparece ya en `servers`
+pub fn manifest_servers(info: &InstanceInfo) -> Vec<ManifestServer> {
+    let mut servers = info.servers.clone();
+    if let Some(address) = info.server.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+        if !servers.iter().any(|s| s.ip.eq_ignore_ascii_case(address)) {
+            servers.insert(0, ManifestServer { name: info.name.clone(), ip: address.to_string(), icon: None, accept_textures: None });
+        }
+    }
+    servers
+}
+
+/// Lee las entradas tal cual, conservando claves que el launcher no conoce
+fn read_entries(path: &Path) -> Result<(String, Tag, Vec<Tag>), String> {
+    if !path.exists() {
+        return Ok((String::new(), Tag::Compound(BTreeMap::new()), Vec::new()));
+    }
+    let (name, root) = crate::nbt::read_file(path)?;
+    let entries = root.get("servers").and_then(|t| t.as_list()).map(|l| l.to_vec()).unwrap_or_default();
+    Ok((name, root, entries))
+}
+
+fn write_entries(path: &Path, name: &str, mut root: Tag, entries: Vec<Tag>) -> Result<(), String> {
+    let map = root.as_compound_mut().ok_or("servers.dat root is not a compound")?;
+    map.insert("servers".to_string(), Tag::List(entries));
+    // El juego lee servers.dat sin comprimir
+    crate::nbt::write_file(path, name, &root, false)
+}
+
+fn entry_ip(entry: &Tag) -> Option<&str> {
+    entry.get("ip").and_then(|t| t.as_str())
+}
+
+fn set_fields(entry: &mut BTreeMap<String, Tag>, name: &str, ip: &str, icon: Option<&str>, accept_textures: Option<bool>) {
+    entry.insert("name".to_string(), Tag::String(name.to_string()));
+    entry.insert("ip".to_string(), Tag::String(ip.to_string()));
+    if let Some(icon) = icon {
+        let icon = icon.strip_prefix(ICON_PREFIX).unwrap_or(icon);
+        entry.insert("icon".to_string(), Tag::String(icon.to_string()));
+    }
+    if let Some(accept) = accept_textures {
+        entry.insert("acceptTextures".to_string(), Tag::Byte(accept as i8));
+    }
+}
+
+/// Añade o actualiza los servidores del manifest y quita los que el launcher añadió antes y ya
+/// no están. Las entradas del jugador (IPs que nunca gestionó el launcher) no se tocan.
+/// Devuelve las IPs que quedan gestionadas en `servers.dat`
+pub fn apply_manifest_servers(instance_dir: &Path, info: &InstanceInfo, previously_managed: &[String]) -> Result<Vec<String>, String> {
+    let servers = manifest_servers(info);
+    let managed: Vec<String> = servers.iter().map(|s| s.ip.clone()).collect();
+    let path = instance_dir.join(SERVERS_FILE);
+    if servers.is_empty() && previously_managed.is_empty() {
+        return Ok(managed);
+    }
+    let (name, root, mut entries) = read_entries(&path)?;
+    let original = entries.clone();
+
+    entries.retain(|entry| {
+        let Some(ip) = entry_ip(entry) else { return true };
+        let was_managed = previously_managed.iter().any(|m| m.eq_ignore_ascii_case(ip));
+        !was_managed || servers.iter().any(|s| s.ip.eq_ignore_ascii_case(ip))
+    });
+
+    // Los servidores nuevos van arriba, en el orden del manifest
+    let mut inserted = 0;
+    for server in &servers {
+        let existing = entries.iter_mut().find(|e| entry_ip(e).is_some_and(|ip| ip.eq_ignore_ascii_case(&server.ip)));
+        match existing.and_then(|e| e.as_compound_mut()) {
+            Some(entry) => set_fields(entry, &server.name, &server.ip, server.icon.as_deref(), server.accept_textures),
+            None => {
+                let mut entry = BTreeMap::new();
+                set_fields(&mut entry, &server.name, &server.ip, server.icon.as_deref(), server.accept_textures);
+                entries.insert(inserted, Tag::Compound(entry));
+                inserted += 1;
+            }
+        }
+    }
+
+    if entries == original {
+        return Ok(managed);
+    }
+    write_entries(&path, &name, root, entries)?;
+    log::info!("Updated {} with {} managed servers", path.display(), servers.len());
+    Ok(managed)
+}
+
+fn managed_ips(instance_dir: &Path) -> Vec<String> {
+    crate::instances::load_manifest_history(instance_dir)
+        .ok()
+        .flatten()
+        .map(|h| h.managed_servers)
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_instance_servers(instance_id: String) -> Result<Vec<ServerListEntry>, String> {
+    let instance_dir = crate::local_instances::get_instance_directory_smart(&instance_id);
+    let (_, _, entries) = read_entries(&instance_dir.join(SERVERS_FILE))?;
+    let managed = managed_ips(&instance_dir);
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let ip = entry_ip(entry)?.to_string();
+            Some(ServerListEntry {
+                name: entry.get("name").and_then(|t| t.as_str()).unwrap_or_default().to_string(),
+                icon: entry.get("icon").and_then(|t| t.as_str()).map(|icon| format!("{}{}", ICON_PREFIX, icon)),
+                accept_textures: entry.get("acceptTextures").and_then(|t| t.as_bool()),
+                hidden: entry.get("hidden").and_then(|t| t.as_bool()).unwrap_or(false),
+                managed: managed.iter().any(|m| m.eq_ignore_ascii_case(&ip)),
+                ip,
+            })
+        })
+        .collect())
+}
+
+/// Reescribe la lista en el orden recibido. Las entradas que ya existían (misma IP) conservan el
+/// resto de sus datos
+#[tauri::command]
+pub async fn save_instance_servers(instance_id: String, servers: Vec<ServerListEntry>) -> Result<(), String> {
+    let instance_dir = crate::local_instances::get_instance_directory_smart(&instance_id);
+    if !instance_dir.exists() {
+        return Err(format!("Instance {} not found", instance_id));
+    }
+    let path = instance_dir.join(SERVERS_FILE);
+    let (name, root, existing) = read_entries(&path)?;
+
+    let mut entries = Vec::with_capacity(servers.len());
+    for server in &servers {
+        let ip = server.ip.trim();
+        if ip.is_empty() {
+            return Err(format!("Server {} has no address", server.name));
+        }
+        let mut entry = existing
+            .iter()
+            .find(|e| entry_ip(e).is_some_and(|existing_ip| existing_ip.eq_ignore_ascii_case(ip)))
+            .and_then(|e| e.as_compound().cloned())
+            .unwrap_or_default();
+        if server.icon.is_none() {
+            entry.remove("icon");
+        }
+        if server.accept_textures.is_none() {
+            entry.remove("acceptTextures");
+        }
+        set_fields(&mut entry, &server.name, ip, server.icon.as_deref(), server.accept_textures);
+        if server.hidden {
+            entry.insert("hidden".to_string(), Tag::Byte(1));
+        } else {
+            entry.remove("hidden");
+        }
+        entries.push(Tag::Compound(entry));
+    }
+
+    write_entries(&path, &name, root, entries)?;
+    log::info!("Saved {} servers to {}", servers.len(), path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(server: Option<&str>, servers: serde_json::Value) -> InstanceInfo {
+        serde_json::from_value(serde_json::json!({
+            "id": "event",
+            "name": "Evento",
+            "description": "",
+            "version": "1.0.0",
+            "minecraft_version": "1.20.1",
+            "mod_loader": null,
+            "icon": null,
+            "background": null,
+            "server": server,
+            "servers": servers,
+        }))
+        .unwrap()
+    }
+
+    fn instance_with_fixture(name: &str) -> std::path::PathBuf {
+        let dir = crate::test_fixtures::temp_dir(name);
+        std::fs::copy(Path::new(crate::test_fixtures::FIXTURES).join("nbt").join(SERVERS_FILE), dir.join(SERVERS_FILE)).unwrap();
+        dir
+    }
+
+    fn ips(dir: &Path) -> Vec<String> {
+        let (_, _, entries) = read_entries(&dir.join(SERVERS_FILE)).unwrap();
+        entries.iter().filter_map(|e| entry_ip(e).map(|s| s.to_string())).collect()
+    }
+
+    #[test]
+    fn manifest_servers_replace_only_what_the_launcher_managed() {
+        let dir = instance_with_fixture("server-list-apply");
+        let info = info(Some("event.kindlyklan.net"), serde_json::json!([{ "name": "Lobby", "ip": "mc.hypixel.net" }]));
+
+        // play.kindlyklan.net lo puso el launcher en una sincronización anterior; el resto son del jugador
+        let managed = apply_manifest_servers(&dir, &info, &["play.kindlyklan.net".to_string()]).unwrap();
+
+        assert_eq!(managed, ["event.kindlyklan.net", "mc.hypixel.net"]);
+        assert_eq!(ips(&dir), ["event.kindlyklan.net", "mc.hypixel.net", "192.168.1.20:25566"]);
+        let (_, root) = crate::nbt::read_file(&dir.join(SERVERS_FILE)).unwrap();
+        let servers = root.get("servers").and_then(|t| t.as_list()).unwrap();
+        assert_eq!(servers[1].get("name").and_then(|t| t.as_str()), Some("Lobby"));
+        // Lo que el launcher no conoce se conserva
+        assert_eq!(servers[2].get("hidden").and_then(|t| t.as_bool()), Some(true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unreadable_servers_dat_is_an_error() {
+        let dir = crate::test_fixtures::temp_dir("server-list-corrupt");
+        std::fs::write(dir.join(SERVERS_FILE), b"not nbt").unwrap();
+        let info = info(Some("event.kindlyklan.net"), serde_json::json!([]));
+        assert!(apply_manifest_servers(&dir, &info, &[]).is_err());
+        assert_eq!(std::fs::read(dir.join(SERVERS_FILE)).unwrap(), b"not nbt");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
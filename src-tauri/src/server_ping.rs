@@ -0,0 +1,428 @@
+use crate::models::{InstanceServerStatus, ServerStatus};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Límite de una respuesta de estado; el favicon en base64 es lo que más ocupa
+const MAX_PACKET_LEN: usize = 2 * 1024 * 1024;
+
+/// Versión de protocolo del handshake. Para pedir el estado vale cualquiera; -1 es la convención
+const STATUS_PROTOCOL: i32 = -1;
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    write_varint(buf, value.len() as i32);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Antepone la longitud del paquete
+fn frame(packet: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(packet.len() + 5);
+    write_varint(&mut framed, packet.len() as i32);
+    framed.extend_from_slice(packet);
+    framed
+}
+
+async fn read_varint(stream: &mut TcpStream) -> Result<i32, String> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = stream.read_u8().await.map_err(|e| format!("Failed to read from server: {}", e))?;
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err("VarInt too long".to_string())
+}
+
+fn read_varint_from(data: &[u8], pos: &mut usize) -> Result<i32, String> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let byte = *data.get(*pos).ok_or("Truncated packet")?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err("VarInt too long".to_string())
+}
+
+/// Lee un paquete y devuelve su id y el resto del contenido
+async fn read_packet(stream: &mut TcpStream) -> Result<(i32, Vec<u8>), String> {
+    let len = read_varint(stream).await?;
+    if len <= 0 || len as usize > MAX_PACKET_LEN {
+        return Err(format!("Invalid packet length: {}", len));
+    }
+    let mut data = vec![0u8; len as usize];
+    stream.read_exact(&mut data).await.map_err(|e| format!("Failed to read from server: {}", e))?;
+    let mut pos = 0;
+    let id = read_varint_from(&data, &mut pos)?;
+    Ok((id, data.split_off(pos)))
+}
+
+/// Texto plano de un componente de chat (la descripción puede ser texto o un objeto con `extra`)
+fn chat_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(chat_to_text).collect(),
+        Value::Object(obj) => {
+            let mut text = obj.get("text").map(chat_to_text).unwrap_or_default();
+            if let Some(extra) = obj.get("extra") {
+                text.push_str(&chat_to_text(extra));
+            }
+            text
+        }
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// `_minecraft._tcp.<host>`; sin registro se usa el host tal cual
+async fn resolve_srv(host: &str) -> Option<(String, u16)> {
+    let resolver = hickory_resolver::TokioAsyncResolver::tokio_from_system_conf().ok()?;
+    let lookup = resolver.srv_lookup(format!("_minecraft._tcp.{}", host)).await.ok()?;
+    let record = lookup.iter().min_by_key(|srv| (srv.priority(), std::cmp::Reverse(srv.weight())))?;
+    let target = record.target().to_utf8().trim_end_matches('.').to_string();
+    Some((target, record.port()))
+}
+
+async fn connect(host: &str, port: u16) -> Result<TcpStream, String> {
+    let stream = tokio::time::timeout(TIMEOUT, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}:{}", host, port))?
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+    let _ = stream.set_nodelay(true);
+    Ok(stream)
+}
+
+/// Handshake, petición de estado y ping/pong (1.7+)
+async fn modern_status(address: &str, handshake_host: &str, host: &str, port: u16) -> Result<ServerStatus, String> {
+    let mut stream = connect(host, port).await?;
+
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, 0x00);
+    write_varint(&mut handshake, STATUS_PROTOCOL);
+    write_string(&mut handshake, handshake_host);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+    let mut request = frame(&handshake);
+    request.extend_from_slice(&frame(&[0x00]));
+
+    let started = Instant::now();
+    stream.write_all(&request).await.map_err(|e| format!("Failed to send status request: {}", e))?;
+    let (id, body) = read_packet(&mut stream).await?;
+    let status_latency = started.elapsed();
+    if id != 0x00 {
+        return Err(format!("Unexpected status packet id: {}", id));
+    }
+    let mut pos = 0;
+    let json_len = read_varint_from(&body, &mut pos)? as usize;
+    let json_bytes = body.get(pos..pos + json_len).ok_or("Truncated status response")?;
+    let json: Value = serde_json::from_slice(json_bytes).map_err(|e| format!("Invalid status JSON: {}", e))?;
+
+    // Si el servidor no contesta al ping, vale la latencia de la petición de estado
+    let mut ping = vec![0x01];
+    let payload = chrono::Utc::now().timestamp_millis();
+    ping.extend_from_slice(&payload.to_be_bytes());
+    let ping_started = Instant::now();
+    let latency = match stream.write_all(&frame(&ping)).await {
+        Ok(()) => match read_packet(&mut stream).await {
+            Ok((0x01, pong)) if pong == payload.to_be_bytes() => ping_started.elapsed(),
+            _ => status_latency,
+        },
+        Err(_) => status_latency,
+    };
+
+    let players = json.get("players");
+    Ok(ServerStatus {
+        address: address.to_string(),
+        host: host.to_string(),
+        port,
+        motd: json.get("description").map(chat_to_text).unwrap_or_default(),
+        version_name: json.pointer("/version/name").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        protocol: json.pointer("/version/protocol").and_then(|v| v.as_i64()).map(|p| p as i32),
+        players_online: players.and_then(|p| p.get("online")).and_then(|v| v.as_i64()).unwrap_or(0),
+        players_max: players.and_then(|p| p.get("max")).and_then(|v| v.as_i64()).unwrap_or(0),
+        player_sample: players
+            .and_then(|p| p.get("sample"))
+            .and_then(|s| s.as_array())
+            .map(|sample| sample.iter().filter_map(|p| p.get("name").and_then(|n| n.as_str()).map(|s| s.to_string())).collect())
+            .unwrap_or_default(),
+        favicon: json.get("favicon").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        latency_ms: latency.as_millis() as u64,
+        legacy: false,
+    })
+}
+
+/// Ping anterior a 1.7: `FE 01` y una respuesta `FF` con texto UTF-16BE
+async fn legacy_status(address: &str, host: &str, port: u16) -> Result<ServerStatus, String> {
+    let mut stream = connect(host, port).await?;
+    let started = Instant::now();
+    stream.write_all(&[0xFE, 0x01]).await.map_err(|e| format!("Failed to send legacy ping: {}", e))?;
+
+    let kick = stream.read_u8().await.map_err(|e| format!("Failed to read legacy response: {}", e))?;
+    if kick != 0xFF {
+        return Err(format!("Unexpected legacy response: 0x{:02X}", kick));
+    }
+    let chars = stream.read_u16().await.map_err(|e| format!("Failed to read legacy response: {}", e))? as usize;
+    let mut raw = vec![0u8; chars * 2];
+    stream.read_exact(&mut raw).await.map_err(|e| format!("Failed to read legacy response: {}", e))?;
+    let latency = started.elapsed();
+    let units: Vec<u16> = raw.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+    let text = String::from_utf16_lossy(&units);
+
+    // 1.4-1.6: "§1\0protocolo\0versión\0motd\0online\0max"; beta: "motd§online§max"
+    let (motd, version_name, protocol, online, max) = if let Some(rest) = text.strip_prefix("§1\0") {
+        let fields: Vec<&str> = rest.split('\0').collect();
+        (
+            fields.get(2).copied().unwrap_or_default().to_string(),
+            fields.get(1).map(|s| s.to_string()),
+            fields.first().and_then(|p| p.parse().ok()),
+            fields.get(3).and_then(|v| v.parse().ok()).unwrap_or(0),
+            fields.get(4).and_then(|v| v.parse().ok()).unwrap_or(0),
+        )
+    } else {
+        let mut fields = text.rsplitn(3, '§');
+        let max = fields.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+        let online = fields.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+        (fields.next().unwrap_or_default().to_string(), None, None, online, max)
+    };
+
+    Ok(ServerStatus {
+        address: address.to_string(),
+        host: host.to_string(),
+        port,
+        motd,
+        version_name,
+        protocol,
+        players_online: online,
+        players_max: max,
+        player_sample: Vec::new(),
+        favicon: None,
+        latency_ms: latency.as_millis() as u64,
+        legacy: true,
+    })
+}
+
+/// Estado de un servidor a partir de su dirección tal como la escribiría un jugador
+pub async fn ping(address: &str) -> Result<ServerStatus, String> {
+    let (host, explicit_port) = crate::launch_plan::split_server_address(address);
+    if host.is_empty() {
+        return Err("Empty server address".to_string());
+    }
+    // El SRV solo aplica a nombres sin puerto, igual que en el cliente
+    let (target_host, port) = match explicit_port {
+        Some(port) => (host.clone(), port),
+        None if host.parse::<std::net::IpAddr>().is_err() => resolve_srv(&host)
+            .await
+            .unwrap_or_else(|| (host.clone(), crate::launch_plan::DEFAULT_SERVER_PORT)),
+        None => (host.clone(), crate::launch_plan::DEFAULT_SERVER_PORT),
+    };
+
+    let modern = tokio::time::timeout(TIMEOUT, modern_status(address, &host, &target_host, port))
+        .await
+        .unwrap_or_else(|_| Err(format!("Timed out waiting for {}:{}", target_host, port)));
+    match modern {
+        Ok(status) => Ok(status),
+        Err(e) => {
+            log::debug!("Status ping to {} failed ({}), trying legacy ping", address, e);
+            match tokio::time::timeout(TIMEOUT, legacy_status(address, &target_host, port)).await {
+                Ok(Ok(status)) => Ok(status),
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn ping_server(address: String) -> Result<ServerStatus, String> {
+    ping(&address).await
+}
+
+/// Estado de los servidores que declaran las instancias de una distribución
+#[tauri::command]
+pub async fn ping_distribution_servers(url: String) -> Result<Vec<InstanceServerStatus>, String> {
+    let manifest = crate::commands::load_distribution_manifest(url).await?;
+    let pings = manifest.instances.into_iter().filter_map(|instance| {
+        let address = instance.server?;
+        let instance_id = instance.id;
+        Some(async move {
+            let result = ping(&address).await;
+            if let Err(e) = &result {
+                log::info!("Server {} of {} is not reachable: {}", address, instance_id, e);
+            }
+            let (status, error) = match result {
+                Ok(status) => (Some(status), None),
+                Err(e) => (None, Some(e)),
+            };
+            InstanceServerStatus { instance_id, address, status, error }
+        })
+    });
+    Ok(futures_util::future::join_all(pings).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Servidor falso en un puerto libre; `handler` atiende cada conexión
+    async fn fake_server<F, Fut>(handler: F) -> u16
+    where
+        F: Fn(TcpStream) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handler(stream));
+            }
+        });
+        port
+    }
+
+    fn status_json() -> Value {
+        serde_json::json!({
+            "version": { "name": "1.20.1", "protocol": 763 },
+            "players": { "max": 100, "online": 2, "sample": [
+                { "name": "Steve", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" },
+                { "name": "Alex", "id": "61699b2e-d327-4a01-9f1e-0ea8c3f06bc6" }
+            ] },
+            "description": { "text": "Kindly ", "extra": [{ "text": "Klan", "bold": true }] },
+            // Más de 127 bytes para que las longitudes ocupen varios bytes de VarInt
+            "favicon": format!("data:image/png;base64,{}", "A".repeat(300)),
+        })
+    }
+
+    /// Lee el handshake y la petición de estado y contesta con `status_json`. Devuelve el host y
+    /// puerto del handshake
+    async fn answer_status(stream: &mut TcpStream) -> (String, u16) {
+        let (id, handshake) = read_packet(stream).await.unwrap();
+        assert_eq!(id, 0x00);
+        let mut pos = 0;
+        assert_eq!(read_varint_from(&handshake, &mut pos).unwrap(), STATUS_PROTOCOL);
+        let host_len = read_varint_from(&handshake, &mut pos).unwrap() as usize;
+        let host = String::from_utf8(handshake[pos..pos + host_len].to_vec()).unwrap();
+        pos += host_len;
+        let port = u16::from_be_bytes([handshake[pos], handshake[pos + 1]]);
+        pos += 2;
+        assert_eq!(read_varint_from(&handshake, &mut pos).unwrap(), 1, "next state must be status");
+
+        assert_eq!(read_packet(stream).await.unwrap(), (0x00, Vec::new()));
+        let mut response = Vec::new();
+        write_varint(&mut response, 0x00);
+        write_string(&mut response, &status_json().to_string());
+        stream.write_all(&frame(&response)).await.unwrap();
+        (host, port)
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            let mut pos = 0;
+            assert_eq!(read_varint_from(&buf, &mut pos).unwrap(), value);
+            assert_eq!(pos, buf.len());
+        }
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 25565);
+        assert_eq!(buf, [0xDD, 0xC7, 0x01]);
+        assert_eq!(read_varint_from(&[0x80, 0x80], &mut 0).unwrap_err(), "Truncated packet");
+        assert_eq!(read_varint_from(&[0xFF; 6], &mut 0).unwrap_err(), "VarInt too long");
+    }
+
+    #[tokio::test]
+    async fn modern_status_with_ping_pong() {
+        let (handshake_tx, mut handshake_rx) = tokio::sync::mpsc::unbounded_channel();
+        let port = fake_server(move |mut stream| {
+            let handshake_tx = handshake_tx.clone();
+            async move {
+                let handshake = answer_status(&mut stream).await;
+                let _ = handshake_tx.send(handshake);
+                let (id, payload) = read_packet(&mut stream).await.unwrap();
+                assert_eq!(id, 0x01);
+                assert_eq!(payload.len(), 8);
+                let mut pong = vec![0x01];
+                pong.extend_from_slice(&payload);
+                stream.write_all(&frame(&pong)).await.unwrap();
+            }
+        })
+        .await;
+
+        let address = format!("127.0.0.1:{}", port);
+        let status = ping(&address).await.unwrap();
+
+        assert_eq!(handshake_rx.recv().await.unwrap(), ("127.0.0.1".to_string(), port));
+        assert_eq!(status.address, address);
+        assert_eq!(status.motd, "Kindly Klan");
+        assert_eq!(status.version_name.as_deref(), Some("1.20.1"));
+        assert_eq!(status.protocol, Some(763));
+        assert_eq!((status.players_online, status.players_max), (2, 100));
+        assert_eq!(status.player_sample, ["Steve", "Alex"]);
+        assert_eq!(status.favicon.map(|f| f.len()), Some(322));
+        assert!(!status.legacy);
+    }
+
+    #[tokio::test]
+    async fn status_without_pong_keeps_the_status_latency() {
+        let port = fake_server(|mut stream| async move {
+            answer_status(&mut stream).await;
+            // Cierra sin contestar al ping
+        })
+        .await;
+
+        let status = ping(&format!("127.0.0.1:{}", port)).await.unwrap();
+        assert_eq!(status.motd, "Kindly Klan");
+        assert!(!status.legacy);
+    }
+
+    #[tokio::test]
+    async fn pre_1_7_servers_answer_the_legacy_ping() {
+        let port = fake_server(|mut stream| async move {
+            let mut first = [0u8; 2];
+            if stream.read_exact(&mut first).await.is_err() || first != [0xFE, 0x01] {
+                // Un servidor antiguo no entiende el handshake moderno y corta
+                return;
+            }
+            let text = "§1\0127\01.6.4\0A legacy server\03\020";
+            let units: Vec<u16> = text.encode_utf16().collect();
+            let mut reply = vec![0xFF];
+            reply.extend_from_slice(&(units.len() as u16).to_be_bytes());
+            reply.extend(units.iter().flat_map(|u| u.to_be_bytes()));
+            stream.write_all(&reply).await.unwrap();
+        })
+        .await;
+
+        let status = ping(&format!("127.0.0.1:{}", port)).await.unwrap();
+        assert!(status.legacy);
+        assert_eq!(status.motd, "A legacy server");
+        assert_eq!(status.version_name.as_deref(), Some("1.6.4"));
+        assert_eq!(status.protocol, Some(127));
+        assert_eq!((status.players_online, status.players_max), (3, 20));
+    }
+
+    #[tokio::test]
+    async fn unreachable_servers_fail() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        assert!(ping(&format!("127.0.0.1:{}", port)).await.is_err());
+    }
+}
@@ -51,13 +51,16 @@ pub struct SessionManager {
 
 impl SessionManager {
     pub fn new(app_handle: &AppHandle) -> SqlResult<Self> {
-        let app_dir = app_handle.path().app_data_dir()
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        // La base de datos vive en la carpeta de datos para moverse con ella
+        let data_dir = crate::paths::data_root();
 
-        std::fs::create_dir_all(&app_dir)
+        std::fs::create_dir_all(&data_dir)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
 
-        let db_path = app_dir.join("sessions.db");
+        let db_path = data_dir.join("sessions.db");
+        if !db_path.exists() {
+            Self::migrate_from_app_data_dir(app_handle, &db_path);
+        }
 
         let manager = Self { db_path };
         manager.init_db()?;
@@ -65,6 +68,22 @@ impl SessionManager {
         Ok(manager)
     }
 
+    /// Las versiones anteriores guardaban la base de datos en el app_data_dir de Tauri
+    fn migrate_from_app_data_dir(app_handle: &AppHandle, db_path: &std::path::Path) {
+        let Ok(app_dir) = app_handle.path().app_data_dir() else { return };
+        let legacy_path = app_dir.join("sessions.db");
+        if !legacy_path.exists() {
+            return;
+        }
+        match std::fs::copy(&legacy_path, db_path) {
+            Ok(_) => {
+                info!("Sessions database moved from {} to {}", legacy_path.display(), db_path.display());
+                let _ = std::fs::remove_file(&legacy_path);
+            }
+            Err(e) => warn!("Failed to move sessions database from {}: {}", legacy_path.display(), e),
+        }
+    }
+
     fn init_db(&self) -> SqlResult<()> {
         let conn = Connection::open(&self.db_path)?;
 
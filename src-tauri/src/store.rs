@@ -0,0 +1,564 @@
+use crate::launcher::LauncherConfig;
+use crate::models::{StoreGcReport, StoreMigrationReport};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Marca que indica que las instancias existentes ya se movieron al store compartido
+const MIGRATION_MARKER: &str = ".store_migrated";
+
+/// Archivos que deja un instalador de Forge/NeoForge en `libraries/` sin listarlos en su JSON
+/// (cliente parcheado, mappings...). Se guarda junto al JSON del loader para que el GC no los borre
+const INSTALL_RECEIPT: &str = "installed_files.json";
+
+/// Directorios de la raíz de datos que pertenecen al store y no son instancias
+const STORE_DIRS: &[&str] = &["libraries", "assets", "versions", "local_instances"];
+
+/// El store compartido vive en los directorios de `LauncherConfig`:
+/// `libraries/` (layout maven), `assets/{indexes,objects}` (direccionado por sha1) y
+/// `versions/<id>/<id>.jar`. Cada instancia solo conserva sus JSON de versión
+fn store_config() -> LauncherConfig {
+    LauncherConfig::new().expect("LauncherConfig only computes paths")
+}
+
+pub fn store_root() -> PathBuf {
+    store_config().minecraft_dir
+}
+
+pub fn libraries_dir() -> PathBuf {
+    store_config().libraries_dir
+}
+
+pub fn assets_dir() -> PathBuf {
+    store_config().assets_dir
+}
+
+pub fn version_dir(version_id: &str) -> PathBuf {
+    store_config().versions_dir.join(version_id)
+}
+
+pub fn client_jar_path(mc_version: &str) -> PathBuf {
+    version_dir(mc_version).join(format!("{}.jar", mc_version))
+}
+
+pub fn sha1_file(path: &Path) -> Result<String, String> {
+    use sha1::{Digest, Sha1};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {} for sha1: {}", path.display(), e))?;
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Comprueba el sha1 de un archivo del store. Si no coincide se borra para que se vuelva a descargar
+pub fn verify_store_file(path: &Path, expected_sha1: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected_sha1 else { return Ok(()); };
+    let actual = sha1_file(path)?;
+    if actual.eq_ignore_ascii_case(expected) {
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(path);
+    Err(format!("SHA1 mismatch for {}: expected {}, got {}", path.display(), expected, actual))
+}
+
+/// Descarga un archivo al store y verifica su sha1 (si el JSON de versión lo trae)
+pub async fn download_to_store(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+    expected_sha1: Option<&str>,
+    options: &crate::sync_engine::SyncOptions,
+) -> Result<(), String> {
+    crate::sync_engine::download_with_options(client, url, path, options).await?;
+    verify_store_file(path, expected_sha1)
+}
+
+/// Id exacto de la versión que crea el instalador de cada mod loader
+fn expected_loader_id(loader_type: &str, minecraft_version: &str, loader_version: &str) -> Option<String> {
+    match loader_type {
+        "fabric" => Some(format!("fabric-loader-{}-{}", loader_version, minecraft_version)),
+        // Forge usa "1.20.1-47.2.0" como versión pero el id es "1.20.1-forge-47.2.0"
+        "forge" => {
+            let forge_version = loader_version
+                .strip_prefix(minecraft_version)
+                .and_then(|v| v.strip_prefix('-'))
+                .unwrap_or(loader_version);
+            Some(format!("{}-forge-{}", minecraft_version, forge_version))
+        }
+        "neoforge" => Some(format!("neoforge-{}", loader_version)),
+        _ => None,
+    }
+}
+
+/// Busca en `versions/` del store el JSON que dejó el instalador de un mod loader
+fn find_store_loader_version(loader_type: &str, minecraft_version: &str, loader_version: &str) -> Option<String> {
+    let id = expected_loader_id(loader_type, minecraft_version, loader_version)?;
+    let content = std::fs::read_to_string(version_dir(&id).join(format!("{}.json", id))).ok()?;
+    let json = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+    let inherits_ok = json
+        .get("inheritsFrom")
+        .and_then(|v| v.as_str())
+        .map(|v| v == minecraft_version)
+        .unwrap_or(true);
+    inherits_ok.then_some(id)
+}
+
+/// Copia el JSON de un mod loader ya instalado en el store a la instancia y devuelve su id
+pub fn adopt_loader_version(instance_dir: &Path, loader_type: &str, minecraft_version: &str, loader_version: &str) -> Result<Option<String>, String> {
+    let Some(id) = find_store_loader_version(loader_type, minecraft_version, loader_version) else {
+        return Ok(None);
+    };
+    let source = version_dir(&id).join(format!("{}.json", id));
+    let target_dir = instance_dir.join("versions").join(&id);
+    std::fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create version dir {}: {}", target_dir.display(), e))?;
+    std::fs::copy(&source, target_dir.join(format!("{}.json", id)))
+        .map_err(|e| format!("Failed to copy loader json {}: {}", source.display(), e))?;
+    log::info!("Using {} from the shared store for {}", id, instance_dir.display());
+    Ok(Some(id))
+}
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+fn relative_key(base: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(base).ok().map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+/// Librerías presentes en el store antes de ejecutar un instalador
+pub fn snapshot_libraries() -> HashSet<String> {
+    let libs = libraries_dir();
+    list_files(&libs).iter().filter_map(|p| relative_key(&libs, p)).collect()
+}
+
+fn add_to_receipt(version_id: &str, files: impl IntoIterator<Item = String>) {
+    let path = version_dir(version_id).join(INSTALL_RECEIPT);
+    let mut existing: HashSet<String> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    existing.extend(files);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let mut sorted: Vec<&String> = existing.iter().collect();
+    sorted.sort();
+    if let Err(e) = serde_json::to_string_pretty(&sorted).map_err(|e| e.to_string()).and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string())) {
+        log::warn!("Failed to write install receipt for {}: {}", version_id, e);
+    }
+}
+
+/// Guarda qué librerías añadió un instalador para mantenerlas mientras el loader esté en uso
+pub fn record_install_receipt(version_id: &str, before: &HashSet<String>) {
+    let added: Vec<String> = snapshot_libraries().into_iter().filter(|f| !before.contains(f)).collect();
+    add_to_receipt(version_id, added);
+}
+
+/// Directorios de instancias (remotas y locales) que pueden referenciar el store
+pub(crate) fn instance_dirs() -> Vec<PathBuf> {
+    let root = store_root();
+    let mut dirs = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if name.starts_with('.') || STORE_DIRS.contains(&name.as_str()) || !path.is_dir() {
+                continue;
+            }
+            if path.join("versions").is_dir() {
+                dirs.push(path);
+            }
+        }
+    }
+    if let Ok(entries) = std::fs::read_dir(root.join("local_instances")) {
+        for entry in entries.flatten() {
+            if entry.path().join("versions").is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    dirs
+}
+
+fn read_version_jsons(instance_dir: &Path) -> Vec<serde_json::Value> {
+    let mut jsons = Vec::new();
+    let Ok(entries) = std::fs::read_dir(instance_dir.join("versions")) else { return jsons; };
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().to_string();
+        let Ok(content) = std::fs::read_to_string(entry.path().join(format!("{}.json", id))) else { continue; };
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
+            jsons.push(json);
+        }
+    }
+    jsons
+}
+
+/// Rutas de librería (relativas a `libraries/`) que declara un JSON de versión
+fn library_paths(json: &serde_json::Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    let Some(libraries) = json.get("libraries").and_then(|v| v.as_array()) else { return paths; };
+    for lib in libraries {
+        let downloads = lib.get("downloads");
+        if let Some(path) = downloads.and_then(|d| d.get("artifact")).and_then(|a| a.get("path")).and_then(|p| p.as_str()) {
+            paths.push(path.to_string());
+        } else if let Some(name) = lib.get("name").and_then(|v| v.as_str()) {
+            if let Ok(path) = crate::launcher::maven_to_path(name) {
+                paths.push(path);
+            }
+        }
+        if let Some(classifiers) = downloads.and_then(|d| d.get("classifiers")).and_then(|c| c.as_object()) {
+            for classifier in classifiers.values() {
+                if let Some(path) = classifier.get("path").and_then(|p| p.as_str()) {
+                    paths.push(path.to_string());
+                }
+            }
+        }
+    }
+    paths
+}
+
+#[derive(Default)]
+struct StoreReferences {
+    libraries: HashSet<String>,
+    versions: HashSet<String>,
+    asset_indexes: HashSet<String>,
+    asset_objects: HashSet<String>,
+}
+
+fn collect_references() -> StoreReferences {
+    let mut refs = StoreReferences::default();
+    for instance_dir in instance_dirs() {
+        for json in read_version_jsons(&instance_dir) {
+            refs.libraries.extend(library_paths(&json));
+            for key in ["id", "inheritsFrom"] {
+                if let Some(id) = json.get(key).and_then(|v| v.as_str()) {
+                    refs.versions.insert(id.to_string());
+                }
+            }
+            if let Some(index_id) = json.get("assetIndex").and_then(|a| a.get("id")).and_then(|v| v.as_str()) {
+                refs.asset_indexes.insert(index_id.to_string());
+            }
+        }
+    }
+
+    // Las librerías que dejaron los instaladores de los loaders en uso también cuentan
+    for version_id in &refs.versions {
+        let receipt = version_dir(version_id).join(INSTALL_RECEIPT);
+        if let Some(files) = std::fs::read_to_string(&receipt).ok().and_then(|c| serde_json::from_str::<Vec<String>>(&c).ok()) {
+            refs.libraries.extend(files);
+        }
+    }
+
+    let indexes_dir = assets_dir().join("indexes");
+    for index_id in &refs.asset_indexes {
+        let Ok(content) = std::fs::read_to_string(indexes_dir.join(format!("{}.json", index_id))) else { continue; };
+        let Ok(index) = serde_json::from_str::<serde_json::Value>(&content) else { continue; };
+        if let Some(objects) = index.get("objects").and_then(|o| o.as_object()) {
+            for object in objects.values() {
+                if let Some(hash) = object.get("hash").and_then(|h| h.as_str()) {
+                    refs.asset_objects.insert(hash.to_string());
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// Marca de la migración al store en curso (automática o manual)
+static STORE_MIGRATION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+fn is_busy(app_handle: &AppHandle) -> bool {
+    let downloading = match app_handle.try_state::<Arc<Mutex<bool>>>() {
+        Some(state) => state.lock().map(|d| *d).unwrap_or(false),
+        None => false,
+    };
+    downloading || crate::predownload::has_active_predownloads()
+}
+
+fn remove_empty_dirs(dir: &Path) {
+    let mut dirs: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+        .collect();
+    // Los más profundos primero para poder borrar los padres después
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    for d in dirs {
+        let _ = std::fs::remove_dir(&d);
+    }
+}
+
+fn garbage_collect(dry_run: bool) -> StoreGcReport {
+    let refs = collect_references();
+    let root = store_root();
+    let libs = libraries_dir();
+    let assets = assets_dir();
+    let versions_dir = store_config().versions_dir;
+
+    let mut report = StoreGcReport { dry_run, removed_files: Vec::new(), freed_bytes: 0, kept_files: 0 };
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    for file in list_files(&libs) {
+        match relative_key(&libs, &file) {
+            Some(key) if refs.libraries.contains(&key) => report.kept_files += 1,
+            _ => candidates.push(file),
+        }
+    }
+    for file in list_files(&assets.join("objects")) {
+        let hash = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if refs.asset_objects.contains(&hash) { report.kept_files += 1; } else { candidates.push(file); }
+    }
+    for file in list_files(&assets.join("indexes")) {
+        let id = file.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if refs.asset_indexes.contains(&id) { report.kept_files += 1; } else { candidates.push(file); }
+    }
+    if let Ok(entries) = std::fs::read_dir(&versions_dir) {
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let files = list_files(&entry.path());
+            if refs.versions.contains(&id) { report.kept_files += files.len(); } else { candidates.extend(files); }
+        }
+    }
+
+    for file in candidates {
+        let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+        if !dry_run {
+            if let Err(e) = std::fs::remove_file(&file) {
+                log::warn!("Failed to remove {}: {}", file.display(), e);
+                continue;
+            }
+        }
+        report.freed_bytes += size;
+        report.removed_files.push(relative_key(&root, &file).unwrap_or_else(|| file.to_string_lossy().to_string()));
+    }
+
+    if !dry_run {
+        remove_empty_dirs(&libs);
+        remove_empty_dirs(&assets.join("objects"));
+        remove_empty_dirs(&versions_dir);
+    }
+    report
+}
+
+/// Borra del store las librerías, assets y jars que ya no referencia ninguna instancia
+#[tauri::command]
+pub async fn garbage_collect_store(dry_run: Option<bool>, app_handle: AppHandle) -> Result<StoreGcReport, String> {
+    if is_busy(&app_handle) {
+        return Err("Cannot clean the shared store while a download is in progress".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(false);
+    let report = tauri::async_runtime::spawn_blocking(move || garbage_collect(dry_run))
+        .await
+        .map_err(|e| format!("Store cleanup task failed: {}", e))?;
+    log::info!(
+        "Store GC{}: {} files, {} bytes freed, {} kept",
+        if dry_run { " (dry run)" } else { "" },
+        report.removed_files.len(),
+        report.freed_bytes,
+        report.kept_files
+    );
+    Ok(report)
+}
+
+/// Mueve un archivo de la instancia al store. Si ya estaba en el store, la copia de la instancia sobra
+fn move_into_store(source: &Path, target: &Path, report: &mut StoreMigrationReport) -> Result<(), String> {
+    let size = std::fs::metadata(source).map(|m| m.len()).unwrap_or(0);
+    if target.exists() {
+        std::fs::remove_file(source).map_err(|e| format!("Failed to remove {}: {}", source.display(), e))?;
+        report.duplicates_removed += 1;
+        report.freed_bytes += size;
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    // rename falla entre volúmenes distintos: en ese caso copiar y borrar
+    if std::fs::rename(source, target).is_err() {
+        std::fs::copy(source, target).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+        std::fs::remove_file(source).map_err(|e| format!("Failed to remove {}: {}", source.display(), e))?;
+    }
+    report.moved_files += 1;
+    Ok(())
+}
+
+fn migrate_tree(source_dir: &Path, target_dir: &Path, report: &mut StoreMigrationReport) -> Vec<String> {
+    let mut moved = Vec::new();
+    for file in list_files(source_dir) {
+        if file.extension().map_or(false, |e| e == "tmp") {
+            let _ = std::fs::remove_file(&file);
+            continue;
+        }
+        let Some(key) = relative_key(source_dir, &file) else { continue; };
+        match move_into_store(&file, &target_dir.join(&key), report) {
+            Ok(()) => moved.push(key),
+            Err(e) => log::warn!("Store migration: {}", e),
+        }
+    }
+    remove_empty_dirs(source_dir);
+    moved
+}
+
+fn migrate_instance(instance_dir: &Path, report: &mut StoreMigrationReport) {
+    let before = report.moved_files + report.duplicates_removed;
+    let jsons = read_version_jsons(instance_dir);
+
+    let moved_libraries = migrate_tree(&instance_dir.join("libraries"), &libraries_dir(), report);
+    // Solo índices y objetos: `assets/` de la instancia también guarda el vídeo de fondo
+    migrate_tree(&instance_dir.join("assets").join("indexes"), &assets_dir().join("indexes"), report);
+    migrate_tree(&instance_dir.join("assets").join("objects"), &assets_dir().join("objects"), report);
+
+    if let Ok(entries) = std::fs::read_dir(instance_dir.join("versions")) {
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let jar = entry.path().join(format!("{}.jar", id));
+            if jar.exists() {
+                if let Err(e) = move_into_store(&jar, &client_jar_path(&id), report) {
+                    log::warn!("Store migration: {}", e);
+                }
+            }
+            // El JSON se queda en la instancia; una copia en el store permite reutilizar el loader
+            let json = entry.path().join(format!("{}.json", id));
+            let store_json = version_dir(&id).join(format!("{}.json", id));
+            if json.exists() && !store_json.exists() {
+                let _ = std::fs::create_dir_all(version_dir(&id));
+                let _ = std::fs::copy(&json, &store_json);
+            }
+        }
+    }
+
+    // Lo que había en libraries/ sin aparecer en ningún JSON lo generó el instalador del loader
+    let declared: HashSet<String> = jsons.iter().flat_map(library_paths).collect();
+    let generated: Vec<String> = moved_libraries.into_iter().filter(|f| !declared.contains(f)).collect();
+    if !generated.is_empty() {
+        for json in jsons.iter().filter(|j| j.get("inheritsFrom").is_some()) {
+            if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+                add_to_receipt(id, generated.clone());
+            }
+        }
+    }
+
+    if report.moved_files + report.duplicates_removed > before {
+        report.instances_migrated += 1;
+        log::info!("Migrated {} into the shared store", instance_dir.display());
+    }
+}
+
+fn migrate_all_instances() -> StoreMigrationReport {
+    let mut report = StoreMigrationReport { instances_migrated: 0, moved_files: 0, duplicates_removed: 0, freed_bytes: 0 };
+    for instance_dir in instance_dirs() {
+        migrate_instance(&instance_dir, &mut report);
+    }
+    let _ = std::fs::write(store_root().join(MIGRATION_MARKER), chrono::Utc::now().to_rfc3339());
+    report
+}
+
+/// Lanzamientos y sincronizaciones lo comprueban antes de tocar instancias o el store
+pub fn ensure_store_not_migrating() -> Result<(), String> {
+    if STORE_MIGRATION_IN_PROGRESS.load(Ordering::SeqCst) {
+        return Err("Instances are being migrated to the shared store, try again when it finishes".to_string());
+    }
+    Ok(())
+}
+
+/// Bloquea lanzamientos, sincronizaciones, limpieza del store y movimiento de datos durante toda
+/// la migración marcando también el estado de descarga
+struct StoreMigrationGuard {
+    app_handle: AppHandle,
+}
+
+impl StoreMigrationGuard {
+    fn acquire(app_handle: &AppHandle) -> Result<Self, String> {
+        if STORE_MIGRATION_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return Err("Instances are already being migrated to the shared store".to_string());
+        }
+        if is_busy(app_handle) {
+            STORE_MIGRATION_IN_PROGRESS.store(false, Ordering::SeqCst);
+            return Err("Cannot migrate instances while a download is in progress".to_string());
+        }
+        if let Some(state) = app_handle.try_state::<Arc<Mutex<bool>>>() {
+            if let Ok(mut downloading) = state.lock() {
+                *downloading = true;
+            }
+        }
+        Ok(Self { app_handle: app_handle.clone() })
+    }
+}
+
+impl Drop for StoreMigrationGuard {
+    fn drop(&mut self) {
+        if let Some(state) = self.app_handle.try_state::<Arc<Mutex<bool>>>() {
+            if let Ok(mut downloading) = state.lock() {
+                *downloading = false;
+            }
+        }
+        STORE_MIGRATION_IN_PROGRESS.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Migración automática de las instancias creadas antes del store. Solo se ejecuta una vez
+pub fn migrate_to_store_on_startup(app_handle: &AppHandle) {
+    if store_root().join(MIGRATION_MARKER).exists() {
+        return;
+    }
+    let guard = match StoreMigrationGuard::acquire(app_handle) {
+        Ok(guard) => guard,
+        Err(e) => {
+            log::warn!("Store migration postponed to the next start: {}", e);
+            return;
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = guard;
+        let report = migrate_all_instances();
+        log::info!(
+            "Store migration finished: {} instances, {} files moved, {} duplicates removed, {} bytes freed",
+            report.instances_migrated,
+            report.moved_files,
+            report.duplicates_removed,
+            report.freed_bytes
+        );
+    });
+}
+
+#[tauri::command]
+pub async fn migrate_instances_to_store(app_handle: AppHandle) -> Result<StoreMigrationReport, String> {
+    let guard = StoreMigrationGuard::acquire(&app_handle)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = guard;
+        migrate_all_instances()
+    })
+    .await
+        .map_err(|e| format!("Store migration task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loader_ids_match_installer_output() {
+        assert_eq!(expected_loader_id("fabric", "1.20.1", "0.15.11").as_deref(), Some("fabric-loader-0.15.11-1.20.1"));
+        assert_eq!(expected_loader_id("forge", "1.20.1", "1.20.1-47.2.0").as_deref(), Some("1.20.1-forge-47.2.0"));
+        assert_eq!(expected_loader_id("forge", "1.20.1", "47.2.0").as_deref(), Some("1.20.1-forge-47.2.0"));
+        assert_eq!(expected_loader_id("neoforge", "1.21.1", "21.1.65").as_deref(), Some("neoforge-21.1.65"));
+        assert_eq!(expected_loader_id("quilt", "1.20.1", "0.26.0"), None);
+    }
+}
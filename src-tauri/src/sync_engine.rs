@@ -0,0 +1,530 @@
+use crate::models::{FileEntry, InstanceManifest};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+/// Limitador global de ancho de banda compartido por todas las descargas de una sincronización
+pub struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    next_slot: Mutex<tokio::time::Instant>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            next_slot: Mutex::new(tokio::time::Instant::now()),
+        }
+    }
+
+    /// Reserva el tiempo que "cuestan" `bytes` y espera hasta que se haya consumido
+    pub async fn consume(&self, bytes: usize) {
+        let wait_until = {
+            let Ok(mut next) = self.next_slot.lock() else { return };
+            let now = tokio::time::Instant::now();
+            let start = (*next).max(now);
+            *next = start + std::time::Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64);
+            *next
+        };
+        tokio::time::sleep_until(wait_until).await;
+    }
+}
+
+#[derive(Clone)]
+pub struct SyncOptions {
+    /// Descargas simultáneas de archivos de la instancia. `None` = automático según CPU
+    pub parallelism: Option<usize>,
+    /// Límite de ancho de banda. `None` = sin límite
+    pub limiter: Option<Arc<BandwidthLimiter>>,
+    /// Evento de progreso a emitir. `None` = sincronización silenciosa
+    pub progress_event: Option<&'static str>,
+    /// Permite abortar la sincronización entre archivos
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            parallelism: None,
+            limiter: None,
+            progress_event: Some("asset-download-progress"),
+            cancel: None,
+        }
+    }
+}
+
+impl SyncOptions {
+    /// Sincronización en segundo plano: pocas conexiones y ancho de banda limitado
+    pub fn background(max_bytes_per_sec: Option<u64>, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            parallelism: Some(2),
+            limiter: max_bytes_per_sec.map(|b| Arc::new(BandwidthLimiter::new(b))),
+            progress_event: Some("predownload-progress"),
+            cancel: Some(cancel),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or(false)
+    }
+
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("SYNC_CANCELLED".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Número de descargas simultáneas, con `auto` como valor por defecto
+    pub fn parallel(&self, auto: usize, pending: usize) -> usize {
+        self.parallelism.unwrap_or(auto).max(1).min(pending.max(1))
+    }
+
+    pub fn emit_progress(&self, app_handle: &AppHandle, current: u64, total: u64, percentage: f32, current_file: &str, status: &str) {
+        if let Some(event) = self.progress_event {
+            let _ = app_handle.emit(event, serde_json::json!({
+                "current": current,
+                "total": total,
+                "percentage": percentage,
+                "current_file": current_file,
+                "status": status
+            }));
+        }
+    }
+}
+
+pub struct InstanceSyncRequest {
+    pub instance_id: String,
+    pub minecraft_version: String,
+    pub base_url: Option<String>,
+    pub instance_url: Option<String>,
+}
+
+/// Verifica si un archivo debe ignorarse basándose en los patrones de ignorar :)
+/// Los patrones sin '/' solo coinciden con archivos en la raíz.
+/// Los patrones con '/' pueden coincidir con rutas completas.
+pub fn should_ignore_config_file(file_path: &str, ignored_patterns: &[String]) -> bool {
+    let is_root_file = !file_path.contains('/');
+
+    if is_root_file {
+        crate::utils::matches_glob_patterns(file_path, ignored_patterns)
+    } else {
+        let matches_full_path = crate::utils::matches_glob_patterns(file_path, ignored_patterns);
+        if matches_full_path {
+            true
+        } else {
+            let has_simple_pattern = ignored_patterns.iter().any(|p| !p.contains('/'));
+            if has_simple_pattern {
+                // NO ignorar
+                false
+            } else {
+                // No hay patrones simples, solo comparar con la ruta completa
+                false
+            }
+        }
+    }
+}
+
+pub(crate) fn build_sync_client() -> Result<Arc<reqwest::Client>, String> {
+    // Cliente HTTP optimizado con pool de conexiones grande
+    Ok(Arc::new(reqwest::Client::builder()
+        .user_agent("KindlyKlanKlient/1.0")
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(120))
+        .pool_max_idle_per_host(50)
+        .pool_idle_timeout(std::time::Duration::from_secs(60))
+        .tcp_nodelay(true)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?))
+}
+
+async fn download_file_limited(client: &reqwest::Client, url: &str, file_path: &Path, limiter: &BandwidthLimiter, options: &SyncOptions) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to start download from {}: {}", url, e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("HTTP error {} for {}: {}", status, url, error_text));
+    }
+
+    let parent_dir = file_path.parent().ok_or_else(|| format!("Invalid path: {}", file_path.display()))?;
+    tokio::fs::create_dir_all(parent_dir).await
+        .map_err(|e| format!("Failed to create parent directory {}: {}", parent_dir.display(), e))?;
+
+    let tmp_path = file_path.with_extension("kk.tmp");
+    let mut tmp_file = tokio::fs::File::create(&tmp_path)
+        .await
+        .map_err(|e| format!("Failed to create temp file {}: {}", tmp_path.display(), e))?;
+
+    // Descarga por trozos para poder respetar el límite de ancho de banda
+    while let Some(chunk) = response.chunk().await
+        .map_err(|e| format!("Failed to read response bytes from {}: {}", url, e))?
+    {
+        options.check_cancelled()?;
+        limiter.consume(chunk.len()).await;
+        tmp_file.write_all(&chunk).await
+            .map_err(|e| format!("Failed to write bytes to {}: {}", tmp_path.display(), e))?;
+    }
+
+    tmp_file
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to flush temp file {}: {}", tmp_path.display(), e))?;
+    drop(tmp_file);
+
+    tokio::fs::rename(&tmp_path, file_path)
+        .await
+        .map_err(|e| format!("Failed to move temp file to {}: {}", file_path.display(), e))?;
+
+    Ok(())
+}
+
+/// Descarga con reintentos respetando las opciones de sincronización
+pub async fn download_with_options(client: &reqwest::Client, url: &str, file_path: &Path, options: &SyncOptions) -> Result<(), String> {
+    options.check_cancelled()?;
+    let Some(limiter) = options.limiter.as_ref() else {
+        return crate::instances::download_file_with_retry_and_client(client, url, file_path).await;
+    };
+
+    const MAX_RETRIES: u32 = 3;
+    for attempt in 1..=MAX_RETRIES {
+        match download_file_limited(client, url, file_path, limiter, options).await {
+            Ok(_) => return Ok(()),
+            Err(_e) => {
+                options.check_cancelled()?;
+                if attempt < MAX_RETRIES {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    Err(format!("Failed to download {} after {} attempts", url, MAX_RETRIES))
+}
+
+/// Un fallo no detiene el resto del lote: se devuelve cuántas descargas fallaron
+async fn download_batch(downloads: Vec<(String, PathBuf)>, auto_parallel: usize, options: &SyncOptions, label: &str) -> Result<usize, String> {
+    if downloads.is_empty() {
+        return Ok(0);
+    }
+
+    use futures_util::stream::{self, StreamExt};
+    let parallel = options.parallel(auto_parallel, downloads.len());
+    let client = build_sync_client()?;
+
+    let results: Vec<Result<(), String>> = stream::iter(downloads.into_iter())
+        .map(|(url, path)| {
+            let client = client.clone();
+            async move {
+                download_with_options(&client, &url, &path, options).await
+            }
+        })
+        .buffer_unordered(parallel)
+        .collect()
+        .await;
+
+    options.check_cancelled()?;
+    let mut failed = 0;
+    for result in results {
+        if let Err(e) = result {
+            log::warn!("Error downloading {}: {}", label, e);
+            failed += 1;
+        }
+    }
+    Ok(failed)
+}
+
+fn resolve_file_url(base: &str, file: &FileEntry) -> String {
+    if file.url.starts_with("http") {
+        file.url.clone()
+    } else {
+        format!("{}/{}", base.trim_end_matches('/'), file.url.trim_start_matches('/'))
+    }
+}
+
+/// true si el archivo local ya coincide con el del manifest
+fn is_up_to_date(target_path: &Path, file: &FileEntry) -> bool {
+    if !target_path.exists() {
+        return false;
+    }
+    if !file.sha256.is_empty() {
+        return crate::instances::verify_file_checksum(target_path, &file.sha256).is_ok();
+    }
+    if let Some(md5) = file.md5.as_ref() {
+        if !md5.is_empty() {
+            return crate::instances::verify_file_md5(target_path, md5).is_ok();
+        }
+    }
+    false
+}
+
+/// Archivos colocados por nombre en una carpeta (mods, resourcepacks, shaderpacks).
+/// Los que ya existen en otra instancia se enlazan en vez de descargarse.
+/// Hashea y enlaza archivos, así que se ejecuta fuera del runtime async
+async fn plan_named_files(base: &str, files: &[FileEntry], dir: &Path, ignored: &[String], expected: &mut HashSet<String>) -> Result<Vec<(String, PathBuf)>, String> {
+    expected.extend(files.iter().map(|file| file.name.clone()));
+    let (base, files, dir, ignored) = (base.to_string(), files.to_vec(), dir.to_path_buf(), ignored.to_vec());
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut downloads = Vec::new();
+        for file in &files {
+            let should_ignore = crate::utils::matches_glob_patterns(&file.name, &ignored);
+            let target_path = dir.join(&file.name);
+
+            let needs_file = if should_ignore {
+                // Archivo ignorado: solo descargar si NO existe (primera vez)
+                !target_path.exists()
+            } else {
+                !is_up_to_date(&target_path, file)
+            };
+            if needs_file && crate::dedup::place_existing(&file.sha256, &target_path).is_none() {
+                downloads.push((resolve_file_url(&base, file), target_path));
+            }
+        }
+        downloads
+    })
+    .await
+    .map_err(|e| format!("File planning task failed: {}", e))
+}
+
+/// Descarga archivos compartibles y los registra en el índice de deduplicación
+async fn download_shareable_batch(downloads: Vec<(String, PathBuf)>, auto_parallel: usize, options: &SyncOptions, label: &str) -> Result<usize, String> {
+    let paths: Vec<PathBuf> = downloads.iter().map(|(_, path)| path.clone()).collect();
+    let failed = download_batch(downloads, auto_parallel, options, label).await?;
+    crate::dedup::register_files(paths).await?;
+    Ok(failed)
+}
+
+/// Borra los archivos de `dir` que estaban en el manifest anterior pero ya no están en el actual
+fn cleanup_named_files(dir: &Path, previous: &[String], expected: &HashSet<String>, ignored: &[String]) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
+            if previous.iter().any(|p| p == file_name) && !expected.contains(file_name) {
+                if !crate::utils::matches_glob_patterns(file_name, ignored) {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+    }
+}
+
+fn normalize_config_target(file: &FileEntry) -> String {
+    let mut rel = file.target.clone().unwrap_or(file.path.clone());
+    if rel == "config/options.txt" { rel = "options.txt".to_string(); }
+    if rel.starts_with("config/config/") { rel = rel.replacen("config/config/", "config/", 1); }
+    rel
+}
+
+/// Sincroniza los archivos del manifest de la instancia (mods, configs, resourcepacks y shaderpacks)
+pub async fn sync_instance_files(instance_dir: &Path, instance: &InstanceManifest, base: &str, options: &SyncOptions) -> Result<(), String> {
+    // Cargar historial de manifest anterior
+    let previous_history = crate::instances::load_manifest_history(instance_dir)?;
+
+    // Obtener patrones de archivos ignorados
+    let ignored_patterns = instance.ignored_files.as_ref();
+    let empty_vec = Vec::<String>::new();
+    let ignored_mods = ignored_patterns.map(|p| &p.mods).unwrap_or(&empty_vec);
+    let ignored_configs = ignored_patterns.map(|p| &p.configs).unwrap_or(&empty_vec);
+    let ignored_resourcepacks = ignored_patterns.map(|p| &p.resourcepacks).unwrap_or(&empty_vec);
+    let ignored_shaderpacks = ignored_patterns.map(|p| &p.shaderpacks).unwrap_or(&empty_vec);
+
+    // El índice de deduplicación se pone al día una vez y se guarda al acabar con las carpetas compartibles
+    crate::dedup::begin_sync().await?;
+
+    // Mods
+    let mods_dir = instance_dir.join("mods");
+    tokio::fs::create_dir_all(&mods_dir).await.map_err(|e| e.to_string())?;
+    let mut expected_mods: HashSet<String> = HashSet::new();
+    let mods_to_download = plan_named_files(base, &instance.files.mods, &mods_dir, ignored_mods, &mut expected_mods).await?;
+    let mut failed = download_shareable_batch(mods_to_download, num_cpus::get().saturating_mul(8).max(50), options, "mod").await?;
+    if let Some(history) = &previous_history {
+        cleanup_named_files(&mods_dir, &history.files.mods, &expected_mods, ignored_mods);
+    }
+
+    // Configs
+    let mut expected_configs: HashSet<String> = HashSet::new();
+    let mut expected_root_files: HashSet<String> = HashSet::new();
+    let mut configs_to_download: Vec<(String, PathBuf)> = Vec::new();
+    for config_file in &instance.files.configs {
+        let rel = normalize_config_target(config_file);
+        expected_configs.insert(rel.clone());
+
+        if !rel.contains('/') {
+            expected_root_files.insert(rel.clone());
+        }
+
+        let should_ignore = should_ignore_config_file(&rel, ignored_configs);
+        let target_path = instance_dir.join(&rel);
+
+        // Crear directorio padre si es necesario
+        if let Some(parent) = target_path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+
+        if should_ignore {
+            // Archivo ignorado: solo descargar si NO existe (primera vez)
+            if !target_path.exists() {
+                configs_to_download.push((resolve_file_url(base, config_file), target_path));
+            }
+        } else if !is_up_to_date(&target_path, config_file) {
+            configs_to_download.push((resolve_file_url(base, config_file), target_path));
+        }
+    }
+    failed += download_batch(configs_to_download, num_cpus::get().saturating_mul(4).max(20), options, "config").await?;
+
+    if let Some(history) = &previous_history {
+        let config_dir = instance_dir.join("config");
+        if config_dir.exists() {
+            for entry in walkdir::WalkDir::new(&config_dir) {
+                let entry = entry.map_err(|e| e.to_string())?;
+                if entry.file_type().is_file() {
+                    let rel_path = entry.path().strip_prefix(instance_dir).map_err(|e| e.to_string())?.to_string_lossy().replace('\\', "/");
+                    // Solo borrar si estaba en el historial pero ya no está en el manifest actual
+                    if history.files.configs.contains(&rel_path) && !expected_configs.contains(&rel_path) {
+                        let should_ignore = should_ignore_config_file(&rel_path, ignored_configs);
+                        if !should_ignore {
+                            let _ = std::fs::remove_file(entry.path());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Limpiar archivos en la raíz: solo borrar si estaban en el historial pero ya no están en el manifest actual
+    if let Some(history) = &previous_history {
+        if let Ok(entries) = std::fs::read_dir(instance_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() {
+                    if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
+                        // Ignorar archivos del sistema (.manifest_history.json, etc.)
+                        if file_name.starts_with('.') {
+                            continue;
+                        }
+                        // Solo procesar archivos que estaban en el historial de root_files
+                        if history.files.root_files.contains(&file_name.to_string()) && !expected_root_files.contains(file_name) {
+                            let should_ignore = should_ignore_config_file(file_name, ignored_configs);
+                            if !should_ignore {
+                                let _ = std::fs::remove_file(&path);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Resourcepacks y shaderpacks
+    let packs = [
+        ("resourcepacks", instance.files.resourcepacks.as_deref().unwrap_or(&[]), ignored_resourcepacks),
+        ("shaderpacks", instance.files.shaderpacks.as_deref().unwrap_or(&[]), ignored_shaderpacks),
+    ];
+    for (folder, files, ignored) in packs {
+        let dir = instance_dir.join(folder);
+        let mut expected: HashSet<String> = HashSet::new();
+        if !files.is_empty() {
+            tokio::fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+            let downloads = plan_named_files(base, files, &dir, ignored, &mut expected).await?;
+            failed += download_shareable_batch(downloads, num_cpus::get().saturating_mul(4).max(20), options, folder).await?;
+        }
+        if let Some(history) = &previous_history {
+            let previous = if folder == "resourcepacks" { &history.files.resourcepacks } else { &history.files.shaderpacks };
+            cleanup_named_files(&dir, previous, &expected, ignored);
+        }
+    }
+    crate::dedup::persist_index().await?;
+
+    // Servidores del evento en la lista multijugador. Un servers.dat ilegible no impide jugar; si no
+    // se pudo escribir siguen gestionados los de antes, para poder quitarlos en otra sincronización
+    let previously_managed = previous_history.as_ref().map(|h| h.managed_servers.as_slice()).unwrap_or_default();
+    let managed_servers = match crate::server_list::apply_manifest_servers(instance_dir, &instance.instance, previously_managed) {
+        Ok(managed) => managed,
+        Err(e) => {
+            log::warn!("Failed to update servers.dat: {}", e);
+            previously_managed.to_vec()
+        }
+    };
+
+    // Guardar el nuevo historial después de procesar todos los archivos
+    crate::instances::save_manifest_history(instance_dir, instance, managed_servers).await?;
+    if failed > 0 {
+        return Err(format!("{} instance files failed to download", failed));
+    }
+    Ok(())
+}
+
+pub async fn fetch_instance_manifest(base_url: &str, instance_url: &str) -> Result<InstanceManifest, String> {
+    let full_url = if instance_url.starts_with("http") { instance_url.to_string() } else { format!("{}/{}", base_url.trim_end_matches('/'), instance_url.trim_start_matches('/')) };
+    let client = reqwest::Client::new();
+    let response = client.get(&full_url).send().await.map_err(|e| format!("Failed to fetch instance details: {}", e))?;
+    if !response.status().is_success() { return Err(format!("HTTP error: {}", response.status())); }
+    response.json().await.map_err(|e| format!("Failed to parse instance JSON: {}", e))
+}
+
+/// Sincronización completa de una instancia remota: cliente, mod loader, assets, librerías y archivos del manifest
+pub async fn sync_instance(app_handle: &AppHandle, request: &InstanceSyncRequest, options: &SyncOptions) -> Result<(), String> {
+    crate::paths::ensure_data_not_moving()?;
+    crate::store::ensure_store_not_migrating()?;
+    let instance_dir = crate::launcher::get_instance_directory(&request.instance_id);
+    let minecraft_version = &request.minecraft_version;
+    let _ = tokio::fs::create_dir_all(crate::store::libraries_dir()).await;
+    let _ = tokio::fs::create_dir_all(instance_dir.join("mods")).await;
+    options.emit_progress(app_handle, 0, 1, 0.0, "", "Starting");
+    options.emit_progress(app_handle, 0, 100, 1.0, "", "Version");
+    crate::instances::ensure_minecraft_client_present(&instance_dir, minecraft_version, options).await?;
+    options.check_cancelled()?;
+
+    let mut instance_manifest: Option<InstanceManifest> = None;
+    let mut installed_mod_loader_version_id: Option<String> = None;
+    if let (Some(base), Some(instance_url)) = (&request.base_url, &request.instance_url) {
+        let manifest = fetch_instance_manifest(base, instance_url).await?;
+        if let Some(mod_loader) = manifest.instance.mod_loader.as_ref() {
+            options.emit_progress(app_handle, 3, 100, 3.0, "", "ModLoader");
+            installed_mod_loader_version_id = crate::instances::install_mod_loader(minecraft_version, mod_loader, &instance_dir, options).await?;
+        }
+        // El lanzamiento usa exactamente esta versión en vez de buscarla en versions/
+        let launch_version = installed_mod_loader_version_id.as_deref().unwrap_or(minecraft_version);
+        crate::version_resolver::record_version_id(&instance_dir, launch_version)?;
+        instance_manifest = Some(manifest);
+    }
+    options.check_cancelled()?;
+
+    options.emit_progress(app_handle, 10, 100, 10.0, "", "Mojang");
+    let _ = crate::instances::ensure_assets_present_with_progress(app_handle, &instance_dir, minecraft_version, None, options).await?;
+    options.check_cancelled()?;
+
+    options.emit_progress(app_handle, 90, 100, 90.0, "", "Libraries");
+    let mut failed_libraries = crate::instances::ensure_version_libraries(&instance_dir, minecraft_version, options).await?;
+
+    // Download mod loader libraries if applicable (using the version_id returned by install_mod_loader)
+    if let Some(version_id) = &installed_mod_loader_version_id {
+        failed_libraries += crate::instances::ensure_mod_loader_libraries(&instance_dir, version_id, options).await?;
+    }
+    options.check_cancelled()?;
+    // Sin todas las librerías la instancia no arranca: no se da por sincronizada
+    if failed_libraries > 0 {
+        return Err(format!("{} libraries failed to download", failed_libraries));
+    }
+
+    if let (Some(instance), Some(base)) = (instance_manifest, &request.base_url) {
+        options.emit_progress(app_handle, 95, 100, 95.0, "", "Instance");
+        sync_instance_files(&instance_dir, &instance, base, options).await?;
+    }
+
+    options.emit_progress(app_handle, 100, 100, 100.0, "", "Completado");
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Utilidades compartidas por los tests que usan los JSON de `tests/fixtures`
+
+use std::path::{Path, PathBuf};
+
+pub const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// Carpeta temporal vacía y propia de un test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kkk-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Instancia temporal con los JSON de `tests/fixtures/versions` indicados
+pub fn fixture_instance(name: &str, ids: &[&str]) -> PathBuf {
+    let dir = temp_dir(name);
+    for id in ids {
+        let version_dir = dir.join("versions").join(id);
+        std::fs::create_dir_all(&version_dir).unwrap();
+        std::fs::copy(
+            Path::new(FIXTURES).join("versions").join(format!("{}.json", id)),
+            version_dir.join(format!("{}.json", id)),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+/// Resuelve la primera versión de `ids` en una instancia con esos fixtures
+pub fn fixture_version(name: &str, ids: &[&str]) -> (PathBuf, crate::version_resolver::ResolvedVersion) {
+    let dir = fixture_instance(name, ids);
+    let version = crate::version_resolver::resolve_version(&dir, ids[0]).unwrap();
+    (dir, version)
+}
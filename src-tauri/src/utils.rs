@@ -52,3 +52,24 @@ pub fn matches_glob_patterns(file_path: &str, patterns: &[String]) -> bool {
     false
 }
 
+/// Los ids de instancias, copias, etc. acaban en rutas: sin separadores ni `..`
+pub fn validate_id(kind: &str, id: &str) -> Result<(), String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("Invalid {} id: {}", kind, id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_cannot_leave_their_directory() {
+        assert!(validate_id("instance", "kindly-survival").is_ok());
+        assert!(validate_id("backup", "20240610-181500123-launch-all").is_ok());
+        for id in ["", "..", "../other", "a/b", "a\\b", "..\\..\\x"] {
+            assert_eq!(validate_id("instance", id).unwrap_err(), format!("Invalid instance id: {}", id));
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Archivo en la carpeta de la instancia con el id exacto de la versión que se lanza
+const VERSION_ID_FILE: &str = ".version_id";
+
+const MAX_INHERITANCE_DEPTH: usize = 8;
+
+/// Una versión con todos sus `inheritsFrom` ya fusionados
+#[derive(Debug, Clone)]
+pub struct ResolvedVersion {
+    pub id: String,
+    /// Ids de la cadena, del pedido al más base
+    pub inherits: Vec<String>,
+    pub main_class: String,
+    /// Librerías del más base al pedido; las posteriores sustituyen a las anteriores del mismo artefacto
+    pub libraries: Vec<Value>,
+    /// `arguments.jvm`/`arguments.game` de toda la cadena concatenados (padre primero)
+    pub arguments: Option<Value>,
+    /// `minecraftArguments` del JSON más específico que lo tenga (versiones anteriores a 1.13)
+    pub minecraft_arguments: Option<String>,
+    pub asset_index: Option<Value>,
+    pub assets: Option<String>,
+    pub java_version: Option<Value>,
+    /// Versión cuyo jar de cliente se usa
+    pub jar: String,
+    pub version_type: String,
+}
+
+/// JSON de una versión: primero el de la instancia y, si no está, el del store
+pub fn version_json_path(instance_dir: &Path, version_id: &str) -> Option<PathBuf> {
+    let file = format!("{}.json", version_id);
+    [
+        instance_dir.join("versions").join(version_id).join(&file),
+        crate::store::version_dir(version_id).join(&file),
+    ]
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+fn load_version_json(instance_dir: &Path, version_id: &str) -> Result<Value, String> {
+    let path = version_json_path(instance_dir, version_id)
+        .ok_or_else(|| format!("Version JSON not found for {}", version_id))?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read version JSON {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse version JSON {}: {}", path.display(), e))
+}
+
+fn merge_argument_list(target: &mut serde_json::Map<String, Value>, key: &str, list: Option<&Value>) {
+    if let Some(items) = list.and_then(|l| l.as_array()) {
+        let entry = target.entry(key.to_string()).or_insert_with(|| Value::Array(Vec::new()));
+        if let Some(existing) = entry.as_array_mut() {
+            existing.extend(items.iter().cloned());
+        }
+    }
+}
+
+/// Carga `version_id` y fusiona sus padres como el launcher oficial: el hijo manda en
+/// mainClass, assetIndex, javaVersion y minecraftArguments, las librerías y `arguments` se suman
+pub fn resolve_version(instance_dir: &Path, version_id: &str) -> Result<ResolvedVersion, String> {
+    let mut chain = vec![load_version_json(instance_dir, version_id)?];
+    let mut ids = vec![version_id.to_string()];
+    while let Some(parent) = chain.last().and_then(|j| j.get("inheritsFrom")).and_then(|v| v.as_str()).map(|s| s.to_string()) {
+        if ids.contains(&parent) || ids.len() >= MAX_INHERITANCE_DEPTH {
+            return Err(format!("Invalid inheritsFrom chain for {}: {} -> {}", version_id, ids.join(" -> "), parent));
+        }
+        chain.push(load_version_json(instance_dir, &parent)?);
+        ids.push(parent);
+    }
+
+    // Del hijo al padre: el primer valor encontrado es el del JSON más específico
+    let first_str = |key: &str| chain.iter().find_map(|j| j.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+    let first_value = |key: &str| chain.iter().find_map(|j| j.get(key).cloned());
+
+    let main_class = first_str("mainClass").ok_or_else(|| format!("No mainClass in {} or its parents", version_id))?;
+
+    let mut libraries = Vec::new();
+    let mut arguments = serde_json::Map::new();
+    for json in chain.iter().rev() {
+        if let Some(libs) = json.get("libraries").and_then(|v| v.as_array()) {
+            libraries.extend(libs.iter().cloned());
+        }
+        if let Some(args) = json.get("arguments") {
+            merge_argument_list(&mut arguments, "jvm", args.get("jvm"));
+            merge_argument_list(&mut arguments, "game", args.get("game"));
+        }
+    }
+    let has_arguments = chain.iter().any(|j| j.get("arguments").is_some());
+
+    Ok(ResolvedVersion {
+        id: version_id.to_string(),
+        main_class,
+        libraries,
+        arguments: has_arguments.then_some(Value::Object(arguments)),
+        minecraft_arguments: first_str("minecraftArguments"),
+        asset_index: first_value("assetIndex"),
+        assets: first_str("assets"),
+        java_version: first_value("javaVersion"),
+        jar: first_str("jar").unwrap_or_else(|| ids.last().cloned().unwrap_or_default()),
+        version_type: first_str("type").unwrap_or_else(|| "release".to_string()),
+        inherits: ids,
+    })
+}
+
+/// Id de versión registrado para la instancia
+pub fn recorded_version_id(instance_dir: &Path) -> Option<String> {
+    std::fs::read_to_string(instance_dir.join(VERSION_ID_FILE))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Guarda el id exacto a lanzar, para no tener que adivinarlo buscando en `versions/`
+pub fn record_version_id(instance_dir: &Path, version_id: &str) -> Result<(), String> {
+    std::fs::write(instance_dir.join(VERSION_ID_FILE), version_id)
+        .map_err(|e| format!("Failed to record version id for {}: {}", instance_dir.display(), e))
+}
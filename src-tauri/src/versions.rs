@@ -21,45 +21,48 @@ pub struct VersionManifest {
 // Library and rule structures for Minecraft version parsing
 #[derive(Deserialize, Debug, Clone)]
 pub struct Extract {
-    #[allow(dead_code)]
+    #[serde(default)]
     pub exclude: Vec<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct Rule {
-    pub action: String,
-    pub os: Option<OsRule>,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct OsRule {
-    pub name: Option<String>,
-}
-
 #[derive(Deserialize, Debug, Clone)]
 pub struct Library {
-    #[allow(dead_code)]
     pub name: String,
     pub downloads: Option<LibraryDownloads>,
-    #[allow(dead_code)]
+    /// Sistema -> classifier de los nativos (p. ej. "natives-windows-${arch}"), solo en versiones antiguas
     pub natives: Option<HashMap<String, String>>,
-    pub rules: Option<Vec<Rule>>,
+    /// Se evalúan con `arguments::rules_allow`, igual que las de los argumentos
+    pub rules: Option<serde_json::Value>,
     #[serde(default)]
-    #[allow(dead_code)]
     pub extract: Option<Extract>,
 }
 
 impl Library {
-    #[allow(dead_code)]
+    pub fn is_allowed(&self, ctx: &crate::arguments::RuleContext) -> bool {
+        crate::arguments::rules_allow(self.rules.as_ref(), ctx)
+    }
+
     pub fn get_extract(&self) -> Option<&Extract> {
         self.extract.as_ref()
     }
+
+    /// Classifier con los nativos de esta librería para el sistema indicado
+    pub fn native_classifier(&self, os_name: &str) -> Option<String> {
+        let classifier = self.natives.as_ref()?.get(os_name)?;
+        let arch = if cfg!(target_pointer_width = "64") { "64" } else { "32" };
+        Some(classifier.replace("${arch}", arch))
+    }
+
+    /// Jar de nativos a descargar y extraer, si la librería los tiene para este sistema
+    pub fn native_artifact(&self, os_name: &str) -> Option<&LibraryArtifact> {
+        let classifier = self.native_classifier(os_name)?;
+        self.downloads.as_ref()?.classifiers.as_ref()?.get(&classifier)
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct LibraryDownloads {
     pub artifact: Option<LibraryArtifact>,
-    #[allow(dead_code)]
     pub classifiers: Option<HashMap<String, LibraryArtifact>>,
 }
 
@@ -67,30 +70,8 @@ pub struct LibraryDownloads {
 pub struct LibraryArtifact {
     pub url: String,
     pub path: String,
-}
-
-// Check if a library is allowed for the current operating system based on rules
-pub fn is_library_allowed(lib: &Library, os_name: &str) -> bool {
-    let rules = match &lib.rules {
-        Some(r) => r,
-        None => return true,
-    };
-    let mut allowed = false;
-    for rule in rules {
-        let matches = if let Some(os) = &rule.os {
-            if let Some(name) = &os.name {
-                name == os_name
-            } else {
-                true
-            }
-        } else {
-            true
-        };
-        if matches {
-            allowed = rule.action == "allow";
-        }
-    }
-    allowed
+    #[serde(default)]
+    pub sha1: Option<String>,
 }
 
 // Commands for fetching Minecraft and Fabric versions
@@ -0,0 +1,327 @@
+use crate::models::WorldMetadata;
+use crate::nbt::Tag;
+use std::path::{Path, PathBuf};
+
+/// Archivo que identifica una carpeta como mundo
+pub const LEVEL_DAT: &str = "level.dat";
+
+fn game_mode_name(id: i64) -> Option<&'static str> {
+    match id {
+        0 => Some("survival"),
+        1 => Some("creative"),
+        2 => Some("adventure"),
+        3 => Some("spectator"),
+        _ => None,
+    }
+}
+
+fn difficulty_name(id: i64) -> Option<&'static str> {
+    match id {
+        0 => Some("peaceful"),
+        1 => Some("easy"),
+        2 => Some("normal"),
+        3 => Some("hard"),
+        _ => None,
+    }
+}
+
+/// Metadatos de `Data` en `level.dat` (gzip). Las claves que falten en versiones antiguas quedan vacías
+pub fn read_world_metadata(world_dir: &Path) -> Result<WorldMetadata, String> {
+    let (_, root) = crate::nbt::read_file(&world_dir.join(LEVEL_DAT))?;
+    let data = root.get("Data").ok_or("level.dat has no Data compound")?;
+    let string = |path: &str| data.path(path).and_then(|t| t.as_str()).map(|s| s.to_string());
+    let flag = |key: &str| data.get(key).and_then(|t| t.as_bool()).unwrap_or(false);
+
+    // Desde 1.16 la semilla está en WorldGenSettings; antes en RandomSeed
+    let seed = data.path("WorldGenSettings/seed").or_else(|| data.get("RandomSeed")).and_then(|t| t.as_i64()).map(|s| s.to_string());
+    // Algunas versiones guardan la dificultad como texto en lugar de número
+    let difficulty = match data.get("Difficulty") {
+        Some(Tag::String(name)) => Some(name.to_lowercase()),
+        Some(tag) => tag.as_i64().and_then(difficulty_name).map(|s| s.to_string()),
+        None => None,
+    };
+
+    Ok(WorldMetadata {
+        display_name: string("LevelName"),
+        game_mode: data.get("GameType").and_then(|t| t.as_i64()).and_then(game_mode_name).map(|s| s.to_string()),
+        hardcore: flag("hardcore"),
+        difficulty,
+        last_played: data.get("LastPlayed").and_then(|t| t.as_i64()),
+        version_name: string("Version/Name"),
+        data_version: data.get("DataVersion").and_then(|t| t.as_i64()).map(|v| v as i32),
+        seed,
+        cheats: flag("allowCommands"),
+    })
+}
+
+/// Bytes que ocupan los archivos de una carpeta, recursivamente
+pub fn directory_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// El juego mantiene bloqueado `session.lock` mientras el mundo está abierto
+pub fn is_world_in_use(world_dir: &Path) -> bool {
+    let lock = world_dir.join("session.lock");
+    lock.exists() && session_lock_held(&lock)
+}
+
+/// Java usa bloqueos fcntl: se pregunta si otro proceso tiene uno sobre todo el archivo
+#[cfg(unix)]
+fn session_lock_held(lock: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+    let Ok(file) = std::fs::File::open(lock) else { return false };
+    let mut query: libc::flock = unsafe { std::mem::zeroed() };
+    query.l_type = libc::F_WRLCK as _;
+    query.l_whence = libc::SEEK_SET as _;
+    let result = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut query) };
+    result == 0 && query.l_type != libc::F_UNLCK as _
+}
+
+/// En Windows el bloqueo es obligatorio: leer el archivo falla mientras el juego lo tiene
+#[cfg(not(unix))]
+fn session_lock_held(lock: &Path) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    match std::fs::read(lock) {
+        Ok(_) => false,
+        Err(e) => matches!(e.raw_os_error(), Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)),
+    }
+}
+
+/// Un nombre de carpeta de mundo sin separadores ni `..`
+pub fn validate_world_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(format!("Invalid world name: {}", name));
+    }
+    Ok(())
+}
+
+/// Comprime mundos de `saves_dir` en un zip con cada mundo como carpeta de primer nivel. `session.lock`
+/// no se incluye. `on_progress` recibe (archivos hechos, total, archivo actual). Devuelve el tamaño del zip
+pub fn write_worlds_zip(
+    zip_path: &Path,
+    saves_dir: &Path,
+    worlds: &[String],
+    mut on_progress: impl FnMut(usize, usize, &str),
+) -> Result<u64, String> {
+    let mut files = Vec::new();
+    for world in worlds {
+        for entry in walkdir::WalkDir::new(saves_dir.join(world)) {
+            let entry = entry.map_err(|e| format!("Failed to read world {}: {}", world, e))?;
+            if entry.file_name() == "session.lock" {
+                continue;
+            }
+            let rel = entry.path().strip_prefix(saves_dir).map_err(|e| e.to_string())?;
+            let name = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.push((entry.path().to_path_buf(), name, entry.file_type().is_dir()));
+        }
+    }
+
+    let file = std::fs::File::create(zip_path).map_err(|e| format!("Failed to create {}: {}", zip_path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let total = files.len();
+    for (done, (path, name, is_dir)) in files.iter().enumerate() {
+        if *is_dir {
+            zip.add_directory(name.as_str(), options).map_err(|e| format!("Zip write failed: {}", e))?;
+        } else {
+            zip.start_file(name.as_str(), options).map_err(|e| format!("Zip write failed: {}", e))?;
+            let mut source = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            std::io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to compress {}: {}", path.display(), e))?;
+        }
+        on_progress(done + 1, total, name);
+    }
+    zip.finish().map_err(|e| format!("Zip write failed: {}", e))?;
+    std::fs::metadata(zip_path).map(|m| m.len()).map_err(|e| e.to_string())
+}
+
+/// Quita caracteres que no valen en nombres de carpeta en Windows
+fn sanitize_folder_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').to_string();
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." { "World".to_string() } else { cleaned }
+}
+
+/// `name`, o `name (2)`, `name (3)`... si ya existe en `dir`
+fn unique_name(dir: &Path, name: &str, extension: &str) -> String {
+    let candidate = |n: &str| if extension.is_empty() { n.to_string() } else { format!("{}.{}", n, extension) };
+    let mut result = candidate(name);
+    let mut index = 2;
+    while dir.join(&result).exists() {
+        result = candidate(&format!("{} ({})", name, index));
+        index += 1;
+    }
+    result
+}
+
+fn export_world_blocking(instance_id: &str, world: &str, target_dir: &Path) -> Result<PathBuf, String> {
+    crate::utils::validate_id("instance", instance_id)?;
+    validate_world_name(world)?;
+    let saves_dir = crate::local_instances::get_instance_directory_smart(instance_id).join("saves");
+    let world_dir = saves_dir.join(world);
+    if !world_dir.join(LEVEL_DAT).exists() {
+        return Err(format!("World {} not found", world));
+    }
+    if is_world_in_use(&world_dir) {
+        return Err(format!("World {} is open in the game (session.lock is held), close it before exporting", world));
+    }
+    std::fs::create_dir_all(target_dir).map_err(|e| format!("Failed to create {}: {}", target_dir.display(), e))?;
+
+    let zip_path = target_dir.join(unique_name(target_dir, &sanitize_folder_name(world), "zip"));
+    let part = zip_path.with_extension("zip.part");
+    if let Err(e) = write_worlds_zip(&part, &saves_dir, &[world.to_string()], |_, _, _| {}) {
+        let _ = std::fs::remove_file(&part);
+        return Err(e);
+    }
+    std::fs::rename(&part, &zip_path).map_err(|e| format!("Failed to finish export: {}", e))?;
+    log::info!("Exported world {} of {} to {}", world, instance_id, zip_path.display());
+    Ok(zip_path)
+}
+
+/// Carpeta del zip que contiene el `level.dat` menos profundo (vacía si está en la raíz)
+fn find_world_root(zip: &mut zip::ZipArchive<std::fs::File>) -> Result<(PathBuf, usize), String> {
+    let mut best: Option<(PathBuf, usize)> = None;
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
+        let Some(path) = entry.enclosed_name().map(|p| p.to_path_buf()) else { continue };
+        // Los zips hechos en macOS traen copias con metadatos que no son mundos
+        if path.starts_with("__MACOSX") || path.file_name().is_none_or(|n| n != LEVEL_DAT) {
+            continue;
+        }
+        let root = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        if best.as_ref().is_none_or(|(current, _)| root.components().count() < current.components().count()) {
+            best = Some((root, i));
+        }
+    }
+    best.ok_or_else(|| "The archive does not contain a level.dat".to_string())
+}
+
+/// Extrae lo que cuelga de `root` en `target`, sin `session.lock`
+fn extract_world(zip: &mut zip::ZipArchive<std::fs::File>, root: &Path, target: &Path) -> Result<(), String> {
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| format!("Zip index failed: {}", e))?;
+        let Some(path) = entry.enclosed_name().map(|p| p.to_path_buf()) else { continue };
+        let Ok(rel) = path.strip_prefix(root) else { continue };
+        if rel.as_os_str().is_empty() || rel.file_name().is_some_and(|n| n == "session.lock") {
+            continue;
+        }
+        let outpath = target.join(rel);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&outpath).map_err(|e| format!("Create dir failed: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = outpath.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Create parent failed: {}", e))?;
+        }
+        let mut outfile = std::fs::File::create(&outpath).map_err(|e| format!("Create file failed: {}", e))?;
+        std::io::copy(&mut entry, &mut outfile).map_err(|e| format!("Write file failed: {}", e))?;
+    }
+    Ok(())
+}
+
+fn import_world_blocking(instance_id: &str, zip_path: &Path) -> Result<String, String> {
+    crate::utils::validate_id("instance", instance_id)?;
+    let instance_dir = crate::local_instances::get_instance_directory_smart(instance_id);
+    if !instance_dir.exists() {
+        return Err(format!("Instance {} not found", instance_id));
+    }
+    let file = std::fs::File::open(zip_path).map_err(|e| format!("Failed to open {}: {}", zip_path.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read {}: {}", zip_path.display(), e))?;
+    let (root, level_index) = find_world_root(&mut zip)?;
+
+    // Un level.dat ilegible es un mundo que el juego no podrá abrir: se rechaza antes de copiar nada
+    let mut level_dat = Vec::new();
+    {
+        use std::io::Read;
+        let mut entry = zip.by_index(level_index).map_err(|e| format!("Zip index failed: {}", e))?;
+        entry.read_to_end(&mut level_dat).map_err(|e| format!("Failed to read level.dat: {}", e))?;
+    }
+    let (_, level) = crate::nbt::read_bytes(&level_dat).map_err(|e| format!("Invalid level.dat: {}", e))?;
+    if level.get("Data").and_then(|d| d.as_compound()).is_none() {
+        return Err("Invalid level.dat: missing Data compound".to_string());
+    }
+
+    // Nombre de la carpeta del zip; si level.dat está en la raíz, el del propio zip
+    let base_name = root
+        .file_name()
+        .or_else(|| zip_path.file_stem())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "World".to_string());
+    let saves_dir = instance_dir.join("saves");
+    std::fs::create_dir_all(&saves_dir).map_err(|e| format!("Failed to create saves directory: {}", e))?;
+    let world_name = unique_name(&saves_dir, &sanitize_folder_name(&base_name), "");
+
+    let staging = saves_dir.join(format!(".import-{}", chrono::Utc::now().timestamp_millis()));
+    if let Err(e) = extract_world(&mut zip, &root, &staging).and_then(|()| {
+        std::fs::rename(&staging, saves_dir.join(&world_name)).map_err(|e| format!("Failed to place world: {}", e))
+    }) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    log::info!("Imported world {} into {} from {}", world_name, instance_id, zip_path.display());
+    Ok(world_name)
+}
+
+/// Exporta un mundo como zip con la carpeta del mundo en la raíz. Sin `target_dir` se deja en Descargas.
+/// Devuelve la ruta del zip
+#[tauri::command]
+pub async fn export_world(instance_id: String, world: String, target_dir: Option<String>) -> Result<String, String> {
+    let target_dir = target_dir
+        .map(PathBuf::from)
+        .or_else(dirs::download_dir)
+        .unwrap_or_else(|| crate::paths::data_root().join("exports"));
+    let zip_path = tauri::async_runtime::spawn_blocking(move || export_world_blocking(&instance_id, &world, &target_dir))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))??;
+    Ok(zip_path.to_string_lossy().to_string())
+}
+
+/// Importa un mundo desde un zip (la carpeta del mundo puede estar anidada). Devuelve el nombre de la carpeta creada en `saves/`
+#[tauri::command]
+pub async fn import_world(instance_id: String, zip_path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || import_world_blocking(&instance_id, Path::new(&zip_path)))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_keeps_the_full_seed() {
+        let dir = crate::test_fixtures::temp_dir("worlds-metadata");
+        std::fs::copy(Path::new(crate::test_fixtures::FIXTURES).join("nbt").join(LEVEL_DAT), dir.join(LEVEL_DAT)).unwrap();
+
+        let metadata = read_world_metadata(&dir).unwrap();
+
+        assert_eq!(metadata.seed.as_deref(), Some("-4172144997902289642"));
+        assert_eq!(serde_json::to_value(&metadata).unwrap()["seed"], "-4172144997902289642");
+        assert_eq!(metadata.display_name.as_deref(), Some("Nuevo mundo"));
+        assert_eq!(metadata.game_mode.as_deref(), Some("survival"));
+        assert_eq!(metadata.difficulty.as_deref(), Some("normal"));
+        assert_eq!(metadata.version_name.as_deref(), Some("1.20.1"));
+        assert_eq!(metadata.data_version, Some(3465));
+        assert!(metadata.cheats && !metadata.hardcore);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn export_and_import_reject_unsafe_instance_ids() {
+        let target = std::env::temp_dir();
+        for id in ["..", "../other", "a/b"] {
+            let expected = format!("Invalid instance id: {}", id);
+            assert_eq!(export_world_blocking(id, "world", &target).unwrap_err(), expected);
+            assert_eq!(import_world_blocking(id, Path::new("world.zip")).unwrap_err(), expected);
+        }
+    }
+}
@@ -9,6 +9,16 @@ interface MinecraftWorld {
   name: string;
   path: string;
   icon_path: string | null;
+  display_name?: string | null;
+  game_mode?: string | null;
+  hardcore?: boolean;
+  difficulty?: string | null;
+  last_played?: number | null;
+  version_name?: string | null;
+  data_version?: number | null;
+  seed?: string | null;
+  cheats?: boolean;
+  size_bytes: number;
 }
 
 interface RemoteInstance {